@@ -0,0 +1,570 @@
+"use strict";
+
+// DISTRICT admin dashboard, talks to the regular HTTP API with the key staff log in with.
+
+const KEY_STORAGE = "district-api-key";
+const EVENT_LIMIT = 500;
+const SERVER_REFRESH_MS = 10000;
+const VERIFICATION_STATUSES = ["None", "Created", "Pending", "Success", "Full", "Expired", "Banned", "Suspended"];
+const PUNISHMENT_TYPES = ["None", "Ban", "Kick", "Mute"];
+
+const state = {
+  key: sessionStorage.getItem(KEY_STORAGE) || localStorage.getItem(KEY_STORAGE),
+  players: [],
+  punishments: [],
+  servers: [],
+  serverTimer: null,
+  events: null,
+};
+
+// MARK: Helpers
+
+const $ = (id) => document.getElementById(id);
+
+/** Creates an element, children are appended as text unless they are nodes */
+function el(tag, attrs = {}, ...children) {
+  const node = document.createElement(tag);
+  for (const [name, value] of Object.entries(attrs)) {
+    if (name.startsWith("on")) {
+      node.addEventListener(name.slice(2), value);
+    } else if (value !== false && value !== null && value !== undefined) {
+      node.setAttribute(name, value === true ? "" : value);
+    }
+  }
+  for (const child of children.flat()) {
+    if (child !== null && child !== undefined) {
+      node.append(child instanceof Node ? child : String(child));
+    }
+  }
+  return node;
+}
+
+/**
+ * Player IDs don't fit in a JS number, so long integers are read as strings.
+ * String literals are matched as a whole, digits inside them are left alone
+ */
+function parseJson(text) {
+  return JSON.parse(
+    text.replace(/"(?:[^"\\]|\\.)*"|-?\d+(?:\.\d+)?(?:[eE][+-]?\d+)?/g, (token) =>
+      /^-?\d{16,}$/.test(token) ? `"${token}"` : token,
+    ),
+  );
+}
+
+/** Writes ID fields back as plain numbers, see `parseJson` */
+function jsonBody(data) {
+  return JSON.stringify(data).replace(/"(player_id)":"(\d+)"/g, '"$1":$2');
+}
+
+function formatDate(value) {
+  return value ? new Date(value).toLocaleString() : "-";
+}
+
+function notify(message, ok = false) {
+  const notice = $("notice");
+  notice.textContent = message;
+  notice.classList.toggle("ok", ok);
+  notice.classList.remove("hidden");
+  clearTimeout(notify.timer);
+  notify.timer = setTimeout(() => notice.classList.add("hidden"), 6000);
+}
+
+class ApiError extends Error {
+  constructor(status, message) {
+    super(message);
+    this.status = status;
+  }
+}
+
+async function api(method, path, body) {
+  const headers = { Authorization: `Bearer ${state.key}` };
+  if (body !== undefined) {
+    headers["Content-Type"] = "application/json";
+  }
+  const response = await fetch(path, { method, headers, body: body === undefined ? undefined : jsonBody(body) });
+  const text = await response.text();
+  const data = text ? parseJson(text) : null;
+  if (!response.ok) {
+    throw new ApiError(response.status, data?.message || `${response.status} ${response.statusText}`);
+  }
+  return data;
+}
+
+/** Runs a request for a button, reporting failures instead of throwing */
+async function run(action, successMessage) {
+  try {
+    const result = await action();
+    if (successMessage) {
+      notify(successMessage, true);
+    }
+    return result;
+  } catch (e) {
+    if (e.status === 401) {
+      logout("Your key was rejected, log in again.");
+    } else {
+      notify(e.message);
+    }
+    return undefined;
+  }
+}
+
+// MARK: Login
+
+async function login(event) {
+  event.preventDefault();
+  const key = $("login-key").value.trim();
+  state.key = key;
+  try {
+    await api("GET", "/api/v1/servers");
+  } catch (e) {
+    // Keys without `servers:read` are still valid
+    if (e.status === 401 || e.status === 429) {
+      $("login-error").textContent = e.message;
+      state.key = null;
+      return;
+    }
+  }
+  (($("login-remember").checked && localStorage) || sessionStorage).setItem(KEY_STORAGE, key);
+  $("login-key").value = "";
+  $("login-error").textContent = "";
+  start();
+}
+
+function logout(message) {
+  sessionStorage.removeItem(KEY_STORAGE);
+  localStorage.removeItem(KEY_STORAGE);
+  state.key = null;
+  stopEvents();
+  clearInterval(state.serverTimer);
+  $("app").classList.add("hidden");
+  $("login").classList.remove("hidden");
+  $("login-error").textContent = typeof message === "string" ? message : "";
+}
+
+function start() {
+  $("login").classList.add("hidden");
+  $("app").classList.remove("hidden");
+  showTab("servers");
+}
+
+function showTab(name) {
+  for (const button of $("tabs").querySelectorAll("button")) {
+    button.classList.toggle("active", button.dataset.tab === name);
+  }
+  for (const tab of document.querySelectorAll(".tab")) {
+    tab.classList.toggle("hidden", tab.id !== `tab-${name}`);
+  }
+  clearInterval(state.serverTimer);
+  if (name === "servers") {
+    loadServers();
+    state.serverTimer = setInterval(loadServers, SERVER_REFRESH_MS);
+  } else if (name === "players" && state.players.length === 0) {
+    loadPlayers();
+  } else if (name === "punishments") {
+    loadPunishments();
+  } else if (name === "verification") {
+    loadReviews();
+  } else if (name === "events") {
+    loadServerOptions();
+  }
+}
+
+// MARK: Servers
+
+async function loadServers() {
+  const servers = await run(() => api("GET", "/api/v1/servers"));
+  if (!servers) {
+    return;
+  }
+  state.servers = servers;
+  $("servers-list").replaceChildren(...servers.map(serverCard));
+}
+
+function serverCard(server) {
+  const input = el("input", { placeholder: "Console command", disabled: !server.websocket_connected });
+  const send = async (event) => {
+    event.preventDefault();
+    const command = input.value.trim();
+    if (!command) {
+      return;
+    }
+    const sent = await run(
+      () => api("POST", `/api/v1/servers/${server.id}/commands`, { command }),
+      `Sent '${command}' to ${server.name}`,
+    );
+    if (sent) {
+      input.value = "";
+    }
+  };
+
+  return el(
+    "div",
+    { class: "card" },
+    el("h3", {}, el("span", { class: `status-dot${server.open ? " online" : ""}` }), server.name, " ", el("span", { class: "muted" }, `#${server.id}`)),
+    el(
+      "dl",
+      {},
+      el("dt", {}, "Status"),
+      el("dd", {}, server.open ? "Open" : "Closed"),
+      el("dt", {}, "Players"),
+      el("dd", {}, `${server.player_count} / ${server.max_player_count}`),
+      el("dt", {}, "TPS"),
+      el("dd", {}, `${server.tps} / ${server.max_tps}`),
+      el("dt", {}, "Last heard"),
+      el("dd", {}, formatDate(server.last_heard)),
+      el("dt", {}, "WebSocket"),
+      el("dd", {}, server.websocket_connected ? "Connected" : "Not connected"),
+    ),
+    el("form", { class: "row", onsubmit: send }, input, el("button", { type: "submit", disabled: !server.websocket_connected }, "Send")),
+  );
+}
+
+// MARK: Players
+
+async function loadPlayers() {
+  const players = await run(() => api("GET", "/api/v1/players"));
+  if (players) {
+    state.players = players;
+    renderPlayers();
+  }
+}
+
+function renderPlayers() {
+  const query = $("players-search").value.trim().toLowerCase();
+  const matches = state.players.filter(
+    (player) =>
+      !query ||
+      [player.player_id, player.steam_id, player.discord_id, ...player.usernames, ...player.ips]
+        .filter(Boolean)
+        .some((value) => String(value).toLowerCase().includes(query)),
+  );
+  $("players-list").replaceChildren(
+    ...matches.slice(0, 200).map((player) =>
+      el(
+        "tr",
+        { class: "clickable", onclick: () => showPlayer(player.player_id) },
+        el("td", {}, player.player_id),
+        el("td", {}, player.usernames.at(-1) || "-"),
+        el("td", {}, player.steam_id),
+        el("td", {}, formatDate(player.last_join_date)),
+        el("td", {}, VERIFICATION_STATUSES[player.verified_status ?? 0]),
+      ),
+    ),
+  );
+  if (matches.length > 200) {
+    $("players-list").append(el("tr", {}, el("td", { colspan: 5, class: "muted" }, `${matches.length - 200} more, narrow the search`)));
+  }
+}
+
+async function showPlayer(playerId) {
+  const detail = $("player-detail");
+  const player = await run(() => api("GET", `/api/v1/players/${playerId}`));
+  if (!player) {
+    return;
+  }
+  const [punishments, verification] = await Promise.all([
+    api("GET", `/api/v1/players/${playerId}/punishments`).catch(() => null),
+    api("GET", `/api/v1/players/${playerId}/verification`).catch(() => null),
+  ]);
+
+  detail.replaceChildren(
+    el("div", { class: "toolbar" }, el("h2", {}, player.usernames.at(-1) || player.steam_id), el("button", { class: "secondary", onclick: () => detail.classList.add("hidden") }, "Close")),
+    el(
+      "dl",
+      {},
+      el("dt", {}, "Player ID"),
+      el("dd", {}, player.player_id),
+      el("dt", {}, "User ID"),
+      el("dd", {}, player.steam_id),
+      el("dt", {}, "Usernames"),
+      el("dd", {}, player.usernames.join(", ")),
+      el("dt", {}, "IPs"),
+      el("dd", {}, player.ips.join(", ")),
+      el("dt", {}, "Joined"),
+      el("dd", {}, `${player.times_joined} times, first ${formatDate(player.first_join_date)}, last ${formatDate(player.last_join_date)}`),
+      el("dt", {}, "Hours played"),
+      el("dd", {}, player.hours_played.toFixed(1)),
+      el("dt", {}, "Discord ID"),
+      el("dd", {}, player.discord_id || "-"),
+      el("dt", {}, "Do not track"),
+      el("dd", {}, player.do_not_track ? "Yes" : "No"),
+    ),
+    el("h3", {}, "Punishments"),
+    punishments === null ? el("p", { class: "muted" }, "Not allowed to read punishments") : punishmentTable(punishments, () => showPlayer(playerId)),
+    el("h3", {}, "Verification"),
+    verification === null ? el("p", { class: "muted" }, "Not allowed to read verification") : verificationForm(player, verification),
+  );
+  detail.classList.remove("hidden");
+}
+
+function verificationForm(player, verification) {
+  const status = el("select", {}, ...VERIFICATION_STATUSES.map((name, index) => el("option", { value: index, selected: index === (verification.verified_status ?? 0) }, name)));
+  const discordId = el("input", { placeholder: "Discord ID", value: verification.discord_id || "" });
+  const save = async (event) => {
+    event.preventDefault();
+    const saved = await run(
+      () =>
+        api("POST", "/api/v1/verifications/status", {
+          player_id: player.player_id,
+          steam_id: player.steam_id,
+          discord_id: discordId.value.trim() || null,
+          verified_status: Number(status.value),
+          code: verification.verification_key,
+        }),
+      "Verification updated",
+    );
+    if (saved !== undefined) {
+      showPlayer(player.player_id);
+    }
+  };
+
+  return el(
+    "div",
+    {},
+    el(
+      "dl",
+      {},
+      el("dt", {}, "Status"),
+      el("dd", {}, VERIFICATION_STATUSES[verification.verified_status ?? 0]),
+      el("dt", {}, "Verified"),
+      el("dd", {}, verification.is_considered_verified ? `Yes, ${formatDate(verification.verified_date)}` : "No"),
+    ),
+    el("form", { class: "row", onsubmit: save }, status, discordId, el("button", { type: "submit" }, "Save")),
+  );
+}
+
+// MARK: Punishments
+
+async function loadPunishments() {
+  const punishments = await run(() => api("GET", "/api/v1/punishments"));
+  if (punishments) {
+    state.punishments = punishments.sort((a, b) => b.punishment_created_at.localeCompare(a.punishment_created_at));
+    renderPunishments();
+  }
+}
+
+function renderPunishments() {
+  const query = $("punishments-search").value.trim().toLowerCase();
+  const matches = state.punishments.filter(
+    (punishment) =>
+      !query ||
+      [punishment.username, punishment.steam_id, punishment.reason, punishment.issuer_name, punishment.issuer_steam_id]
+        .some((value) => String(value).toLowerCase().includes(query)),
+  );
+  $("punishments-list").replaceChildren(...punishmentRows(matches, loadPunishments));
+}
+
+function punishmentTable(punishments, reload) {
+  if (punishments.length === 0) {
+    return el("p", { class: "muted" }, "None");
+  }
+  return el(
+    "table",
+    {},
+    el("thead", {}, el("tr", {}, ["Created", "Type", "Player", "Reason", "Duration", "Issuer", ""].map((title) => el("th", {}, title)))),
+    el("tbody", {}, punishmentRows(punishments, reload)),
+  );
+}
+
+function punishmentRows(punishments, reload) {
+  return punishments.map((punishment) => {
+    const revoke = async () => {
+      if (!confirm(`Revoke ${PUNISHMENT_TYPES[punishment.punishment_type]} of ${punishment.username}?`)) {
+        return;
+      }
+      const revoked = await run(() => api("DELETE", `/api/v1/punishments/${encodeURIComponent(punishment.punishment_id)}`), "Punishment revoked");
+      if (revoked) {
+        state.punishments = state.punishments.filter((val) => val.punishment_id !== punishment.punishment_id);
+        reload();
+      }
+    };
+    return el(
+      "tr",
+      {},
+      el("td", {}, formatDate(punishment.punishment_created_at)),
+      el("td", {}, PUNISHMENT_TYPES[punishment.punishment_type]),
+      el("td", {}, punishment.username, el("br"), el("span", { class: "muted" }, punishment.steam_id)),
+      el("td", {}, punishment.reason),
+      el("td", {}, punishment.punishment_duration ? `${punishment.punishment_duration} s` : "-"),
+      el("td", {}, punishment.issuer_name),
+      el("td", {}, el("button", { class: "danger", onclick: revoke }, "Revoke")),
+    );
+  });
+}
+
+// MARK: Verification
+
+async function loadReviews() {
+  const reviews = await run(() => api("GET", "/api/v1/verifications/reviews"));
+  if (!reviews) {
+    return;
+  }
+  $("reviews-list").replaceChildren(
+    ...reviews.map((review) =>
+      el(
+        "tr",
+        {
+          class: "clickable",
+          onclick: () => {
+            showTab("players");
+            showPlayer(review.player_id);
+          },
+        },
+        el("td", {}, review.review_id),
+        el("td", {}, formatDate(review.created_at)),
+        el("td", {}, review.player_id),
+        el("td", {}, review.discord_id),
+        el("td", {}, review.reasons.join(", ")),
+      ),
+    ),
+  );
+  if (reviews.length === 0) {
+    $("reviews-list").append(el("tr", {}, el("td", { colspan: 5, class: "muted" }, "Nothing to review")));
+  }
+}
+
+// MARK: Live log
+
+async function loadServerOptions() {
+  if (state.servers.length === 0) {
+    state.servers = (await run(() => api("GET", "/api/v1/servers"))) || [];
+  }
+  const select = $("events-server");
+  const selected = select.value;
+  select.replaceChildren(el("option", { value: "" }, "All servers"), ...state.servers.map((server) => el("option", { value: server.id, selected: String(server.id) === selected }, server.name)));
+}
+
+function describeEvent(event) {
+  const data = event.data || {};
+  switch (event.kind) {
+    case "log":
+      return data.message;
+    case "server_status":
+      return `${data.open ? "open" : "closed"}, ${data.player_count}/${data.max_player_count} players, ${data.tps} TPS`;
+    case "player_join":
+      return `${data.username} (${data.steam_id}) joined`;
+    default:
+      return JSON.stringify(data);
+  }
+}
+
+function addEvent(event) {
+  const server = state.servers.find((val) => String(val.id) === String(event.server_id));
+  const list = $("events-list");
+  list.prepend(
+    el(
+      "li",
+      {},
+      el("span", { class: "muted" }, formatDate(event.timestamp)),
+      el("span", { class: "kind" }, server ? `[${server.name}] ${event.kind}` : event.kind),
+      describeEvent(event),
+    ),
+  );
+  while (list.childElementCount > EVENT_LIMIT) {
+    list.lastElementChild.remove();
+  }
+}
+
+/** Reads the event stream with `fetch`, `EventSource` can't send the key */
+async function streamEvents(controller, lastEventId) {
+  const params = new URLSearchParams();
+  if ($("events-server").value) {
+    params.set("server_id", $("events-server").value);
+  }
+  if ($("events-types").value) {
+    params.set("types", $("events-types").value);
+  }
+  const headers = { Authorization: `Bearer ${state.key}` };
+  if (lastEventId) {
+    headers["Last-Event-ID"] = lastEventId;
+  }
+
+  const response = await fetch(`/api/v1/events?${params}`, { headers, signal: controller.signal });
+  if (!response.ok) {
+    const text = await response.text();
+    throw new ApiError(response.status, (text && parseJson(text).message) || response.statusText);
+  }
+  const reader = response.body.pipeThrough(new TextDecoderStream()).getReader();
+  let buffer = "";
+  for (;;) {
+    const { value, done } = await reader.read();
+    if (done) {
+      return lastEventId;
+    }
+    buffer += value;
+    const messages = buffer.split("\n\n");
+    buffer = messages.pop();
+    for (const message of messages) {
+      const data = message
+        .split("\n")
+        .filter((line) => line.startsWith("data:"))
+        .map((line) => line.slice(5).trimStart())
+        .join("\n");
+      const id = message.split("\n").find((line) => line.startsWith("id:"));
+      if (id) {
+        lastEventId = id.slice(3).trim();
+      }
+      if (data) {
+        addEvent(parseJson(data));
+      }
+    }
+  }
+}
+
+async function startEvents() {
+  const controller = new AbortController();
+  state.events = controller;
+  $("events-toggle").textContent = "Disconnect";
+  let lastEventId = null;
+  while (state.events === controller) {
+    try {
+      lastEventId = await streamEvents(controller, lastEventId);
+    } catch (e) {
+      if (controller.signal.aborted) {
+        return;
+      }
+      if (e.status) {
+        notify(e.message);
+        stopEvents();
+        return;
+      }
+    }
+    // Reconnects after the stream dropped, resuming after the last event
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+  }
+}
+
+function stopEvents() {
+  if (state.events) {
+    state.events.abort();
+    state.events = null;
+  }
+  $("events-toggle").textContent = "Connect";
+}
+
+function restartEvents() {
+  if (state.events) {
+    stopEvents();
+    startEvents();
+  }
+}
+
+// MARK: Setup
+
+$("login-form").addEventListener("submit", login);
+$("logout").addEventListener("click", logout);
+$("tabs").addEventListener("click", (event) => event.target.dataset.tab && showTab(event.target.dataset.tab));
+$("servers-refresh").addEventListener("click", loadServers);
+$("players-refresh").addEventListener("click", loadPlayers);
+$("players-search").addEventListener("input", renderPlayers);
+$("punishments-refresh").addEventListener("click", loadPunishments);
+$("punishments-search").addEventListener("input", renderPunishments);
+$("reviews-refresh").addEventListener("click", loadReviews);
+$("events-toggle").addEventListener("click", () => (state.events ? stopEvents() : startEvents()));
+$("events-clear").addEventListener("click", () => $("events-list").replaceChildren());
+$("events-server").addEventListener("change", restartEvents);
+$("events-types").addEventListener("change", restartEvents);
+
+if (state.key) {
+  start();
+}
@@ -1,10 +1,15 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+  collections::HashMap,
+  path::PathBuf,
+  sync::{atomic::AtomicI64, Arc},
+};
 
 use rocket::request::{self, FromRequest};
 use rocket::Request;
 use tokio::sync::Mutex;
 
 use crate::application::features;
+use crate::application::features::{events::EventBus, rate_limit::RateLimiter};
 use crate::application::timer::timer_loop;
 use crate::logger::{LogLevel, Logger};
 use crate::{log_d, log_e, log_i, log_x};
@@ -18,13 +23,17 @@ pub const APPLICATION_ICON_URL: &str = "https://imgur.com/xiBYGqs.png";
 
 #[derive(Debug, Clone)]
 pub struct Application {
-  main_bot: Option<DistrictBot>,
+  pub(crate) main_bot: Option<DistrictBot>,
   pub(crate) servers: Vec<Arc<Mutex<DistrictServer>>>,
   pub(crate) config: Option<ConfigApp>,
   config_path: PathBuf,
   pub(crate) translations: Option<HashMap<String, String>>,
   pub(crate) databases: Option<Arc<Mutex<DatabaseHandler>>>,
   pub(crate) router: Option<DistrictRouter>,
+  pub(crate) rate_limiter: RateLimiter,
+  pub(crate) events: Arc<EventBus>,
+  /// Unix time of the last timer loop iteration, 0 before the first one
+  pub(crate) timer_last_tick: Arc<AtomicI64>,
 }
 
 impl Application {
@@ -37,6 +46,9 @@ impl Application {
       translations: None,
       databases: None,
       router: None,
+      rate_limiter: RateLimiter::default(),
+      events: Arc::new(EventBus::new()),
+      timer_last_tick: Arc::new(AtomicI64::new(0)),
     }
   }
 
@@ -82,13 +94,15 @@ impl Application {
       cfg.databases.clone(),
     ))));
 
+    let self_arc = Arc::new(Mutex::new(self));
+
     // Spawning district guard bot
     log_d!("Booting main bot!");
-    let mut main_bot = DistrictBot::new(cfg.main_bot, None, None);
-    main_bot.spawn(String::from("DISTRICT_MAIN")).await;
-    self.main_bot = Some(main_bot);
-
-    let self_arc = Arc::new(Mutex::new(self));
+    {
+      let mut main_bot = DistrictBot::new(cfg.main_bot, Arc::clone(&self_arc), None, None);
+      main_bot.spawn(String::from("DISTRICT_MAIN")).await;
+      self_arc.lock().await.main_bot = Some(main_bot);
+    }
 
     log_d!("Checking servers!");
     {
@@ -122,6 +136,9 @@ impl Application {
       }
     });
 
+    // Forwarding events to the configured webhooks
+    features::webhooks::spawn_dispatcher(Arc::clone(&self_arc));
+
     log_i!("App started successfully");
 
     self_arc
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
 use serenity::{
   all::{Context, GatewayIntents, GuildId, PresenceData},
@@ -6,19 +6,21 @@ use serenity::{
 };
 use tokio::sync::Mutex;
 
+use crate::{application::server::server::DistrictServer, log_d, log_e, log_x};
 use crate::{
-  application::config::bots::BotConfig,
+  application::{application::Application, config::bots::BotConfig},
   logger::{LogLevel, Logger},
 };
-use crate::{application::server::server::DistrictServer, log_d, log_e, log_x};
 
 #[derive(Debug, Clone)]
 pub struct DistrictBot {
+  /// Weak, the application owns its bots
+  app: Weak<Mutex<Application>>,
   pub(super) server: Option<Arc<Mutex<DistrictServer>>>,
   pub(crate) token: String,
   pub(crate) presence: Option<PresenceData>,
   pub(crate) active_presence: Option<PresenceData>,
-  pub(super) operational_guild: GuildId,
+  pub(crate) operational_guild: GuildId,
   pub(crate) ctx_manager: Arc<ContextManager>,
   pub(crate) bot_config: BotConfig,
 }
@@ -26,6 +28,7 @@ pub struct DistrictBot {
 impl DistrictBot {
   pub fn new(
     bot_cfg: BotConfig,
+    app: Arc<Mutex<Application>>,
     server: Option<Arc<Mutex<DistrictServer>>>,
     presence: Option<PresenceData>,
   ) -> Self {
@@ -44,6 +47,7 @@ impl DistrictBot {
         log_d!("Creating bot with config bot");
         return DistrictBot {
           bot_config: bot_cfg.clone(),
+          app: Arc::downgrade(&app),
           server: server,
           token: cfg.token.clone(),
           presence: presence.or(cfg.default_presence.as_ref().map(|val| val.into_presence())),
@@ -56,6 +60,7 @@ impl DistrictBot {
         log_d!("Creating bot with server bot config");
         return DistrictBot {
           bot_config: bot_cfg.clone(),
+          app: Arc::downgrade(&app),
           server: server,
           token: cfg.token.clone(),
           presence: presence.or(cfg.default_presence.as_ref().map(|val| val.into_presence())),
@@ -66,15 +71,28 @@ impl DistrictBot {
       }
     };
   }
+
+  /// Application the bot belongs to, `None` once it was dropped
+  pub(super) fn app(&self) -> Option<Arc<Mutex<Application>>> {
+    self.app.upgrade()
+  }
+
   pub async fn spawn(&mut self, server_name: String) {
     let token = self.token.clone();
-    let intents = GatewayIntents::non_privileged()
+    let mut intents = GatewayIntents::non_privileged()
       | GatewayIntents::MESSAGE_CONTENT
       | GatewayIntents::GUILDS
       | GatewayIntents::GUILD_MESSAGES
       | GatewayIntents::GUILD_MESSAGE_REACTIONS
       | GatewayIntents::DIRECT_MESSAGES
       | GatewayIntents::DIRECT_MESSAGE_REACTIONS;
+    if let Some(sync_cfg) = self.bot_config.get_verification_sync() {
+      intents |= GatewayIntents::GUILD_MODERATION;
+      if sync_cfg.on_leave {
+        // Privileged intent, has to be enabled in the Discord developer portal
+        intents |= GatewayIntents::GUILD_MEMBERS;
+      }
+    }
 
     let self_arc = self.clone();
     let mut client = match Client::builder(&token, intents)
@@ -116,6 +134,6 @@ impl ContextManager {
 
   pub async fn get_ctx(&self) -> Option<Arc<Mutex<Context>>> {
     let ctx_guard = self.ctx.lock().await;
-    Some(Arc::new(Mutex::new(ctx_guard.clone().unwrap())))
+    ctx_guard.clone().map(|ctx| Arc::new(Mutex::new(ctx)))
   }
 }
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use serenity::all::{
+  Colour, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+  CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, Permissions,
+  ResolvedOption, ResolvedValue,
+};
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::{self, Application},
+  db::audit::structs::AuditActor,
+  features::audit::{self, actions},
+  features::verification::{on_discord_user_changed, on_verification_changed},
+  server::server::DistrictServer,
+};
+
+use super::CommandHandler;
+
+pub struct AccountsCommand;
+
+impl CommandHandler for AccountsCommand {
+  // MARK: Command handler
+  async fn handle(
+    &self,
+    command: &CommandInteraction,
+    ctx: &Context,
+    app: &Arc<Mutex<Application>>,
+    _server: Option<&Arc<Mutex<DistrictServer>>>,
+  ) -> Result<(), String> {
+    let discord_id = command.user.id.to_string();
+    let options = command.data.options();
+    let (subcommand, sub_options) = match options.first() {
+      Some(ResolvedOption {
+        name,
+        value: ResolvedValue::SubCommand(sub_options),
+        ..
+      }) => (*name, sub_options),
+      _ => return Err(String::from("Unknown subcommand")),
+    };
+    let player_id = sub_options
+      .iter()
+      .find(|option| option.name == "player_id")
+      .and_then(|option| match option.value {
+        ResolvedValue::String(val) => val.trim().parse::<u64>().ok(),
+        _ => None,
+      });
+
+    let embed = {
+      let app_lock = app.lock().await;
+      let db_lock = app_lock.databases.as_ref().ok_or("No databases loaded")?;
+      let mut db_handler = db_lock.lock().await;
+      let before = db_handler
+        .player_database
+        .get_account_links(&discord_id)
+        .map_err(|e| e.to_string())?;
+      let db = &mut db_handler.player_database;
+
+      let action = match (subcommand, player_id) {
+        ("list", _) => None,
+        ("primary", Some(player_id)) => {
+          db.set_primary_account(&discord_id, player_id)
+            .map_err(|_| String::from("This account is not linked to you"))?;
+          Some(actions::LINK_PRIMARY)
+        }
+        ("unlink", Some(player_id)) => {
+          db.unlink_account(&discord_id, player_id)
+            .map_err(|_| String::from("This account is not linked to you"))?;
+          Some(actions::LINK_UNLINK)
+        }
+        _ => return Err(String::from("Invalid player ID")),
+      };
+
+      let links = db
+        .get_account_links(&discord_id)
+        .map_err(|e| e.to_string())?;
+      if let Some(action) = action {
+        audit::record(
+          &db_handler,
+          &AuditActor::discord(&discord_id),
+          action,
+          audit::target("discord", &discord_id),
+          &before,
+          &links,
+        );
+      }
+      let db = &db_handler.player_database;
+      let description = links
+        .iter()
+        .map(|link| {
+          let player = db.get_player_by_id(link.player_id).ok();
+          format!(
+            "- **{}** {} _{}_{}",
+            link.player_id,
+            player.as_ref().map_or("", |val| val.steam_id.as_str()),
+            player
+              .as_ref()
+              .and_then(|val| val.usernames.last().cloned())
+              .unwrap_or_default(),
+            if link.is_primary { " (primary)" } else { "" }
+          )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+      CreateEmbed::new()
+        .title("Linked accounts")
+        .thumbnail(application::APPLICATION_ICON_URL)
+        .color(Colour::from_rgb(152, 212, 245))
+        .description(if description.is_empty() {
+          String::from("You have no linked accounts.")
+        } else {
+          description
+        })
+    };
+
+    match (subcommand, player_id) {
+      ("unlink", Some(player_id)) => {
+        on_verification_changed(app, player_id);
+        on_discord_user_changed(app, discord_id);
+      }
+      ("primary", Some(_)) => on_discord_user_changed(app, discord_id),
+      _ => {}
+    }
+
+    command
+      .create_response(
+        &ctx.http,
+        CreateInteractionResponse::Message(
+          CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(embed),
+        ),
+      )
+      .await
+      .map_err(|e| e.to_string())
+  }
+
+  // MARK: Command registration
+  fn register(&self, permissions: Option<u64>) -> serenity::all::CreateCommand {
+    let player_option = || {
+      CreateCommandOption::new(
+        CommandOptionType::String,
+        "player_id",
+        "DISTRICT player ID of the account",
+      )
+      .required(true)
+    };
+
+    CreateCommand::new("accounts")
+      .description("Manage game accounts linked to your Discord account")
+      .add_option(CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "list",
+        "Lists your linked accounts",
+      ))
+      .add_option(
+        CreateCommandOption::new(
+          CommandOptionType::SubCommand,
+          "primary",
+          "Sets your primary account",
+        )
+        .add_sub_option(player_option()),
+      )
+      .add_option(
+        CreateCommandOption::new(
+          CommandOptionType::SubCommand,
+          "unlink",
+          "Unlinks account from your Discord account",
+        )
+        .add_sub_option(player_option()),
+      )
+      .default_member_permissions(Permissions::from_bits_truncate(
+        permissions.unwrap_or(Permissions::empty().bits()),
+      ))
+  }
+}
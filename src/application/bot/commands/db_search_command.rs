@@ -7,7 +7,9 @@ use serenity::all::{
 use tokio::sync::Mutex;
 
 use crate::application::{
-  application, db::player::structs::DatabasePlayer, server::server::DistrictServer,
+  application::{self, Application},
+  db::player::structs::DatabasePlayer,
+  server::server::DistrictServer,
 };
 
 use super::CommandHandler;
@@ -20,6 +22,7 @@ impl CommandHandler for DbSearchCommand {
     &self,
     command: &CommandInteraction,
     ctx: &Context,
+    _app: &Arc<Mutex<Application>>,
     server: Option<&Arc<Mutex<DistrictServer>>>,
   ) -> Result<(), String> {
     let http = ctx.http.clone();
@@ -218,21 +221,24 @@ impl DbSearchCommand {
         .map(|val| format!("||{}||", val))
         .collect::<Vec<_>>()
         .join("\n");
+      let mut embed = CreateEmbed::new()
+        .author(
+          CreateEmbedAuthor::new(format!("Player {}", player.player_id))
+            .icon_url(application::APPLICATION_ICON_URL),
+        )
+        .title(&player.steam_id);
+      if let Some(profile_url) = player
+        .identity()
+        .and_then(|identity| identity.profile_url())
+      {
+        embed = embed.url(profile_url);
+      }
       command.edit_response(
                 cache_http,
                 EditInteractionResponse::new()
                     .content(search_input)
                     .add_embed(
-                        CreateEmbed::new()
-                            .author(
-                                CreateEmbedAuthor::new(format!("Player {}", player.player_id))
-                                    .icon_url(application::APPLICATION_ICON_URL),
-                            )
-                            .title(&player.steam_id)
-                            .url(format!(
-                                "https://steamcommunity.com/profiles/{}/",
-                                player.steam_id.clone().split_once('@').map_or("", |(first, _)| first)
-                            ))
+                        embed
                             .color(Colour::from_rgb(152, 212, 245))
                             .thumbnail(application::APPLICATION_ICON_URL)
                             .description(
@@ -6,7 +6,7 @@ use serenity::all::{
 };
 use tokio::sync::Mutex;
 
-use crate::application::server::server::DistrictServer;
+use crate::application::{application::Application, server::server::DistrictServer};
 
 use super::CommandHandler;
 
@@ -17,6 +17,7 @@ impl CommandHandler for InfoCommand {
     &self,
     command: &CommandInteraction,
     ctx: &Context,
+    _app: &Arc<Mutex<Application>>,
     server: Option<&Arc<Mutex<DistrictServer>>>,
   ) -> Result<(), String> {
     let http = ctx.http.clone();
@@ -3,17 +3,20 @@ use std::sync::Arc;
 use serenity::all::{CommandInteraction, Context, CreateCommand};
 use tokio::sync::Mutex;
 
-use crate::application::server::server::DistrictServer;
+use crate::application::{application::Application, server::server::DistrictServer};
 
+pub(crate) mod accounts_command;
 pub(crate) mod db_search_command;
 pub(crate) mod info_command;
 pub(crate) mod send_command;
+pub(crate) mod uptime_command;
 
 pub trait CommandHandler: Send {
     fn handle(
         &self,
         command: &CommandInteraction,
         ctx: &Context,
+        app: &Arc<Mutex<Application>>,
         server: Option<&Arc<Mutex<DistrictServer>>>,
     ) -> impl std::future::Future<Output = Result<(), String>> + Send;
     fn register(&self, permissions: Option<u64>) -> CreateCommand;
@@ -23,6 +26,8 @@ pub enum CommandHandlerEnum {
     InfoCommand(self::info_command::InfoCommand),
     DbSearchCommand(self::db_search_command::DbSearchCommand),
     SendCommand(self::send_command::SendCommand),
+    AccountsCommand(self::accounts_command::AccountsCommand),
+    UptimeCommand(self::uptime_command::UptimeCommand),
 }
 
 impl CommandHandler for CommandHandlerEnum {
@@ -30,14 +35,19 @@ impl CommandHandler for CommandHandlerEnum {
         &self,
         command: &CommandInteraction,
         ctx: &Context,
+        app: &Arc<Mutex<Application>>,
         server: Option<&Arc<Mutex<DistrictServer>>>,
     ) -> Result<(), String> {
         match self {
-            CommandHandlerEnum::InfoCommand(handler) => handler.handle(command, ctx, server).await,
+            CommandHandlerEnum::InfoCommand(handler) => handler.handle(command, ctx, app, server).await,
             CommandHandlerEnum::DbSearchCommand(handler) => {
-                handler.handle(command, ctx, server).await
+                handler.handle(command, ctx, app, server).await
             }
-            CommandHandlerEnum::SendCommand(handler) => handler.handle(command, ctx, server).await,
+            CommandHandlerEnum::SendCommand(handler) => handler.handle(command, ctx, app, server).await,
+            CommandHandlerEnum::AccountsCommand(handler) => {
+                handler.handle(command, ctx, app, server).await
+            }
+            CommandHandlerEnum::UptimeCommand(handler) => handler.handle(command, ctx, app, server).await,
         }
     }
     fn register(&self, permissions: Option<u64>) -> CreateCommand {
@@ -45,6 +55,8 @@ impl CommandHandler for CommandHandlerEnum {
             CommandHandlerEnum::InfoCommand(handler) => handler.register(permissions),
             CommandHandlerEnum::DbSearchCommand(handler) => handler.register(permissions),
             CommandHandlerEnum::SendCommand(handler) => handler.register(permissions),
+            CommandHandlerEnum::AccountsCommand(handler) => handler.register(permissions),
+            CommandHandlerEnum::UptimeCommand(handler) => handler.register(permissions),
         }
     }
 }
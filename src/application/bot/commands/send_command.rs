@@ -7,6 +7,9 @@ use serenity::all::{
 use tokio::sync::Mutex;
 
 use crate::application::{
+  application::Application,
+  db::audit::structs::AuditActor,
+  features::audit::{self, actions},
   routes::websocket::structs::{WsCommandResponse, WsResponseCreate},
   server::server::DistrictServer,
 };
@@ -20,6 +23,7 @@ impl CommandHandler for SendCommand {
     &self,
     command: &CommandInteraction,
     ctx: &Context,
+    app: &Arc<Mutex<Application>>,
     server: Option<&Arc<Mutex<DistrictServer>>>,
   ) -> Result<(), String> {
     let http = ctx.http.clone();
@@ -36,6 +40,18 @@ impl CommandHandler for SendCommand {
         "Ok",
         Some(cmd),
       ));
+      let server_id = srv.id;
+      drop(srv);
+      if let Some(db_lock) = app.lock().await.databases.as_ref() {
+        audit::record(
+          &*db_lock.lock().await,
+          &AuditActor::discord(&command.user.id.to_string()),
+          actions::SERVER_COMMAND,
+          audit::target("server", server_id),
+          (),
+          serde_json::json!({ "command": cmd }),
+        );
+      }
       let _ = command
         .create_response(
           http,
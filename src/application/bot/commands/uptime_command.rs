@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use serenity::all::{
+  Colour, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+  CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, Permissions,
+  ResolvedValue,
+};
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::{self, Application},
+  db::status::structs::UptimeReport,
+  server::server::DistrictServer,
+};
+
+use super::CommandHandler;
+
+pub struct UptimeCommand;
+
+fn format_report(report: &UptimeReport) -> String {
+  let uptime = match report.uptime_percent {
+    Some(val) => format!("{:.2}%", val),
+    None => String::from("no data"),
+  };
+  let average_tps = match report.average_tps {
+    Some(val) => format!("{:.1}", val),
+    None => String::from("-"),
+  };
+  let average_players = match report.average_players {
+    Some(val) => format!("{:.1}", val),
+    None => String::from("-"),
+  };
+  format!(
+    "Uptime: **{}**\nDowntime: {} min in {} incident(s)\nAverage TPS: {}\nAverage players: {} (peak {})",
+    uptime,
+    report.downtime_secs / 60,
+    report.incidents,
+    average_tps,
+    average_players,
+    report.peak_players.unwrap_or(0)
+  )
+}
+
+impl CommandHandler for UptimeCommand {
+  // MARK: Command handler
+  async fn handle(
+    &self,
+    command: &CommandInteraction,
+    ctx: &Context,
+    app: &Arc<Mutex<Application>>,
+    server: Option<&Arc<Mutex<DistrictServer>>>,
+  ) -> Result<(), String> {
+    let options = command.data.options();
+    let days = options
+      .iter()
+      .find(|option| option.name == "days")
+      .and_then(|option| match option.value {
+        ResolvedValue::Integer(val) => Some(val),
+        _ => None,
+      })
+      .unwrap_or(1);
+    let server_id = options
+      .iter()
+      .find(|option| option.name == "server_id")
+      .and_then(|option| match option.value {
+        ResolvedValue::Integer(val) => u64::try_from(val).ok(),
+        _ => None,
+      });
+    // Server bots report their own server by default, the main bot every server
+    let server_id = match (server_id, server) {
+      (Some(val), _) => Some(val),
+      (None, Some(server)) => Some(server.lock().await.id),
+      (None, None) => None,
+    };
+
+    let to = Utc::now().timestamp();
+    let from = to - days * 24 * 3600;
+    let embed = {
+      let app_lock = app.lock().await;
+      let mut servers = vec![];
+      for server_lock in app_lock.servers.iter() {
+        let server = server_lock.lock().await;
+        if server_id.is_none_or(|server_id| server_id == server.id) {
+          servers.push((server.id, server.name.clone()));
+        }
+      }
+      if servers.is_empty() {
+        return Err(String::from("Server not found"));
+      }
+
+      let db_lock = app_lock.databases.as_ref().ok_or("No databases loaded")?;
+      let db_handler = db_lock.lock().await;
+      let mut embed = CreateEmbed::new()
+        .title(format!(
+          "Uptime for the last {} day{}",
+          days,
+          if days == 1 { "" } else { "s" }
+        ))
+        .thumbnail(application::APPLICATION_ICON_URL)
+        .color(Colour::from_rgb(126, 212, 212));
+      for (id, name) in servers {
+        let report = db_handler
+          .status_database
+          .get_uptime_report(id, from, to, to)
+          .map_err(|e| e.to_string())?;
+        embed = embed.field(format!("{} ({})", name, id), format_report(&report), false);
+      }
+      embed
+    };
+
+    command
+      .create_response(
+        &ctx.http,
+        CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().embed(embed)),
+      )
+      .await
+      .map_err(|e| e.to_string())
+  }
+
+  // MARK: Command registration
+  fn register(&self, permissions: Option<u64>) -> serenity::all::CreateCommand {
+    CreateCommand::new("uptime")
+      .description("Shows uptime and average TPS of the servers")
+      .add_option(
+        CreateCommandOption::new(CommandOptionType::Integer, "days", "Period to report")
+          .add_int_choice("Last day", 1)
+          .add_int_choice("Last week", 7)
+          .add_int_choice("Last 30 days", 30),
+      )
+      .add_option(CreateCommandOption::new(
+        CommandOptionType::Integer,
+        "server_id",
+        "Only this server",
+      ))
+      .default_member_permissions(Permissions::from_bits_truncate(
+        permissions.unwrap_or(Permissions::empty().bits()),
+      ))
+  }
+}
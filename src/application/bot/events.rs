@@ -1,12 +1,21 @@
+use crate::application::bot::commands::accounts_command::AccountsCommand;
 use crate::application::bot::commands::db_search_command::DbSearchCommand;
 use crate::application::bot::commands::info_command::InfoCommand;
 use crate::application::bot::commands::send_command::SendCommand;
+use crate::application::bot::commands::uptime_command::UptimeCommand;
 use crate::application::bot::commands::{CommandHandler as _, CommandHandlerEnum};
 use crate::application::config::bots::BotConfig;
+use crate::application::features::verification::discord_sync::{
+  handle_guild_event, GuildMembershipEvent,
+};
+use crate::application::features::verification::review::{
+  handle_review_interaction, REVIEW_COMPONENT_PREFIX,
+};
 use crate::logger::{LogLevel, Logger};
 use crate::{log_e, log_i, log_x};
 use serenity::all::{
-  CreateInteractionResponse, CreateInteractionResponseMessage, Interaction, Ready,
+  CreateInteractionResponse, CreateInteractionResponseMessage, GuildId, Interaction, Member, Ready,
+  User,
 };
 use serenity::client::Context;
 use serenity::prelude::EventHandler;
@@ -55,22 +64,81 @@ impl EventHandler for DistrictBot {
         .map_err(|e| log_e!(e));
     }
 
+    if let Some(accounts_permissions) = match &self.bot_config {
+      BotConfig::ConfigBot(cfg) => cfg.commands.accounts_command,
+      BotConfig::ServerBotConfig(cfg) => cfg.commands.accounts_command,
+    } {
+      let _ = self
+        .operational_guild
+        .create_command(
+          &ctx.http,
+          AccountsCommand.register(Some(accounts_permissions)),
+        )
+        .await
+        .map_err(|e| log_e!(e));
+    }
+
+    if let Some(uptime_permissions) = match &self.bot_config {
+      BotConfig::ConfigBot(cfg) => cfg.commands.uptime_command,
+      BotConfig::ServerBotConfig(cfg) => cfg.commands.uptime_command,
+    } {
+      let _ = self
+        .operational_guild
+        .create_command(&ctx.http, UptimeCommand.register(Some(uptime_permissions)))
+        .await
+        .map_err(|e| log_e!(e));
+    }
+
     self.ctx_manager.set_ctx(ctx).await;
   }
   async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+    if let Interaction::Component(component) = &interaction {
+      if component
+        .data
+        .custom_id
+        .starts_with(REVIEW_COMPONENT_PREFIX)
+      {
+        let Some(app) = self.app() else {
+          return;
+        };
+        if let Err(error_msg) = handle_review_interaction(&app, &ctx, component).await {
+          let _ = component
+            .create_response(
+              &ctx.http,
+              CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                  .ephemeral(true)
+                  .content(error_msg),
+              ),
+            )
+            .await
+            .map_err(|e| log_e!(e));
+        }
+      }
+      return;
+    }
     if let Interaction::Command(command) = interaction {
       // find command handler
       let command_handler: Result<CommandHandlerEnum, String> = match command.data.name.as_str() {
         "info" => Ok(CommandHandlerEnum::InfoCommand(InfoCommand)),
         "db_search" => Ok(CommandHandlerEnum::DbSearchCommand(DbSearchCommand)),
         "send_command" => Ok(CommandHandlerEnum::SendCommand(SendCommand)),
+        "accounts" => Ok(CommandHandlerEnum::AccountsCommand(AccountsCommand)),
+        "uptime" => Ok(CommandHandlerEnum::UptimeCommand(UptimeCommand)),
         _ => return,
       };
 
+      let Some(app) = self.app() else {
+        return;
+      };
+
       // execute command handler
       match command_handler {
         Ok(handler) => {
-          if let Err(error_msg) = handler.handle(&command, &ctx, self.server.as_ref()).await {
+          if let Err(error_msg) = handler
+            .handle(&command, &ctx, &app, self.server.as_ref())
+            .await
+          {
             let _ = command
               .create_response(
                 ctx.http,
@@ -88,4 +156,45 @@ impl EventHandler for DistrictBot {
       }
     }
   }
+
+  async fn guild_ban_addition(&self, _ctx: Context, guild_id: GuildId, banned_user: User) {
+    self
+      .handle_membership_event(guild_id, GuildMembershipEvent::Ban, &banned_user)
+      .await;
+  }
+
+  async fn guild_ban_removal(&self, _ctx: Context, guild_id: GuildId, unbanned_user: User) {
+    self
+      .handle_membership_event(guild_id, GuildMembershipEvent::Unban, &unbanned_user)
+      .await;
+  }
+
+  async fn guild_member_removal(
+    &self,
+    _ctx: Context,
+    guild_id: GuildId,
+    user: User,
+    _member_data_if_available: Option<Member>,
+  ) {
+    self
+      .handle_membership_event(guild_id, GuildMembershipEvent::Leave, &user)
+      .await;
+  }
+}
+
+impl DistrictBot {
+  async fn handle_membership_event(
+    &self,
+    guild_id: GuildId,
+    event: GuildMembershipEvent,
+    user: &User,
+  ) {
+    if guild_id != self.operational_guild {
+      return;
+    }
+    let (Some(sync_cfg), Some(app)) = (self.bot_config.get_verification_sync(), self.app()) else {
+      return;
+    };
+    handle_guild_event(&app, event, user, &sync_cfg).await;
+  }
 }
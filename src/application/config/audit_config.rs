@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// # Audit log
+/// Retention of the audit log, entries are kept forever when not configured
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigAudit {
+  /// Days an entry is kept, 0 keeps them forever
+  #[serde(default = "default_retention_days")]
+  pub retention_days: u32,
+}
+
+fn default_retention_days() -> u32 {
+  365
+}
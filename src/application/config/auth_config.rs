@@ -1,8 +1,194 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::application::utils::api_key;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ConfigAuth {
+  /// Legacy token with every `db` scope
+  #[serde(default)]
   pub db: String,
+  /// Legacy token with `logs:write` and `servers:write` scopes
+  #[serde(default)]
   pub log: String,
+  /// Legacy token with `ws:connect` scope
+  #[serde(default)]
   pub ws: String,
+  pub keys: Option<Vec<ConfigApiKey>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigApiKey {
+  pub name: String,
+  /// Plaintext key, prefer `key_hash`
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub key: Option<String>,
+  /// Salted hash of the key (`sha256$<salt>$<hash>`)
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub key_hash: Option<String>,
+  pub scopes: Vec<ApiScope>,
+  /// Servers the key may act for, any server when not set
+  pub servers: Option<Vec<u64>>,
+  /// The key stops working after this date, used to retire the old key while rotating
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ConfigApiKey {
+  pub fn is_expired(&self) -> bool {
+    self
+      .expires_at
+      .is_some_and(|expires_at| expires_at <= Utc::now())
+  }
+
+  /// Checks the token against this key in constant time
+  pub fn matches(&self, token: &str) -> bool {
+    if self.is_expired() {
+      return false;
+    }
+    match (&self.key_hash, &self.key) {
+      (Some(key_hash), _) => api_key::verify_key_hash(token, key_hash),
+      (None, Some(key)) => api_key::keys_equal(token, key),
+      (None, None) => false,
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+/// # Api key scope
+// MARK: (enum) Api scope
+pub enum ApiScope {
+  #[serde(rename = "players:read")]
+  PlayersRead,
+  #[serde(rename = "players:write")]
+  PlayersWrite,
+  #[serde(rename = "punishments:read")]
+  PunishmentsRead,
+  #[serde(rename = "punishments:write")]
+  PunishmentsWrite,
+  #[serde(rename = "verification:read")]
+  VerificationRead,
+  #[serde(rename = "verification:write")]
+  VerificationWrite,
+  #[serde(rename = "leaderboards:read")]
+  LeaderboardsRead,
+  #[serde(rename = "leaderboards:write")]
+  LeaderboardsWrite,
+  #[serde(rename = "logs:write")]
+  LogsWrite,
+  #[serde(rename = "servers:read")]
+  ServersRead,
+  #[serde(rename = "servers:write")]
+  ServersWrite,
+  /// Sending console commands to game servers
+  #[serde(rename = "servers:command")]
+  ServersCommand,
+  #[serde(rename = "ws:connect")]
+  WsConnect,
+  #[serde(rename = "events:read")]
+  EventsRead,
+  #[serde(rename = "metrics:read")]
+  MetricsRead,
+  /// Every scope, including key management
+  #[serde(rename = "admin")]
+  Admin,
+}
+
+impl ApiScope {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ApiScope::PlayersRead => "players:read",
+      ApiScope::PlayersWrite => "players:write",
+      ApiScope::PunishmentsRead => "punishments:read",
+      ApiScope::PunishmentsWrite => "punishments:write",
+      ApiScope::VerificationRead => "verification:read",
+      ApiScope::VerificationWrite => "verification:write",
+      ApiScope::LeaderboardsRead => "leaderboards:read",
+      ApiScope::LeaderboardsWrite => "leaderboards:write",
+      ApiScope::LogsWrite => "logs:write",
+      ApiScope::ServersRead => "servers:read",
+      ApiScope::ServersWrite => "servers:write",
+      ApiScope::ServersCommand => "servers:command",
+      ApiScope::WsConnect => "ws:connect",
+      ApiScope::EventsRead => "events:read",
+      ApiScope::MetricsRead => "metrics:read",
+      ApiScope::Admin => "admin",
+    }
+  }
+}
+
+impl ConfigAuth {
+  /// Configured keys, including the legacy `db`, `log` and `ws` tokens when set
+  pub fn api_keys(&self) -> Vec<ConfigApiKey> {
+    let legacy = [
+      (
+        "legacy-db",
+        &self.db,
+        vec![
+          ApiScope::PlayersRead,
+          ApiScope::PlayersWrite,
+          ApiScope::PunishmentsRead,
+          ApiScope::PunishmentsWrite,
+          ApiScope::VerificationRead,
+          ApiScope::VerificationWrite,
+          ApiScope::LeaderboardsRead,
+          ApiScope::LeaderboardsWrite,
+        ],
+      ),
+      (
+        "legacy-log",
+        &self.log,
+        vec![ApiScope::LogsWrite, ApiScope::ServersWrite],
+      ),
+      ("legacy-ws", &self.ws, vec![ApiScope::WsConnect]),
+    ];
+
+    let mut keys: Vec<ConfigApiKey> = legacy
+      .into_iter()
+      .filter(|(_, key, _)| !key.is_empty())
+      .map(|(name, key, scopes)| ConfigApiKey {
+        name: name.to_string(),
+        key: Some(key.clone()),
+        key_hash: None,
+        scopes,
+        servers: None,
+        expires_at: None,
+      })
+      .collect();
+    keys.extend(self.keys.clone().unwrap_or_default());
+    keys
+  }
+
+  /// Names shared by keys that aren't a rotation of one identity. The name identifies the key
+  /// in rate limits, usage, idempotency and the audit log, so only a key that is being retired
+  /// (with `expires_at`) may share it with its successor, and both need the same scopes and servers
+  pub fn conflicting_key_names(&self) -> Vec<String> {
+    let keys = self.api_keys();
+    let mut names: Vec<String> = keys
+      .iter()
+      .filter(|key| {
+        let same_name: Vec<&ConfigApiKey> = keys.iter().filter(|other| other.name == key.name).collect();
+        same_name.len() > 1
+          && (same_name.iter().filter(|other| other.expires_at.is_none()).count() > 1
+            || same_name
+              .iter()
+              .any(|other| other.scopes != key.scopes || other.servers != key.servers))
+      })
+      .map(|key| key.name.clone())
+      .collect();
+    names.sort();
+    names.dedup();
+    names
+  }
+
+  /// Names of keys stored in plaintext
+  pub fn plaintext_keys(&self) -> Vec<String> {
+    self
+      .api_keys()
+      .into_iter()
+      .filter(|key| key.key_hash.is_none() && key.key.is_some())
+      .map(|key| key.name)
+      .collect()
+  }
 }
@@ -1,4 +1,8 @@
-use super::{commands_config::ConfigBotCommands, presence_config::PresenceConfig};
+use super::{
+  commands_config::ConfigBotCommands, presence_config::PresenceConfig,
+  verification_roles_config::ConfigVerificationRoles,
+  verification_sync_config::ConfigVerificationSync,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -7,4 +11,6 @@ pub struct ConfigBot {
   pub active_guild_id: u64,
   pub default_presence: Option<PresenceConfig>,
   pub commands: ConfigBotCommands,
+  pub verification_roles: Option<ConfigVerificationRoles>,
+  pub verification_sync: Option<ConfigVerificationSync>,
 }
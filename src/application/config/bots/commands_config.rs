@@ -5,4 +5,7 @@ pub struct ConfigBotCommands {
   pub info_command: Option<u64>,
   pub db_search: Option<u64>,
   pub send_command: Option<u64>,
+  pub accounts_command: Option<u64>,
+  #[serde(default)]
+  pub uptime_command: Option<u64>,
 }
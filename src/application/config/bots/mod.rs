@@ -2,10 +2,14 @@ use bot_config::ConfigBot;
 use serde::{Deserialize, Serialize};
 
 use super::server::server_bot_config::ServerBotConfig;
+use verification_roles_config::ConfigVerificationRoles;
+use verification_sync_config::ConfigVerificationSync;
 
 pub mod bot_config;
 pub mod commands_config;
 pub mod presence_config;
+pub mod verification_roles_config;
+pub mod verification_sync_config;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
@@ -28,4 +32,18 @@ impl BotConfig {
       BotConfig::ServerBotConfig(cfg) => cfg.active_guild_id.clone(),
     }
   }
+
+  pub fn get_verification_roles(&self) -> Option<ConfigVerificationRoles> {
+    match self {
+      BotConfig::ConfigBot(cfg) => cfg.verification_roles.clone(),
+      BotConfig::ServerBotConfig(cfg) => cfg.verification_roles.clone(),
+    }
+  }
+
+  pub fn get_verification_sync(&self) -> Option<ConfigVerificationSync> {
+    match self {
+      BotConfig::ConfigBot(cfg) => cfg.verification_sync.clone(),
+      BotConfig::ServerBotConfig(cfg) => cfg.verification_sync.clone(),
+    }
+  }
 }
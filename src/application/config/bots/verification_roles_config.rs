@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConfigVerificationRoles {
+  pub verified_role_id: Option<u64>,
+  pub unverified_role_id: Option<u64>,
+}
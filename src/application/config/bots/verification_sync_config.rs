@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigVerificationSync {
+  pub on_ban: bool,
+  pub on_unban: bool,
+  pub on_leave: bool,
+  pub create_punishment: bool,
+  pub punishment_duration: Option<u32>,
+}
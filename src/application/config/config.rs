@@ -11,13 +11,20 @@ use crate::logger::{LogLevel, Logger};
 use crate::{log_e, log_w, log_x};
 
 use super::{
+  audit_config::ConfigAudit,
   auth_config::ConfigAuth,
   bots::{
     bot_config::ConfigBot, commands_config::ConfigBotCommands, presence_config::PresenceConfig,
     BotConfig,
   },
   db_config::ConfigDatabases,
+  idempotency_config::ConfigIdempotency,
+  public_api_config::ConfigPublicApi,
+  rate_limit_config::ConfigRateLimits,
   server::server_config::ConfigServer,
+  status_history_config::ConfigStatusHistory,
+  verification_config::ConfigVerification,
+  webhook_config::ConfigWebhook,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -29,6 +36,19 @@ pub struct ConfigApp {
   pub lang_path: String,
   pub servers: Vec<ConfigServer>,
   pub databases: ConfigDatabases,
+  pub verification: Option<ConfigVerification>,
+  #[serde(default)]
+  pub rate_limits: Option<ConfigRateLimits>,
+  #[serde(default)]
+  pub public_api: Option<ConfigPublicApi>,
+  #[serde(default)]
+  pub webhooks: Vec<ConfigWebhook>,
+  #[serde(default)]
+  pub audit: Option<ConfigAudit>,
+  #[serde(default)]
+  pub idempotency: Option<ConfigIdempotency>,
+  #[serde(default)]
+  pub status_history: Option<ConfigStatusHistory>,
 }
 
 impl ConfigApp {
@@ -40,6 +60,7 @@ impl ConfigApp {
         db: String::new(),
         log: String::new(),
         ws: String::new(),
+        keys: None,
       },
       lang_path: String::from("./lang.json"),
       main_bot: BotConfig::ConfigBot(ConfigBot {
@@ -49,6 +70,8 @@ impl ConfigApp {
           info_command: None,
           db_search: None,
           send_command: None,
+          accounts_command: None,
+          uptime_command: None,
         },
         default_presence: Some(PresenceConfig {
           status: String::from("dnd"),
@@ -56,6 +79,8 @@ impl ConfigApp {
           name: String::from("DISTRICT SERVER"),
           url: String::from("https://oxydien.dev"),
         }),
+        verification_roles: None,
+        verification_sync: None,
       }),
       servers: vec![],
       databases: ConfigDatabases {
@@ -63,6 +88,13 @@ impl ConfigApp {
         player_db_auto_clear_strict: None,
         leaderboards: false,
       },
+      verification: None,
+      rate_limits: None,
+      public_api: None,
+      webhooks: vec![],
+      audit: None,
+      idempotency: None,
+      status_history: None,
     }
   }
 
@@ -87,6 +119,24 @@ impl ConfigApp {
         return Err(io::Error::new(io::ErrorKind::InvalidData, err));
       }
     };
+
+    let conflicting_keys = config.auth.conflicting_key_names();
+    if !conflicting_keys.is_empty() {
+      let err = format!(
+        "API key names [{}] are used by more than one key, only a key with 'expires_at' may share its name with the key replacing it (same scopes and servers)",
+        conflicting_keys.join(", ")
+      );
+      log_e!(format!("Error while loading config: {}", err));
+      return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+    }
+
+    let plaintext_keys = config.auth.plaintext_keys();
+    if !plaintext_keys.is_empty() {
+      log_w!(format!(
+        "API keys [{}] are stored in plaintext, replace them with 'key_hash' or mint new ones",
+        plaintext_keys.join(", ")
+      ));
+    }
     Ok(config)
   }
 }
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// # Idempotency
+/// How long responses of requests with an `Idempotency-Key` header are replayed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigIdempotency {
+  #[serde(default = "default_window_hours")]
+  pub window_hours: u32,
+}
+
+fn default_window_hours() -> u32 {
+  24
+}
+
+impl Default for ConfigIdempotency {
+  fn default() -> Self {
+    ConfigIdempotency {
+      window_hours: default_window_hours(),
+    }
+  }
+}
@@ -1,5 +1,12 @@
+pub mod audit_config;
 pub mod auth_config;
 pub mod bots;
 pub mod config;
 pub mod db_config;
+pub mod idempotency_config;
+pub mod public_api_config;
+pub mod rate_limit_config;
 pub mod server;
+pub mod status_history_config;
+pub mod verification_config;
+pub mod webhook_config;
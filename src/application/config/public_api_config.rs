@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// # Public API
+/// Unauthenticated read-only routes under `/api/v1/public`, disabled when not configured
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigPublicApi {
+  /// Origins allowed to call the public routes from a browser (`https://example.com`), `*` for any
+  #[serde(default)]
+  pub cors_origins: Vec<String>,
+  /// How long browsers and proxies may cache public responses, in seconds
+  #[serde(default = "default_cache_max_age")]
+  pub cache_max_age: u32,
+}
+
+fn default_cache_max_age() -> u32 {
+  30
+}
+
+impl ConfigPublicApi {
+  /// Value for `Access-Control-Allow-Origin`, if the origin may read the responses
+  pub fn allowed_origin(&self, origin: &str) -> Option<String> {
+    if self.cors_origins.iter().any(|val| val == "*") {
+      return Some("*".to_string());
+    }
+    self
+      .cors_origins
+      .iter()
+      .find(|val| val.trim_end_matches('/') == origin)
+      .cloned()
+  }
+}
@@ -0,0 +1,21 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigRateLimits {
+  /// Limit for every client IP, checked before the API key
+  pub per_ip: Option<ConfigRateLimit>,
+  /// Limit for every API key
+  pub per_key: Option<ConfigRateLimit>,
+  /// Limits per route group (e.g. `logs`, `verifications`), counted for every API key separately
+  pub groups: Option<HashMap<String, ConfigRateLimit>>,
+}
+
+/// # Token bucket
+/// Holds up to `capacity` requests, `refill_per_second` requests are added back every second
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ConfigRateLimit {
+  pub capacity: u32,
+  pub refill_per_second: f64,
+}
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::application::config::bots::{
   commands_config::ConfigBotCommands, presence_config::PresenceConfig,
+  verification_roles_config::ConfigVerificationRoles,
+  verification_sync_config::ConfigVerificationSync,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -12,4 +14,6 @@ pub struct ServerBotConfig {
   pub default_presence: Option<PresenceConfig>,
   pub active_presence: Option<PresenceConfig>,
   pub commands: ConfigBotCommands,
+  pub verification_roles: Option<ConfigVerificationRoles>,
+  pub verification_sync: Option<ConfigVerificationSync>,
 }
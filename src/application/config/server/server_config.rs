@@ -8,4 +8,7 @@ pub struct ConfigServer {
   pub name: String,
   pub channel_id: String,
   pub bot: BotConfig,
+  /// Shared secret for signed requests, when set requests acting for this server must be signed
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub signing_secret: Option<String>,
 }
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// # Status history
+/// Retention of server status samples and incidents
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigStatusHistory {
+  /// Days samples and ended incidents are kept, 0 keeps them forever
+  #[serde(default = "default_retention_days")]
+  pub retention_days: u32,
+}
+
+fn default_retention_days() -> u32 {
+  90
+}
+
+impl Default for ConfigStatusHistory {
+  fn default() -> Self {
+    ConfigStatusHistory {
+      retention_days: default_retention_days(),
+    }
+  }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigVerification {
+  pub review: Option<ConfigVerificationReview>,
+  pub max_linked_accounts: Option<u32>,
+  pub unlink_cooldown_hours: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigVerificationReview {
+  pub channel_id: u64,
+  pub reviewer_role_id: Option<u64>,
+  pub min_account_age_days: Option<u32>,
+  pub check_banned_ips: bool,
+}
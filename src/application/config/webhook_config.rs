@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// # Webhook event
+/// Events a webhook can subscribe to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+  PlayerFirstJoin,
+  PunishmentCreated,
+  PunishmentRevoked,
+  VerificationChanged,
+  ServerUp,
+  ServerDown,
+  /// Sent by the test route only, webhooks don't need to subscribe to it
+  Test,
+}
+
+impl WebhookEvent {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      WebhookEvent::PlayerFirstJoin => "player_first_join",
+      WebhookEvent::PunishmentCreated => "punishment_created",
+      WebhookEvent::PunishmentRevoked => "punishment_revoked",
+      WebhookEvent::VerificationChanged => "verification_changed",
+      WebhookEvent::ServerUp => "server_up",
+      WebhookEvent::ServerDown => "server_down",
+      WebhookEvent::Test => "test",
+    }
+  }
+}
+
+/// # Webhook
+/// Endpoint DISTRICT posts signed JSON to when one of `events` happens
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigWebhook {
+  /// Unique name, used in the delivery log and the test route
+  pub name: String,
+  pub url: String,
+  /// Key of the `X-District-Signature` HMAC
+  pub secret: String,
+  pub events: Vec<WebhookEvent>,
+  /// Attempts before a delivery is given up, retries wait twice as long each time
+  #[serde(default = "default_max_attempts")]
+  pub max_attempts: u32,
+}
+
+fn default_max_attempts() -> u32 {
+  5
+}
@@ -0,0 +1,140 @@
+pub(crate) mod structs;
+
+use rusqlite::{params, Connection, Result};
+use serde_json::Value;
+
+use self::structs::{AuditActor, AuditFilter, DatabaseAuditEntry};
+
+use super::database::DatabaseOperations;
+use crate::application::features::metrics;
+use crate::logger::{LogLevel, Logger};
+use crate::{log_d, log_e, log_x};
+
+#[derive(Debug)]
+pub struct AuditDatabase {
+  path: String,
+  conn: Connection,
+}
+
+impl Clone for AuditDatabase {
+  fn clone(&self) -> Self {
+    AuditDatabase::setup(&self.path).unwrap()
+  }
+}
+
+// MARK: (!) Init Audit Db
+impl DatabaseOperations for AuditDatabase {
+  fn setup(db_path: &str) -> Result<Self> {
+    log_d!("Starting 'Audit' database");
+    let mut conn = match Connection::open(db_path) {
+      Ok(val) => val,
+      Err(e) => {
+        log_e!(format!("Database 'Audit' threw error while opening: {}", e));
+        return Err(e);
+      }
+    };
+    conn.profile(metrics::query_profiler("audit"));
+
+    // Entries can only be added, or removed by the retention cleanup
+    if let Err(e) = conn.execute_batch(
+      r"CREATE TABLE IF NOT EXISTS AuditLog (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              timestamp INT NOT NULL,
+              actor_kind TEXT NOT NULL,
+              actor TEXT NOT NULL,
+              action TEXT NOT NULL,
+              target TEXT,
+              before TEXT,
+              after TEXT
+            );
+            CREATE INDEX IF NOT EXISTS AuditLogTime ON AuditLog (timestamp);
+            CREATE INDEX IF NOT EXISTS AuditLogTarget ON AuditLog (target);
+            CREATE TRIGGER IF NOT EXISTS AuditLogAppendOnly BEFORE UPDATE ON AuditLog
+            BEGIN
+              SELECT RAISE(ABORT, 'AuditLog is append-only');
+            END;",
+    ) {
+      log_e!(format!(
+        "Database 'Audit' threw error while creating table 'AuditLog': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    Ok(Self {
+      path: db_path.to_string(),
+      conn,
+    })
+  }
+
+  fn connection(&self) -> &Connection {
+    &self.conn
+  }
+}
+
+// MARK: (!) Impl Audit Db
+impl AuditDatabase {
+  // MARK: Add entry
+  /// Appends an entry, `Value::Null` snapshots are stored as missing
+  pub fn add_entry(
+    &self,
+    timestamp: i64,
+    actor: &AuditActor,
+    action: &str,
+    target: Option<&str>,
+    before: &Value,
+    after: &Value,
+  ) -> Result<u64> {
+    let snapshot = |val: &Value| (!val.is_null()).then(|| val.to_string());
+    self.conn.execute(
+      "INSERT INTO AuditLog (timestamp, actor_kind, actor, action, target, before, after) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+      params![timestamp, actor.kind, actor.name, action, target, snapshot(before), snapshot(after)],
+    )?;
+    Ok(self.conn.last_insert_rowid() as u64)
+  }
+
+  // MARK: Get entries
+  /// Latest entries first
+  pub fn get_entries(&self, filter: &AuditFilter, limit: u32) -> Result<Vec<DatabaseAuditEntry>> {
+    let action_prefix = filter
+      .action
+      .filter(|action| action.ends_with('.'))
+      .map(|action| format!("{}%", action.replace('%', "\\%").replace('_', "\\_")));
+    let action = filter.action.filter(|action| !action.ends_with('.'));
+
+    let mut stmt = self.conn.prepare(
+      r"SELECT * FROM AuditLog
+        WHERE (?1 IS NULL OR actor_kind = ?1)
+          AND (?2 IS NULL OR actor = ?2)
+          AND (?3 IS NULL OR action = ?3)
+          AND (?4 IS NULL OR action LIKE ?4 ESCAPE '\')
+          AND (?5 IS NULL OR target = ?5)
+          AND (?6 IS NULL OR timestamp >= ?6)
+          AND (?7 IS NULL OR timestamp <= ?7)
+        ORDER BY id DESC LIMIT ?8",
+    )?;
+    let data_iter = stmt.query_map(
+      params![
+        filter.actor_kind,
+        filter.actor,
+        action,
+        action_prefix,
+        filter.target,
+        filter.from,
+        filter.to,
+        limit
+      ],
+      DatabaseAuditEntry::from_row,
+    )?;
+
+    let data: Result<Vec<_>, _> = data_iter.collect();
+    data
+  }
+
+  // MARK: Remove old entries
+  pub fn remove_entries_before(&self, timestamp: i64) -> Result<usize> {
+    self
+      .conn
+      .execute("DELETE FROM AuditLog WHERE timestamp < ?1", [timestamp])
+  }
+}
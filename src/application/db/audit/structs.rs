@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{
+  types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+  Result, ToSql,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+/// # Actor kind
+// MARK: (enum) Actor kind
+pub enum AuditActorKind {
+  /// `actor` is the name of the API key
+  ApiKey,
+  /// `actor` is the Discord user ID
+  Discord,
+  /// `actor` names the subsystem that acted on its own
+  System,
+}
+
+impl AuditActorKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AuditActorKind::ApiKey => "api_key",
+      AuditActorKind::Discord => "discord",
+      AuditActorKind::System => "system",
+    }
+  }
+
+  pub fn from_name(kind: &str) -> Option<Self> {
+    [
+      AuditActorKind::ApiKey,
+      AuditActorKind::Discord,
+      AuditActorKind::System,
+    ]
+    .into_iter()
+    .find(|val| val.as_str() == kind)
+  }
+}
+
+impl ToSql for AuditActorKind {
+  fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+    Ok(ToSqlOutput::from(self.as_str()))
+  }
+}
+
+impl FromSql for AuditActorKind {
+  fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+    let kind = value.as_str()?;
+    AuditActorKind::from_name(kind)
+      .ok_or_else(|| FromSqlError::Other(format!("Unknown actor kind '{}'", kind).into()))
+  }
+}
+
+/// Who made a change
+#[derive(Debug, Clone)]
+pub struct AuditActor {
+  pub kind: AuditActorKind,
+  pub name: String,
+}
+
+impl AuditActor {
+  pub fn api_key(name: &str) -> Self {
+    AuditActor {
+      kind: AuditActorKind::ApiKey,
+      name: name.to_string(),
+    }
+  }
+
+  pub fn discord(user_id: &str) -> Self {
+    AuditActor {
+      kind: AuditActorKind::Discord,
+      name: user_id.to_string(),
+    }
+  }
+
+  pub fn system(subsystem: &str) -> Self {
+    AuditActor {
+      kind: AuditActorKind::System,
+      name: subsystem.to_string(),
+    }
+  }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+/// Who changed what, with the state before and after the change
+// MARK: (struct) Audit entry
+pub struct DatabaseAuditEntry {
+  pub id: u64,
+  pub timestamp: DateTime<Utc>,
+  pub actor_kind: AuditActorKind,
+  pub actor: String,
+  /// `<subject>.<verb>`, for example `punishment.create`
+  pub action: String,
+  /// `<kind>:<id>` of the changed record, for example `player:123`
+  pub target: Option<String>,
+  pub before: Option<Value>,
+  pub after: Option<Value>,
+}
+
+impl DatabaseAuditEntry {
+  pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+    let snapshot = |index: usize| -> Result<Option<Value>> {
+      Ok(
+        row
+          .get::<_, Option<String>>(index)?
+          .and_then(|val| serde_json::from_str(&val).ok()),
+      )
+    };
+    Ok(DatabaseAuditEntry {
+      id: row.get(0)?,
+      timestamp: DateTime::from_timestamp(row.get(1)?, 0).unwrap_or_default(),
+      actor_kind: row.get(2)?,
+      actor: row.get(3)?,
+      action: row.get(4)?,
+      target: row.get(5)?,
+      before: snapshot(6)?,
+      after: snapshot(7)?,
+    })
+  }
+}
+
+/// Conditions of an audit log query, unset fields match everything
+#[derive(Default)]
+pub struct AuditFilter<'a> {
+  pub actor_kind: Option<AuditActorKind>,
+  pub actor: Option<&'a str>,
+  /// Exact action, or every action of a subject when it ends with `.` (`punishment.`)
+  pub action: Option<&'a str>,
+  pub target: Option<&'a str>,
+  pub from: Option<i64>,
+  pub to: Option<i64>,
+}
@@ -0,0 +1,318 @@
+pub(crate) mod structs;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension as _, Result};
+
+use self::structs::{DatabaseApiKey, DatabaseApiKeyUsage, DatabaseIdempotencyRecord};
+
+use super::database::DatabaseOperations;
+use crate::application::config::auth_config::ApiScope;
+use crate::application::features::metrics;
+use crate::logger::{LogLevel, Logger};
+use crate::{log_d, log_e, log_x};
+
+#[derive(Debug)]
+pub struct AuthDatabase {
+  path: String,
+  conn: Connection,
+}
+
+impl Clone for AuthDatabase {
+  fn clone(&self) -> Self {
+    AuthDatabase::setup(&self.path).unwrap()
+  }
+}
+
+// MARK: (!) Init Auth Db
+impl DatabaseOperations for AuthDatabase {
+  fn setup(db_path: &str) -> Result<Self> {
+    log_d!("Starting 'Auth' database");
+    let mut conn = match Connection::open(db_path) {
+      Ok(val) => val,
+      Err(e) => {
+        log_e!(format!("Database 'Auth' threw error while opening: {}", e));
+        return Err(e);
+      }
+    };
+    conn.profile(metrics::query_profiler("auth"));
+
+    if let Err(e) = conn.execute(
+      r"CREATE TABLE IF NOT EXISTS ApiKeyUsage (
+              key_name TEXT PRIMARY KEY,
+              first_used_at DATETIME,
+              last_used_at DATETIME,
+              last_ip TEXT,
+              last_route TEXT,
+              request_count INT
+            );",
+      (),
+    ) {
+      log_e!(format!(
+        "Database 'Auth' threw error while creating table 'ApiKeyUsage': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    if let Err(e) = conn.execute(
+      r"CREATE TABLE IF NOT EXISTS ApiKey (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              name TEXT NOT NULL,
+              key_hash TEXT NOT NULL,
+              scopes TEXT NOT NULL,
+              servers TEXT,
+              created_at DATETIME NOT NULL,
+              expires_at DATETIME
+            );",
+      (),
+    ) {
+      log_e!(format!(
+        "Database 'Auth' threw error while creating table 'ApiKey': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    if let Err(e) = conn.execute(
+      r"CREATE TABLE IF NOT EXISTS RequestNonce (
+              server_id INTEGER NOT NULL,
+              nonce TEXT NOT NULL,
+              seen_at INTEGER NOT NULL,
+              PRIMARY KEY (server_id, nonce)
+            );",
+      (),
+    ) {
+      log_e!(format!(
+        "Database 'Auth' threw error while creating table 'RequestNonce': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    if let Err(e) = conn.execute(
+      r"CREATE TABLE IF NOT EXISTS IdempotencyRecord (
+              key_name TEXT NOT NULL,
+              idempotency_key TEXT NOT NULL,
+              method TEXT NOT NULL,
+              uri TEXT NOT NULL,
+              status INTEGER,
+              content_type TEXT,
+              body BLOB,
+              body_hash TEXT,
+              created_at INTEGER NOT NULL,
+              PRIMARY KEY (key_name, idempotency_key)
+            );",
+      (),
+    ) {
+      log_e!(format!(
+        "Database 'Auth' threw error while creating table 'IdempotencyRecord': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    Ok(Self {
+      path: db_path.to_string(),
+      conn,
+    })
+  }
+
+  fn connection(&self) -> &Connection {
+    &self.conn
+  }
+}
+
+// MARK: (!) Impl Auth Db
+impl AuthDatabase {
+  // MARK: Record key usage
+  pub fn record_key_usage(&self, key_name: &str, ip: Option<String>, route: &str) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    self.conn.execute(
+      r"INSERT INTO ApiKeyUsage (key_name, first_used_at, last_used_at, last_ip, last_route, request_count)
+            VALUES (?1, ?2, ?2, ?3, ?4, 1)
+            ON CONFLICT(key_name) DO UPDATE SET
+                last_used_at = excluded.last_used_at,
+                last_ip = excluded.last_ip,
+                last_route = excluded.last_route,
+                request_count = request_count + 1",
+      params![key_name, now, ip, route],
+    )?;
+    Ok(())
+  }
+
+  // MARK: Get key usage
+  pub fn get_key_usage(&self, key_name: &str) -> Result<Option<DatabaseApiKeyUsage>> {
+    self
+      .conn
+      .query_row(
+        "SELECT * FROM ApiKeyUsage WHERE key_name = ?1",
+        [key_name],
+        DatabaseApiKeyUsage::from_row,
+      )
+      .optional()
+  }
+
+  // MARK: Get active api keys
+  pub fn get_active_api_keys(&self) -> Result<Vec<DatabaseApiKey>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT * FROM ApiKey WHERE expires_at IS NULL OR expires_at > ?1 ORDER BY created_at DESC",
+    )?;
+    let keys = stmt
+      .query_map([Utc::now().to_rfc3339()], DatabaseApiKey::from_row)?
+      .collect::<Result<Vec<_>>>()?;
+    Ok(keys)
+  }
+
+  /// Stores a new key for the identity `name`, only the newest key the identity already had
+  /// stays active (until `previous_until` at most), older ones expire right away.
+  /// That way an identity has at most two active keys while its clients are switched over.
+  // MARK: Rotate api key
+  pub fn rotate_api_key(
+    &mut self,
+    name: &str,
+    key_hash: &str,
+    scopes: &[ApiScope],
+    servers: &Option<Vec<u64>>,
+    expires_at: Option<DateTime<Utc>>,
+    previous_until: DateTime<Utc>,
+  ) -> Result<DatabaseApiKey> {
+    let now = Utc::now();
+    let tx = self.conn.transaction()?;
+
+    let previous = {
+      let mut stmt = tx.prepare(
+        r"SELECT * FROM ApiKey WHERE name = ?1 AND (expires_at IS NULL OR expires_at > ?2)
+            ORDER BY created_at DESC, id DESC",
+      )?;
+      let keys = stmt
+        .query_map(params![name, now.to_rfc3339()], DatabaseApiKey::from_row)?
+        .collect::<Result<Vec<_>>>()?;
+      keys
+    };
+    for (index, key) in previous.iter().enumerate() {
+      let expires = match index {
+        0 => key
+          .expires_at
+          .map_or(previous_until, |val| val.min(previous_until)),
+        _ => now,
+      };
+      tx.execute(
+        "UPDATE ApiKey SET expires_at = ?1 WHERE id = ?2",
+        params![expires.to_rfc3339(), key.id],
+      )?;
+    }
+
+    let scopes_json = serde_json::to_string(scopes).unwrap_or_else(|_| "[]".to_string());
+    let servers_json = servers
+      .as_ref()
+      .and_then(|val| serde_json::to_string(val).ok());
+    tx.execute(
+      r"INSERT INTO ApiKey (name, key_hash, scopes, servers, created_at, expires_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+      params![
+        name,
+        key_hash,
+        scopes_json,
+        servers_json,
+        now.to_rfc3339(),
+        expires_at.map(|val| val.to_rfc3339())
+      ],
+    )?;
+    let key = tx.query_row(
+      "SELECT * FROM ApiKey WHERE id = ?1",
+      [tx.last_insert_rowid()],
+      DatabaseApiKey::from_row,
+    )?;
+
+    tx.commit()?;
+    Ok(key)
+  }
+
+  /// Remembers the nonce of a signed request and forgets nonces older than `forget_before` (unix seconds)
+  ///
+  /// Returns false if the server already used the nonce
+  // MARK: Use request nonce
+  pub fn use_request_nonce(&self, server_id: u64, nonce: &str, forget_before: i64) -> Result<bool> {
+    self.conn.execute(
+      "DELETE FROM RequestNonce WHERE seen_at < ?1",
+      [forget_before],
+    )?;
+    let inserted = self.conn.execute(
+      "INSERT OR IGNORE INTO RequestNonce (server_id, nonce, seen_at) VALUES (?1, ?2, ?3)",
+      params![server_id, nonce, Utc::now().timestamp()],
+    )?;
+    Ok(inserted == 1)
+  }
+
+  /// Claims the idempotency key of an API key for a request, records created before
+  /// `expired_before` and unfinished ones created before `abandoned_before` are replaced
+  ///
+  /// Returns `None` when the key was claimed, or the record that already holds it
+  // MARK: Claim idempotency key
+  pub fn claim_idempotency_key(
+    &mut self,
+    key_name: &str,
+    idempotency_key: &str,
+    method: &str,
+    uri: &str,
+    expired_before: i64,
+    abandoned_before: i64,
+  ) -> Result<Option<DatabaseIdempotencyRecord>> {
+    let tx = self.conn.transaction()?;
+    tx.execute(
+      "DELETE FROM IdempotencyRecord WHERE key_name = ?1 AND idempotency_key = ?2
+       AND (created_at < ?3 OR (status IS NULL AND created_at < ?4))",
+      params![key_name, idempotency_key, expired_before, abandoned_before],
+    )?;
+    let existing = tx
+      .query_row(
+        "SELECT method, uri, status, content_type, body, body_hash FROM IdempotencyRecord WHERE key_name = ?1 AND idempotency_key = ?2",
+        params![key_name, idempotency_key],
+        DatabaseIdempotencyRecord::from_row,
+      )
+      .optional()?;
+    if existing.is_none() {
+      tx.execute(
+        "INSERT INTO IdempotencyRecord (key_name, idempotency_key, method, uri, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![key_name, idempotency_key, method, uri, Utc::now().timestamp()],
+      )?;
+    }
+    tx.commit()?;
+    Ok(existing)
+  }
+
+  // MARK: Store idempotent response
+  pub fn store_idempotent_response(
+    &self,
+    key_name: &str,
+    idempotency_key: &str,
+    status: u16,
+    content_type: Option<String>,
+    body: &[u8],
+    body_hash: &str,
+  ) -> Result<()> {
+    self.conn.execute(
+      "UPDATE IdempotencyRecord SET status = ?3, content_type = ?4, body = ?5, body_hash = ?6 WHERE key_name = ?1 AND idempotency_key = ?2",
+      params![key_name, idempotency_key, status, content_type, body, body_hash],
+    )?;
+    Ok(())
+  }
+
+  /// Frees the key, so the request can be retried
+  // MARK: Release idempotency key
+  pub fn release_idempotency_key(&self, key_name: &str, idempotency_key: &str) -> Result<()> {
+    self.conn.execute(
+      "DELETE FROM IdempotencyRecord WHERE key_name = ?1 AND idempotency_key = ?2",
+      params![key_name, idempotency_key],
+    )?;
+    Ok(())
+  }
+
+  // MARK: Remove old idempotency keys
+  pub fn remove_idempotency_keys_before(&self, timestamp: i64) -> Result<usize> {
+    self.conn.execute(
+      "DELETE FROM IdempotencyRecord WHERE created_at < ?1",
+      [timestamp],
+    )
+  }
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use rusqlite::Result;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::application::{
+  config::auth_config::ApiScope,
+  utils::{self, api_key},
+};
+
+#[derive(Clone, Serialize, ToSchema)]
+/// Api key usage
+// MARK: (struct) Api key usage
+pub struct DatabaseApiKeyUsage {
+  pub key_name: String,
+  pub first_used_at: DateTime<Utc>,
+  pub last_used_at: DateTime<Utc>,
+  pub last_ip: Option<String>,
+  pub last_route: Option<String>,
+  pub request_count: u64,
+}
+
+impl DatabaseApiKeyUsage {
+  pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+    let first_used_at =
+      utils::time::parse_rfc3339_to_utc(row.get::<_, String>(1)?).unwrap_or_else(|_| Utc::now());
+    let last_used_at =
+      utils::time::parse_rfc3339_to_utc(row.get::<_, String>(2)?).unwrap_or_else(|_| Utc::now());
+
+    Ok(Self {
+      key_name: row.get(0)?,
+      first_used_at,
+      last_used_at,
+      last_ip: row.get(3)?,
+      last_route: row.get(4)?,
+      request_count: row.get(5)?,
+    })
+  }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+/// Api key minted through the API, only the salted hash of the secret is stored
+// MARK: (struct) Api key
+pub struct DatabaseApiKey {
+  pub id: i64,
+  pub name: String,
+  #[serde(skip)]
+  pub key_hash: String,
+  pub scopes: Vec<ApiScope>,
+  pub servers: Option<Vec<u64>>,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl DatabaseApiKey {
+  pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+    let created_at =
+      utils::time::parse_rfc3339_to_utc(row.get::<_, String>(5)?).unwrap_or_else(|_| Utc::now());
+    let expires_at = row
+      .get::<_, Option<String>>(6)?
+      .and_then(|val| utils::time::parse_rfc3339_to_utc(val).ok());
+
+    Ok(Self {
+      id: row.get(0)?,
+      name: row.get(1)?,
+      key_hash: row.get(2)?,
+      scopes: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+      servers: row
+        .get::<_, Option<String>>(4)?
+        .and_then(|val| serde_json::from_str(&val).ok()),
+      created_at,
+      expires_at,
+    })
+  }
+
+  pub fn is_expired(&self) -> bool {
+    self
+      .expires_at
+      .is_some_and(|expires_at| expires_at <= Utc::now())
+  }
+
+  /// Checks the token against this key in constant time
+  pub fn matches(&self, token: &str) -> bool {
+    !self.is_expired() && api_key::verify_key_hash(token, &self.key_hash)
+  }
+}
+
+#[derive(Clone, Debug)]
+/// Request made with an `Idempotency-Key`, with its response once it finished
+// MARK: (struct) Idempotency record
+pub struct DatabaseIdempotencyRecord {
+  pub method: String,
+  pub uri: String,
+  /// Not set while the request is being handled
+  pub status: Option<u16>,
+  pub content_type: Option<String>,
+  pub body: Option<Vec<u8>>,
+  /// Hex SHA-256 of the request body the key was first used with
+  pub body_hash: Option<String>,
+}
+
+impl DatabaseIdempotencyRecord {
+  pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+    Ok(DatabaseIdempotencyRecord {
+      method: row.get(0)?,
+      uri: row.get(1)?,
+      status: row.get(2)?,
+      content_type: row.get(3)?,
+      body: row.get(4)?,
+      body_hash: row.get(5)?,
+    })
+  }
+}
@@ -1,7 +1,97 @@
-use rusqlite::Result;
+use rusqlite::{Connection, OpenFlags, Params, Result, Row};
+
+use crate::application::features::metrics;
+use crate::logger::{LogLevel, Logger};
+use crate::{log_e, log_x};
 
 pub trait DatabaseOperations {
     fn setup(db_path: &str) -> Result<Self>
     where
         Self: Sized;
+
+    fn connection(&self) -> &Connection;
+
+    /// Checks that the database answers a trivial query
+    fn ping(&self) -> Result<()> {
+        self.connection().query_row("SELECT 1", (), |_| Ok(()))
+    }
+
+    /// Hands the rows of `sql` to `on_row` one at a time as they're read, without collecting them,
+    /// stops early when `on_row` returns `false`
+    fn for_each_row<T, P: Params>(
+        &self,
+        sql: &str,
+        params: P,
+        from_row: fn(&Row) -> Result<T>,
+        mut on_row: impl FnMut(T) -> bool,
+    ) -> Result<()> {
+        let mut stmt = self.connection().prepare(sql)?;
+        let mut rows = stmt.query(params)?;
+        while let Some(row) = rows.next()? {
+            if !on_row(from_row(row)?) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Databases that can process many items at once
+pub trait BatchOperations: DatabaseOperations + Sized {
+    /// Runs `run` for every item in a single transaction, each item in its own savepoint,
+    /// so a failing item is rolled back without undoing the others.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Result<R, E>>>` - Result of every item in order, or an error if the transaction itself failed.
+    fn run_batch<T, R, E>(
+        &mut self,
+        items: Vec<T>,
+        mut run: impl FnMut(&mut Self, T) -> Result<R, E>,
+    ) -> Result<Vec<Result<R, E>>> {
+        self.connection().execute_batch("BEGIN")?;
+        let mut results = Vec::with_capacity(items.len());
+        for item in items {
+            let result = self
+                .connection()
+                .execute_batch("SAVEPOINT batch_item")
+                .and_then(|_| {
+                    let result = run(self, item);
+                    let end = match result {
+                        Ok(_) => "RELEASE batch_item",
+                        Err(_) => "ROLLBACK TO batch_item; RELEASE batch_item",
+                    };
+                    self.connection().execute_batch(end).map(|_| result)
+                });
+            match result {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    let _ = self.connection().execute_batch("ROLLBACK");
+                    return Err(e);
+                }
+            }
+        }
+        self.connection().execute_batch("COMMIT")?;
+        Ok(results)
+    }
+}
+
+/// Opens a read-only connection of its own to the database at `db_path`, so long reads (exports)
+/// don't hold the lock of the shared one. Runs no setup or migrations, the shared connection did
+pub fn open_reader(db_path: &str, name: &str) -> Result<Connection> {
+    let mut conn = match Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    ) {
+        Ok(val) => val,
+        Err(e) => {
+            log_e!(format!(
+                "Database '{}' threw error while opening reader: {}",
+                name, e
+            ));
+            return Err(e);
+        }
+    };
+    conn.profile(metrics::query_profiler(name));
+    Ok(conn)
 }
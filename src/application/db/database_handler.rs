@@ -1,10 +1,16 @@
 use std::{fs, path::PathBuf};
 
+use rusqlite::Result;
+
 use crate::application::config::db_config::ConfigDatabases;
 use crate::logger::{LogLevel, Logger};
 use crate::{log_e, log_x};
 
+use super::audit::AuditDatabase;
+use super::auth::AuthDatabase;
 use super::leaderboards::LeaderboardDatabase;
+use super::status::StatusDatabase;
+use super::webhooks::WebhookDatabase;
 use super::{
   database::DatabaseOperations as _, player::PlayerDatabase, punishments::PunishmentDatabase,
 };
@@ -14,6 +20,10 @@ pub struct DatabaseHandler {
   pub player_database: PlayerDatabase,
   pub punishment_database: PunishmentDatabase,
   pub leaderboard_database: Option<LeaderboardDatabase>,
+  pub auth_database: AuthDatabase,
+  pub status_database: StatusDatabase,
+  pub webhook_database: WebhookDatabase,
+  pub audit_database: AuditDatabase,
 }
 
 impl DatabaseHandler {
@@ -31,6 +41,26 @@ impl DatabaseHandler {
       } else {
         None
       },
+      auth_database: AuthDatabase::setup("./db/auth.db").unwrap(),
+      status_database: StatusDatabase::setup("./db/status.db").unwrap(),
+      webhook_database: WebhookDatabase::setup("./db/webhooks.db").unwrap(),
+      audit_database: AuditDatabase::setup("./db/audit.db").unwrap(),
+    }
+  }
+
+  /// Checks every open database, by name
+  pub fn ping(&self) -> Vec<(&'static str, Result<()>)> {
+    let mut results = vec![
+      ("players", self.player_database.ping()),
+      ("punishments", self.punishment_database.ping()),
+      ("auth", self.auth_database.ping()),
+      ("status", self.status_database.ping()),
+      ("webhooks", self.webhook_database.ping()),
+      ("audit", self.audit_database.ping()),
+    ];
+    if let Some(leaderboard_database) = self.leaderboard_database.as_ref() {
+      results.push(("leaderboards", leaderboard_database.ping()));
     }
+    results
   }
 }
@@ -5,7 +5,8 @@ use rusqlite::{params, Connection, Result};
 
 use self::structs::{LeaderboardRecord, LeaderboardRecordType};
 
-use super::database::DatabaseOperations;
+use super::database::{open_reader, BatchOperations, DatabaseOperations};
+use crate::application::features::metrics;
 use crate::logger::{LogLevel, Logger};
 use crate::{log_d, log_e, log_x};
 
@@ -25,7 +26,7 @@ impl Clone for LeaderboardDatabase {
 impl DatabaseOperations for LeaderboardDatabase {
   fn setup(db_path: &str) -> Result<Self> {
     log_d!("Starting 'Leaderboard' database");
-    let conn = match Connection::open(db_path) {
+    let mut conn = match Connection::open(db_path) {
       Ok(val) => val,
       Err(e) => {
         log_e!(format!(
@@ -35,6 +36,7 @@ impl DatabaseOperations for LeaderboardDatabase {
         return Err(e);
       }
     };
+    conn.profile(metrics::query_profiler("leaderboards"));
 
     if let Err(e) = conn.execute(
       r"CREATE TABLE IF NOT EXISTS Leaderboard (
@@ -57,10 +59,24 @@ impl DatabaseOperations for LeaderboardDatabase {
       conn,
     })
   }
+
+  fn connection(&self) -> &Connection {
+    &self.conn
+  }
 }
 
+impl BatchOperations for LeaderboardDatabase {}
+
 // MARK: (!) Impl Leaderboard Db
 impl LeaderboardDatabase {
+  /// Read-only connection of its own, for exports that shouldn't hold the database lock
+  pub fn open_reader(&self) -> Result<Self> {
+    Ok(LeaderboardDatabase {
+      path: self.path.clone(),
+      conn: open_reader(&self.path, "leaderboards")?,
+    })
+  }
+
   // MARK: Get all data
   pub fn get_all_data(&self) -> Result<Vec<LeaderboardRecord>> {
     let mut stmt = self.conn.prepare("SELECT * FROM Leaderboard")?;
@@ -70,6 +86,23 @@ impl LeaderboardDatabase {
     data
   }
 
+  // MARK: Export records
+  /// Hands records to `on_row` as they're read, filtered like the `get_all_*` queries when
+  /// `player_id` or `kind` is set
+  pub fn export_records(
+    &self,
+    player_id: Option<u64>,
+    kind: Option<LeaderboardRecordType>,
+    on_row: impl FnMut(LeaderboardRecord) -> bool,
+  ) -> Result<()> {
+    self.for_each_row(
+      "SELECT * FROM Leaderboard WHERE (?1 IS NULL OR player_id = ?1) AND (?2 IS NULL OR type = ?2)",
+      params![player_id, kind],
+      LeaderboardRecord::from_row,
+      on_row,
+    )
+  }
+
   // MARK: Get all by type
   pub fn get_all_by_type(&self, kind: LeaderboardRecordType) -> Result<Vec<LeaderboardRecord>> {
     let mut stmt = self
@@ -81,6 +114,24 @@ impl LeaderboardDatabase {
     data
   }
 
+  // MARK: Get top by type
+  /// Players with the highest sum of `kind` records, as `(player_id, total)`
+  pub fn get_top_by_type(
+    &self,
+    kind: LeaderboardRecordType,
+    limit: u32,
+  ) -> Result<Vec<(u64, f64)>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT player_id, SUM(value) AS total FROM Leaderboard WHERE type = ?1 GROUP BY player_id ORDER BY total DESC LIMIT ?2",
+    )?;
+    let data_iter = stmt.query_map(params![kind, limit], |row| {
+      Ok((row.get(0)?, row.get(1)?))
+    })?;
+
+    let data: Result<Vec<_>, _> = data_iter.collect();
+    data
+  }
+
   // MARK: Get all from player
   pub fn get_all_from_player(&self, player_id: u64) -> Result<Vec<LeaderboardRecord>> {
     let mut stmt = self
@@ -7,11 +7,12 @@ use rusqlite::{
 };
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use utoipa::ToSchema;
 
 use crate::application::utils;
 
 #[repr(u8)]
-#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq)]
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, ToSchema)]
 // MARK: (enum) Leaderboard Record Type
 pub(crate) enum LeaderboardRecordType {
   PlayTime = 0,
@@ -35,7 +36,7 @@ impl LeaderboardRecordType {
       3 => Ok(LeaderboardRecordType::Wins),
       4 => Ok(LeaderboardRecordType::Losses),
       5 => Ok(LeaderboardRecordType::Assists),
-      _ => panic!("Invalid Leaderboard Record Type"),
+      _ => Err("Invalid Leaderboard Record Type".into()),
     }
   }
 }
@@ -57,7 +58,7 @@ impl FromSql for LeaderboardRecordType {
   }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 // MARK: (struct) Leaderboard Record
 pub(crate) struct LeaderboardRecord {
   pub player_id: u64,
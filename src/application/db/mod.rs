@@ -1,5 +1,9 @@
+pub(super) mod audit;
+pub(super) mod auth;
 pub(crate) mod database;
 pub(super) mod database_handler;
 pub(super) mod leaderboards;
 pub(super) mod player;
 pub(super) mod punishments;
+pub(super) mod status;
+pub(super) mod webhooks;
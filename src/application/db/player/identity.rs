@@ -0,0 +1,237 @@
+use std::{fmt, str::FromStr};
+
+use rocket::request::FromParam;
+use rusqlite::{
+  types::{FromSql, FromSqlError, ToSqlOutput, ValueRef},
+  ToSql,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use utoipa::{
+  openapi::{schema::Type, ObjectBuilder, RefOr, Schema},
+  PartialSchema, ToSchema,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// # Platform of the SCP:SL user ID
+// MARK: (enum) Player platform
+pub enum PlayerPlatform {
+  Steam,
+  Discord,
+  Northwood,
+  /// Any other suffix (`patreon`, `localhost`, ...), kept as sent in lowercase
+  Other(String),
+}
+
+impl PlayerPlatform {
+  pub fn as_str(&self) -> &str {
+    match self {
+      PlayerPlatform::Steam => "steam",
+      PlayerPlatform::Discord => "discord",
+      PlayerPlatform::Northwood => "northwood",
+      PlayerPlatform::Other(platform) => platform,
+    }
+  }
+
+  fn validate_native_id(&self, native_id: &str) -> bool {
+    match self {
+      PlayerPlatform::Steam => {
+        native_id.len() == 17 && native_id.chars().all(|c| c.is_ascii_digit())
+      }
+      PlayerPlatform::Discord => {
+        (17..=20).contains(&native_id.len()) && native_id.chars().all(|c| c.is_ascii_digit())
+      }
+      PlayerPlatform::Northwood => {
+        (1..=32).contains(&native_id.len())
+          && native_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+      }
+      PlayerPlatform::Other(_) => {
+        (1..=64).contains(&native_id.len())
+          && native_id.chars().all(|c| c.is_ascii_graphic() && c != '@')
+      }
+    }
+  }
+}
+
+impl FromStr for PlayerPlatform {
+  type Err = PlayerIdentityError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "steam" => Ok(PlayerPlatform::Steam),
+      "discord" => Ok(PlayerPlatform::Discord),
+      "northwood" => Ok(PlayerPlatform::Northwood),
+      platform
+        if (1..=32).contains(&platform.len())
+          && platform
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+      {
+        Ok(PlayerPlatform::Other(platform.to_string()))
+      }
+      _ => Err(PlayerIdentityError::UnknownPlatform(s.to_string())),
+    }
+  }
+}
+
+impl ToSql for PlayerPlatform {
+  fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+    Ok(ToSqlOutput::from(self.as_str()))
+  }
+}
+
+impl FromSql for PlayerPlatform {
+  fn column_result(value: ValueRef<'_>) -> Result<Self, FromSqlError> {
+    PlayerPlatform::from_str(value.as_str()?).map_err(|e| FromSqlError::Other(Box::new(e)))
+  }
+}
+
+impl Serialize for PlayerPlatform {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(self.as_str())
+  }
+}
+
+impl<'de> Deserialize<'de> for PlayerPlatform {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    PlayerPlatform::from_str(&value).map_err(serde::de::Error::custom)
+  }
+}
+
+impl PartialSchema for PlayerPlatform {
+  fn schema() -> RefOr<Schema> {
+    ObjectBuilder::new()
+      .schema_type(Type::String)
+      .description(Some(
+        "`steam`, `discord`, `northwood` or any other suffix of the SCP:SL user ID",
+      ))
+      .into()
+  }
+}
+
+impl ToSchema for PlayerPlatform {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+// MARK: (enum) Identity error
+pub enum PlayerIdentityError {
+  Empty,
+  UnknownPlatform(String),
+  InvalidId(PlayerPlatform, String),
+}
+
+impl fmt::Display for PlayerIdentityError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PlayerIdentityError::Empty => write!(f, "User ID is empty"),
+      PlayerIdentityError::UnknownPlatform(platform) => {
+        write!(f, "Unknown user ID platform '{}'", platform)
+      }
+      PlayerIdentityError::InvalidId(platform, id) => {
+        write!(f, "'{}' is not a valid {} ID", id, platform.as_str())
+      }
+    }
+  }
+}
+
+impl std::error::Error for PlayerIdentityError {}
+
+/// # Player identity
+/// Parsed SCP:SL user ID (`76561198000000000@steam`, `123456789012345678@discord`, `name@northwood`,
+/// other suffixes such as `@patreon` or `@localhost` are kept as they are).
+/// Bare numeric IDs without platform are considered Steam IDs.
+// MARK: (obj) Player identity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerIdentity {
+  pub platform: PlayerPlatform,
+  pub native_id: String,
+}
+
+impl PlayerIdentity {
+  /// Full SCP:SL user ID, as stored in the `steam_id` column
+  pub fn user_id(&self) -> String {
+    format!("{}@{}", self.native_id, self.platform.as_str())
+  }
+
+  /// Link to the public profile of the player, if the platform has one
+  pub fn profile_url(&self) -> Option<String> {
+    match self.platform {
+      PlayerPlatform::Steam => Some(format!(
+        "https://steamcommunity.com/profiles/{}/",
+        self.native_id
+      )),
+      PlayerPlatform::Discord => Some(format!("https://discord.com/users/{}", self.native_id)),
+      PlayerPlatform::Northwood | PlayerPlatform::Other(_) => None,
+    }
+  }
+}
+
+impl FromStr for PlayerIdentity {
+  type Err = PlayerIdentityError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+    if s.is_empty() {
+      return Err(PlayerIdentityError::Empty);
+    }
+
+    let (native_id, platform) = match s.rsplit_once('@') {
+      Some((native_id, platform)) => (native_id, PlayerPlatform::from_str(platform)?),
+      None => (s, PlayerPlatform::Steam),
+    };
+
+    if !platform.validate_native_id(native_id) {
+      return Err(PlayerIdentityError::InvalidId(
+        platform,
+        native_id.to_string(),
+      ));
+    }
+
+    Ok(PlayerIdentity {
+      platform,
+      native_id: native_id.to_string(),
+    })
+  }
+}
+
+impl fmt::Display for PlayerIdentity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.user_id())
+  }
+}
+
+impl Serialize for PlayerIdentity {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.user_id())
+  }
+}
+
+impl<'de> Deserialize<'de> for PlayerIdentity {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    PlayerIdentity::from_str(&value).map_err(serde::de::Error::custom)
+  }
+}
+
+impl PartialSchema for PlayerIdentity {
+  fn schema() -> RefOr<Schema> {
+    ObjectBuilder::new()
+      .schema_type(Type::String)
+      .description(Some(
+        "SCP:SL user ID as `id@platform` (steam, discord, northwood or any other suffix), bare IDs are Steam IDs",
+      ))
+      .examples([serde_json::json!("76561198000000000@steam")])
+      .into()
+  }
+}
+
+impl ToSchema for PlayerIdentity {}
+
+impl<'a> FromParam<'a> for PlayerIdentity {
+  type Error = PlayerIdentityError;
+
+  fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+    PlayerIdentity::from_str(param)
+  }
+}
@@ -1,18 +1,25 @@
+pub(crate) mod identity;
 pub(crate) mod structs;
 
+use std::str::FromStr as _;
+
 use chrono::{DateTime, Utc};
 use rand::Rng as _;
 use rusqlite::{params, Connection, OptionalExtension as _, Result};
+use serde::Serialize;
 
+use crate::application::features::metrics;
+use crate::application::utils::time::parse_rfc3339_to_utc_or_none;
 use crate::logger::{LogLevel, Logger};
-use crate::{log_d, log_e, log_x};
+use crate::{log_d, log_e, log_w, log_x};
 
+use self::identity::PlayerIdentity;
 use self::structs::{
-  DatabaseModifyPlayerVerification, DatabasePlayer, DatabasePlayerCount, DatabasePlayerJoin,
-  DatabasePlayerVerification, PlayerVerification,
+  DatabaseAccountLink, DatabaseModifyPlayerVerification, DatabasePlayer, DatabasePlayerCount, DatabasePlayerJoin,
+  DatabasePlayerVerification, DatabaseVerificationReview, PlayerVerification, ReviewStatus,
 };
 
-use super::database::DatabaseOperations;
+use super::database::{open_reader, BatchOperations, DatabaseOperations};
 
 #[derive(Debug)]
 pub struct PlayerDatabase {
@@ -30,7 +37,7 @@ impl Clone for PlayerDatabase {
 impl DatabaseOperations for PlayerDatabase {
   fn setup(db_path: &str) -> Result<Self> {
     log_d!("Starting 'Player' database");
-    let conn = match Connection::open(db_path) {
+    let mut conn = match Connection::open(db_path) {
       Ok(val) => val,
       Err(e) => {
         log_e!(format!(
@@ -40,6 +47,7 @@ impl DatabaseOperations for PlayerDatabase {
         return Err(e);
       }
     };
+    conn.profile(metrics::query_profiler("players"));
 
     if let Err(e) = conn.execute(
       r"CREATE TABLE IF NOT EXISTS Player (
@@ -59,7 +67,9 @@ impl DatabaseOperations for PlayerDatabase {
                     ban_ids TEXT,
                     rank_id INT,
                     supporter_id INT,
-                    email_address VARCHAR(255)
+                    email_address VARCHAR(255),
+                    platform TEXT,
+                    platform_id TEXT
                 );",
       (),
     ) {
@@ -85,19 +95,138 @@ impl DatabaseOperations for PlayerDatabase {
       return Err(e);
     }
 
+    if let Err(e) = conn.execute(
+      r"CREATE TABLE IF NOT EXISTS VerificationReview (
+                review_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                player_id INT,
+                discord_id TEXT,
+                reasons TEXT,
+                created_at DATETIME,
+                status TINYINT,
+                reviewer_id TEXT,
+                decided_at DATETIME
+            );",
+      (),
+    ) {
+      log_e!(format!(
+        "Database 'Player' threw error while creating table 'VerificationReview': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    if let Err(e) = conn.execute_batch(
+      r"CREATE TABLE IF NOT EXISTS AccountLink (
+                player_id INT PRIMARY KEY,
+                discord_id TEXT NOT NULL,
+                is_primary INT,
+                linked_at DATETIME
+            );
+            CREATE TABLE IF NOT EXISTS AccountUnlink (
+                player_id INT,
+                discord_id TEXT,
+                unlinked_at DATETIME
+            );",
+    ) {
+      log_e!(format!(
+        "Database 'Player' threw error while creating table 'AccountLink': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    // Players verified before account links existed
+    if let Err(e) = conn.execute_batch(
+      r"INSERT OR IGNORE INTO AccountLink (player_id, discord_id, is_primary, linked_at)
+            SELECT player_id, discord_id, 0, COALESCE(verified_date, last_join_date) FROM Player
+            WHERE verified_status IN (3, 4) AND discord_id IS NOT NULL AND discord_id != 'NULL';
+            UPDATE AccountLink SET is_primary = 1
+            WHERE player_id IN (
+                SELECT MIN(player_id) FROM AccountLink GROUP BY discord_id HAVING MAX(is_primary) = 0
+            );",
+    ) {
+      log_e!(format!(
+        "Database 'Player' threw error while migrating account links: {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    if let Err(e) = Self::migrate_player_identities(&conn) {
+      log_e!(format!(
+        "Database 'Player' threw error while migrating player identities: {}",
+        e
+      ));
+      return Err(e);
+    }
+
     Ok(PlayerDatabase {
       path: db_path.to_string(),
       conn,
     })
   }
+
+  fn connection(&self) -> &Connection {
+    &self.conn
+  }
 }
 
+impl BatchOperations for PlayerDatabase {}
+
 // MARK: (!) Impl Player Db
 impl PlayerDatabase {
+  /// Read-only connection of its own, for exports that shouldn't hold the database lock
+  pub fn open_reader(&self) -> Result<Self> {
+    Ok(PlayerDatabase {
+      path: self.path.clone(),
+      conn: open_reader(&self.path, "players")?,
+    })
+  }
+
+  /// Adds `platform` and `platform_id` columns to databases created before them
+  /// and fills them from the stored user ID
+  // MARK: Migrate identities
+  fn migrate_player_identities(conn: &Connection) -> Result<()> {
+    let has_platform = conn
+      .prepare("SELECT 1 FROM pragma_table_info('Player') WHERE name = 'platform'")?
+      .exists([])?;
+    if !has_platform {
+      conn.execute_batch(
+        r"ALTER TABLE Player ADD COLUMN platform TEXT;
+            ALTER TABLE Player ADD COLUMN platform_id TEXT;",
+      )?;
+    }
+
+    let mut stmt = conn.prepare("SELECT player_id, steam_id FROM Player WHERE platform IS NULL")?;
+    let rows = stmt
+      .query_map([], |row| Ok((row.get::<_, u64>(0)?, row.get::<_, Option<String>>(1)?)))?
+      .collect::<Result<Vec<_>>>()?;
+
+    for (player_id, steam_id) in rows {
+      let identity = match steam_id.as_deref().map(PlayerIdentity::from_str) {
+        Some(Ok(identity)) => identity,
+        _ => {
+          log_w!(format!(
+            "Player {} has unparsable user ID '{}'",
+            player_id,
+            steam_id.unwrap_or_default()
+          ));
+          continue;
+        }
+      };
+      conn.execute(
+        "UPDATE Player SET platform = ?1, platform_id = ?2 WHERE player_id = ?3",
+        params![identity.platform, identity.native_id, player_id],
+      )?;
+    }
+
+    Ok(())
+  }
+
   // MARK: Add player
   pub fn add_player(
     &self,
-    steam_id: String,
+    identity: &PlayerIdentity,
     username: String,
     ip_addr: String,
     do_not_track: bool,
@@ -109,9 +238,9 @@ impl PlayerDatabase {
     let ips = format!("[\"{}\"]", ip_addr);
     let joined_date = first_join.to_rfc3339();
 
-    self.conn.execute("INSERT INTO Player (`player_id`, `steam_id`, `usernames`, `ips`, `first_join_date`, `times_joined`, `last_join_date`, `hours_played`, `verified_status`, `ban_ids`, `do_not_track`)\
-                        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-                   (id, steam_id, usernames, ips, &joined_date, 1, &joined_date, 0, PlayerVerification::None, "[]", &do_not_track))?;
+    self.conn.execute("INSERT INTO Player (`player_id`, `steam_id`, `usernames`, `ips`, `first_join_date`, `times_joined`, `last_join_date`, `hours_played`, `verified_status`, `ban_ids`, `do_not_track`, `platform`, `platform_id`)\
+                        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                   params![id, identity.user_id(), usernames, ips, &joined_date, 1, &joined_date, 0, PlayerVerification::None, "[]", &do_not_track, identity.platform, identity.native_id])?;
     Ok(())
   }
 
@@ -150,15 +279,28 @@ impl PlayerDatabase {
     Ok(players)
   }
 
-  /// Gets players in db by their steam_id
+  /// Hands every player to `on_row` as it's read, see [`DatabaseOperations::for_each_row`]
+  // MARK: Export players
+  pub fn export_players(&self, on_row: impl FnMut(DatabasePlayer) -> bool) -> Result<()> {
+    self.for_each_row("SELECT * FROM Player", [], DatabasePlayer::from_row, on_row)
+  }
+
+  /// Gets players in db by their user ID, matching both `id@platform` and bare stored IDs
   /// returns [`Result`] with [`std::Vec`] containing [`DatabasePlayer`]
-  // MARK: Get player by steam ID
-  pub fn get_players_by_steam(&self, steam_id: &str) -> Result<Vec<DatabasePlayer>> {
-    let mut stmt = self
-      .conn
-      .prepare("SELECT * FROM Player WHERE `steam_id` = ?1")?;
-    let mut person_iter =
-      stmt.query_map([steam_id], |row| Ok(DatabasePlayer::from_row(row).unwrap()))?;
+  // MARK: Get player by identity
+  pub fn get_players_by_identity(&self, identity: &PlayerIdentity) -> Result<Vec<DatabasePlayer>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT * FROM Player WHERE (`platform` = ?1 AND `platform_id` = ?2) OR `steam_id` IN (?3, ?4)",
+    )?;
+    let mut person_iter = stmt.query_map(
+      params![
+        identity.platform,
+        identity.native_id,
+        identity.user_id(),
+        identity.native_id
+      ],
+      |row| Ok(DatabasePlayer::from_row(row).unwrap()),
+    )?;
 
     let mut players = Vec::new();
     while let Some(result) = person_iter.next() {
@@ -196,19 +338,21 @@ impl PlayerDatabase {
   pub fn get_player_by_discord_or_steam(
     &mut self,
     discord_id: &String,
-    steam_id: &String,
+    identity: &PlayerIdentity,
   ) -> Result<Option<DatabasePlayer>> {
     let tx = self.conn.transaction()?;
 
     let query = r#"
             SELECT * FROM Player
-            WHERE discord_id = ?1 OR steam_id = ?2
+            WHERE discord_id = ?1 OR (platform = ?2 AND platform_id = ?3) OR steam_id IN (?4, ?3)
         "#;
 
     let player: Option<DatabasePlayer> = tx
-      .query_row(query, params![discord_id, steam_id], |row| {
-        DatabasePlayer::from_row(row)
-      })
+      .query_row(
+        query,
+        params![discord_id, identity.platform, identity.native_id, identity.user_id()],
+        DatabasePlayer::from_row,
+      )
       .optional()?;
 
     Ok(player)
@@ -217,7 +361,13 @@ impl PlayerDatabase {
   /// Updates the player based on player_id
   // MARK: Modify player
   pub fn modify_player(&mut self, id: u64, data: DatabasePlayer) -> Result<(), String> {
-    let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+    self.update_player(id, data).map_err(|e| e.to_string())
+  }
+
+  /// Like [`PlayerDatabase::modify_player`], keeping the database error
+  fn update_player(&mut self, id: u64, data: DatabasePlayer) -> Result<()> {
+    // Savepoint, so this also works inside a batch transaction
+    let tx = self.conn.savepoint()?;
 
     let update_query = r#"
             UPDATE Player
@@ -263,8 +413,8 @@ impl PlayerDatabase {
       update_query,
       params![
         data.steam_id,
-        serde_json::to_string(&escaped_usernames).map_err(|e| e.to_string())?,
-        serde_json::to_string(&escaped_ips).map_err(|e| e.to_string())?,
+        to_json(&escaped_usernames)?,
+        to_json(&escaped_ips)?,
         data.first_join_date.to_rfc3339(),
         data.times_joined,
         data.last_join_date.to_rfc3339(),
@@ -275,17 +425,31 @@ impl PlayerDatabase {
           .verified_date
           .map_or("NULL".to_string(), |date| date.to_rfc3339()),
         data.discord_id,
-        serde_json::to_string(&escaped_ban_ids).map_err(|e| e.to_string())?,
+        to_json(&escaped_ban_ids)?,
         data.rank_id,
         data.do_not_track,
         data.supporter_id,
         data.email_address,
         id
       ],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
-    tx.commit().map_err(|e| e.to_string())?;
+    tx.commit()?;
+    Ok(())
+  }
+
+  // MARK: Add ban id
+  /// Appends the punishment to `ban_ids` of the player, leaving every other column alone
+  pub fn add_ban_id(&self, player_id: u64, punishment_id: &str) -> Result<()> {
+    let mut ban_ids = self.get_player_by_id(player_id)?.ban_ids.unwrap_or_default();
+    ban_ids.push(punishment_id.to_string());
+    self.conn.execute(
+      "UPDATE Player SET ban_ids = ?1 WHERE player_id = ?2",
+      params![
+        to_json(&ban_ids)?,
+        player_id
+      ],
+    )?;
     Ok(())
   }
 
@@ -319,9 +483,7 @@ impl PlayerDatabase {
   pub fn add_playtime_to_player(&mut self, player_id: u64, amount: f32) -> Result<()> {
     let mut player = self.get_player_by_id(player_id)?;
     player.hours_played += amount;
-    self
-      .modify_player(player_id, player)
-      .map_err(|_| rusqlite::Error::ExecuteReturnedResults)
+    self.update_player(player_id, player)
   }
 
   // MARK: Verifications
@@ -331,8 +493,10 @@ impl PlayerDatabase {
     Ok(verification)
   }
 
-  pub fn add_player_verification(&mut self, data: DatabaseModifyPlayerVerification) -> Result<()> {
-    let players = self.get_players_by_steam(&data.steam_id)?;
+  /// Creates verification for the player with given steam_id
+  /// returns [`Result`] with player_id of the modified player
+  pub fn add_player_verification(&mut self, data: DatabaseModifyPlayerVerification) -> Result<u64> {
+    let players = self.get_players_by_identity(&data.steam_id)?;
 
     if players.is_empty() {
       return Err(rusqlite::Error::QueryReturnedNoRows);
@@ -351,7 +515,8 @@ impl PlayerDatabase {
       PlayerVerification::Created,
       data.discord_id,
       data.code,
-    )
+    )?;
+    Ok(player_db.player_id)
   }
 
   pub fn set_player_verification(
@@ -364,7 +529,8 @@ impl PlayerDatabase {
     let player = self.get_player_by_id(player_id)?;
     let current_date = Utc::now();
 
-    let tx = self.conn.transaction()?;
+    // Savepoint, so this also works inside account link changes
+    let tx = self.conn.savepoint()?;
 
     let update_query = r#"
                 UPDATE Player
@@ -398,6 +564,222 @@ impl PlayerDatabase {
     Ok(())
   }
 
+  // MARK: Account links
+  pub fn get_account_links(&self, discord_id: &str) -> Result<Vec<DatabaseAccountLink>> {
+    let mut stmt = self
+      .conn
+      .prepare("SELECT * FROM AccountLink WHERE discord_id = ?1 ORDER BY linked_at")?;
+    let link_iter = stmt.query_map([discord_id], DatabaseAccountLink::from_row)?;
+
+    let links: Result<Vec<_>, _> = link_iter.collect();
+    links
+  }
+
+  pub fn get_account_link(&self, player_id: u64) -> Result<Option<DatabaseAccountLink>> {
+    self
+      .conn
+      .query_row(
+        "SELECT * FROM AccountLink WHERE player_id = ?1",
+        [player_id],
+        DatabaseAccountLink::from_row,
+      )
+      .optional()
+  }
+
+  /// Verification status a linked player should have, [`PlayerVerification::Full`] for primary account
+  pub fn linked_verification_status(&self, player_id: u64) -> Result<PlayerVerification> {
+    Ok(match self.get_account_link(player_id)? {
+      Some(link) if link.is_primary => PlayerVerification::Full,
+      _ => PlayerVerification::Success,
+    })
+  }
+
+  /// Returns time of the last unlink that would allow trading of the account,
+  /// meaning the player was unlinked from a different Discord user or the Discord user unlinked a different player
+  pub fn get_last_foreign_unlink(
+    &self,
+    player_id: u64,
+    discord_id: &str,
+  ) -> Result<Option<DateTime<Utc>>> {
+    let last_unlink: Option<String> = self.conn.query_row(
+      "SELECT MAX(unlinked_at) FROM AccountUnlink WHERE (player_id = ?1 AND discord_id != ?2) OR (discord_id = ?2 AND player_id != ?1)",
+      params![player_id, discord_id],
+      |row| row.get(0),
+    )?;
+    Ok(parse_rfc3339_to_utc_or_none(last_unlink))
+  }
+
+  /// Links player to the Discord user and marks the player as verified,
+  /// first linked account of the Discord user becomes primary
+  pub fn link_account(&mut self, player_id: u64, discord_id: &str) -> Result<PlayerVerification> {
+    let is_primary = self.get_account_links(discord_id)?.iter().all(|link| link.player_id == player_id);
+    let verified_status = if is_primary {
+      PlayerVerification::Full
+    } else {
+      PlayerVerification::Success
+    };
+
+    self.in_savepoint("link_account", |db| {
+      db.conn.execute(
+        "INSERT OR REPLACE INTO AccountLink (player_id, discord_id, is_primary, linked_at) VALUES (?1, ?2, ?3, ?4)",
+        params![player_id, discord_id, is_primary, Utc::now().to_rfc3339()],
+      )?;
+      db.set_player_verification(player_id, verified_status, Some(discord_id.to_string()), None)?;
+      Ok(verified_status)
+    })
+  }
+
+  pub fn set_primary_account(&mut self, discord_id: &str, player_id: u64) -> Result<()> {
+    match self.get_account_link(player_id)? {
+      Some(link) if link.discord_id == discord_id => {}
+      _ => return Err(rusqlite::Error::QueryReturnedNoRows),
+    }
+
+    // Savepoint, so this also works inside `unlink_account`
+    let tx = self.conn.savepoint()?;
+    tx.execute(
+      "UPDATE AccountLink SET is_primary = (player_id = ?1) WHERE discord_id = ?2",
+      params![player_id, discord_id],
+    )?;
+    // Banned or suspended accounts keep their status
+    tx.execute(
+      "UPDATE Player SET verified_status = CASE WHEN player_id = ?1 THEN ?3 ELSE ?4 END
+       WHERE discord_id = ?2 AND verified_status IN (?3, ?4)",
+      params![
+        player_id,
+        discord_id,
+        PlayerVerification::Full,
+        PlayerVerification::Success
+      ],
+    )?;
+    tx.commit()
+  }
+
+  /// Removes link between player and Discord user, oldest remaining account becomes primary
+  pub fn unlink_account(&mut self, discord_id: &str, player_id: u64) -> Result<()> {
+    let link = match self.get_account_link(player_id)? {
+      Some(link) if link.discord_id == discord_id => link,
+      _ => return Err(rusqlite::Error::QueryReturnedNoRows),
+    };
+
+    self.in_savepoint("unlink_account", |db| {
+      db.conn
+        .execute("DELETE FROM AccountLink WHERE player_id = ?1", [player_id])?;
+      db.conn.execute(
+        "INSERT INTO AccountUnlink (player_id, discord_id, unlinked_at) VALUES (?1, ?2, ?3)",
+        params![player_id, discord_id, Utc::now().to_rfc3339()],
+      )?;
+      db.set_player_verification(
+        player_id,
+        PlayerVerification::None,
+        Some("NULL".to_string()),
+        Some("NULL".to_string()),
+      )?;
+
+      if link.is_primary {
+        if let Some(next) = db.get_account_links(discord_id)?.first() {
+          db.set_primary_account(discord_id, next.player_id)?;
+        }
+      }
+      Ok(())
+    })
+  }
+
+  /// Runs `run` in a savepoint, all of its writes are rolled back when it fails
+  pub(crate) fn in_savepoint<R>(&mut self, name: &str, run: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
+    self.conn.execute_batch(&format!("SAVEPOINT {}", name))?;
+    let result = run(self);
+    let end = match result {
+      Ok(_) => format!("RELEASE {}", name),
+      Err(_) => format!("ROLLBACK TO {0}; RELEASE {0}", name),
+    };
+    self.conn.execute_batch(&end)?;
+    result
+  }
+
+  // MARK: Verification reviews
+  /// Suspends the player and creates pending review of the verification in a single savepoint
+  /// returns [`Result`] with review_id of the created review
+  pub fn suspend_for_review(
+    &mut self,
+    player_id: u64,
+    discord_id: &str,
+    reasons: &[String],
+  ) -> Result<u64> {
+    self.in_savepoint("suspend_for_review", |db| {
+      db.set_player_verification(
+        player_id,
+        PlayerVerification::Suspended,
+        Some(discord_id.to_string()),
+        None,
+      )?;
+      db.create_verification_review(player_id, discord_id, reasons)
+    })
+  }
+
+  /// Creates pending review of player verification
+  /// returns [`Result`] with review_id of the created review
+  pub fn create_verification_review(
+    &self,
+    player_id: u64,
+    discord_id: &str,
+    reasons: &[String],
+  ) -> Result<u64> {
+    self.conn.execute(
+      "INSERT INTO VerificationReview (player_id, discord_id, reasons, created_at, status) VALUES (?1, ?2, ?3, ?4, ?5)",
+      params![
+        player_id,
+        discord_id,
+        serde_json::to_string(reasons).unwrap_or_else(|_| "[]".to_string()),
+        Utc::now().to_rfc3339(),
+        ReviewStatus::Pending
+      ],
+    )?;
+    Ok(self.conn.last_insert_rowid() as u64)
+  }
+
+  pub fn get_verification_review(&self, review_id: u64) -> Result<DatabaseVerificationReview> {
+    self.conn.query_row(
+      "SELECT * FROM VerificationReview WHERE review_id = ?1",
+      [review_id],
+      DatabaseVerificationReview::from_row,
+    )
+  }
+
+  pub fn get_pending_verification_reviews(&self) -> Result<Vec<DatabaseVerificationReview>> {
+    let mut stmt = self
+      .conn
+      .prepare("SELECT * FROM VerificationReview WHERE status = ?1")?;
+    let review_iter = stmt.query_map([ReviewStatus::Pending], DatabaseVerificationReview::from_row)?;
+
+    let reviews: Result<Vec<_>, _> = review_iter.collect();
+    reviews
+  }
+
+  /// Records decision of the reviewer, fails with [`rusqlite::Error::QueryReturnedNoRows`]
+  /// when the review does not exist or was already decided
+  pub fn decide_verification_review(
+    &self,
+    review_id: u64,
+    status: ReviewStatus,
+    reviewer_id: &str,
+  ) -> Result<()> {
+    let updated = self.conn.execute(
+      "UPDATE VerificationReview SET status = ?1, reviewer_id = ?2, decided_at = ?3 WHERE review_id = ?4 AND status = ?5",
+      params![
+        status,
+        reviewer_id,
+        Utc::now().to_rfc3339(),
+        review_id,
+        ReviewStatus::Pending
+      ],
+    )?;
+    if updated == 0 {
+      return Err(rusqlite::Error::QueryReturnedNoRows);
+    }
+    Ok(())
+  }
+
   // MARK: Player count
   pub fn get_player_count(&self) -> Result<Vec<DatabasePlayerCount>> {
     let mut stmt = self.conn.prepare("SELECT * FROM PlayerCount")?;
@@ -434,6 +816,21 @@ impl PlayerDatabase {
     Ok(players)
   }
 
+  /// Hands player counts recorded since `from_timestamp` (all of them when not set) to `on_row` as they're read
+  // MARK: Export player count
+  pub fn export_player_count(
+    &self,
+    from_timestamp: Option<u64>,
+    on_row: impl FnMut(DatabasePlayerCount) -> bool,
+  ) -> Result<()> {
+    self.for_each_row(
+      "SELECT * FROM PlayerCount WHERE ?1 IS NULL OR timestamp >= ?1",
+      [from_timestamp],
+      DatabasePlayerCount::from_row,
+      on_row,
+    )
+  }
+
   pub fn set_player_count_auto(&self, player_count: u32) -> Result<()> {
     let current_timestamp = Utc::now().timestamp() as u64;
 
@@ -455,7 +852,7 @@ impl PlayerDatabase {
   }
 
   pub fn player_joined(&mut self, data: DatabasePlayerJoin) -> Result<DatabasePlayer> {
-    let mut existing_records = self.get_players_by_steam(&data.steam_id)?;
+    let mut existing_records = self.get_players_by_identity(&data.steam_id)?;
 
     if existing_records.len() > 0 {
       let mut player = existing_records[0].clone();
@@ -471,23 +868,26 @@ impl PlayerDatabase {
       player.do_not_track = data.do_not_track;
       player.times_joined += 1;
       player.last_join_date = Utc::now();
-      let _ = self
-        .modify_player(player.player_id, player.clone())
-        .map_err(|_| rusqlite::Error::ExecuteReturnedResults)?;
+      self.update_player(player.player_id, player.clone())?;
       return Ok(player);
     }
     self.add_player(
-      data.steam_id.clone(),
+      &data.steam_id,
       data.username.clone(),
       data.ip_addr.clone(),
       data.do_not_track.clone(),
       Utc::now(),
     )?;
 
-    existing_records = self.get_players_by_steam(&data.steam_id)?;
+    existing_records = self.get_players_by_identity(&data.steam_id)?;
     if existing_records.is_empty() {
       return Err(rusqlite::Error::QueryReturnedNoRows);
     }
     Ok(existing_records[0].clone())
   }
 }
+
+/// Value of a JSON column, serialization failures as database errors
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+  serde_json::to_string(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
@@ -5,19 +5,22 @@ use rusqlite::{
 };
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use utoipa::ToSchema;
 
 use crate::application::utils;
 
+use super::identity::{PlayerIdentity, PlayerPlatform};
+
 #[repr(u8)]
-#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq)]
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, ToSchema)]
 /// # Player verification status
 // MARK: (enum) Player verification
 pub enum PlayerVerification {
     None = 0,      // Not verified
     Created = 1,   // When code created, but not sent
     Pending = 2,   // When sent to player
-    Success = 3,   // Player is verified
-    Full = 4,      // Player has newer verification
+    Success = 3,   // Player is verified (linked, not primary account of the Discord user)
+    Full = 4,      // Player is verified and is the primary account of the Discord user
     Expired = 5,   // When player verification expired
     Banned = 6,    // When player got banned (from this feature or discord)
     Suspended = 7, // When player's verification under review
@@ -54,7 +57,7 @@ impl FromSql for PlayerVerification {
 
 //
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, ToSchema)]
 /// Player
 // MARK: Player
 pub struct DatabasePlayer {
@@ -75,6 +78,8 @@ pub struct DatabasePlayer {
     pub rank_id: Option<u16>,
     pub supporter_id: Option<u32>,
     pub email_address: Option<String>,
+    pub platform: Option<PlayerPlatform>,
+    pub platform_id: Option<String>,
 }
 
 impl DatabasePlayer {
@@ -127,8 +132,20 @@ impl DatabasePlayer {
             rank_id: row.get(14)?,
             supporter_id: row.get(15)?,
             email_address: row.get(16)?,
+            platform: row.get(17)?,
+            platform_id: row.get(18)?,
         })
     }
+    /// Parsed identity of the player, falls back to the stored user ID when the platform columns are empty
+    pub(crate) fn identity(&self) -> Option<PlayerIdentity> {
+        match (&self.platform, &self.platform_id) {
+            (Some(platform), Some(native_id)) => Some(PlayerIdentity {
+                platform: platform.clone(),
+                native_id: native_id.clone(),
+            }),
+            _ => self.steam_id.parse().ok(),
+        }
+    }
     pub(crate) fn is_verified(&self) -> bool {
         matches!(
             self.verified_status,
@@ -143,7 +160,7 @@ impl DatabasePlayer {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
 /// Player count
 // MARK: Player count
 pub struct DatabasePlayerCount {
@@ -162,7 +179,7 @@ impl DatabasePlayerCount {
     }
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, ToSchema)]
 /// Player Verification
 // MARK: (obj) Player verification
 pub struct DatabasePlayerVerification {
@@ -190,23 +207,121 @@ impl From<DatabasePlayer> for DatabasePlayerVerification {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 /// Player verification modification
 // MARK: (obj) Player verification modification
 pub struct DatabaseModifyPlayerVerification {
     pub player_id: u64,
-    pub steam_id: String,
+    pub steam_id: PlayerIdentity,
     pub discord_id: Option<String>,
     pub verified_status: PlayerVerification,
     pub code: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 /// Player Join
 // MARK: (obj) Player join
 pub struct DatabasePlayerJoin {
     pub username: String,
-    pub steam_id: String,
+    pub steam_id: PlayerIdentity,
     pub ip_addr: String,
     pub do_not_track: bool,
 }
+
+#[repr(u8)]
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, ToSchema)]
+/// # Verification review status
+// MARK: (enum) Review status
+pub enum ReviewStatus {
+    Pending = 0,
+    Approved = 1,
+    Rejected = 2,
+}
+
+impl ToSql for ReviewStatus {
+    fn to_sql(&self) -> Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::from(*self as u8))
+    }
+}
+
+impl FromSql for ReviewStatus {
+    fn column_result(value: ValueRef<'_>) -> Result<ReviewStatus, FromSqlError> {
+        let int_value = value.as_i64()?;
+        match int_value {
+            0 => Ok(ReviewStatus::Pending),
+            1 => Ok(ReviewStatus::Approved),
+            2 => Ok(ReviewStatus::Rejected),
+            _ => Err(FromSqlError::OutOfRange(int_value)),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+/// Verification review
+// MARK: (obj) Verification review
+pub struct DatabaseVerificationReview {
+    pub review_id: u64,
+    pub player_id: u64,
+    pub discord_id: String,
+    pub reasons: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub status: ReviewStatus,
+    pub reviewer_id: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+impl DatabaseVerificationReview {
+    pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+        let reasons: Vec<String> =
+            serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default();
+        let created_at = match utils::time::parse_rfc3339_to_utc(row.get::<_, String>(4)?) {
+            Ok(val) => val,
+            Err(_) => Utc::now(),
+        };
+
+        Ok(DatabaseVerificationReview {
+            review_id: row.get(0)?,
+            player_id: row.get(1)?,
+            discord_id: row.get(2)?,
+            reasons,
+            created_at,
+            status: row.get(5)?,
+            reviewer_id: row.get(6)?,
+            decided_at: utils::time::parse_rfc3339_to_utc_or_none(row.get(7)?),
+        })
+    }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+/// Link between Discord user and one of their game accounts
+// MARK: (obj) Account link
+pub struct DatabaseAccountLink {
+    pub player_id: u64,
+    pub discord_id: String,
+    pub is_primary: bool,
+    pub linked_at: DateTime<Utc>,
+}
+
+impl DatabaseAccountLink {
+    pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+        let linked_at = match utils::time::parse_rfc3339_to_utc(row.get::<_, String>(3)?) {
+            Ok(val) => val,
+            Err(_) => Utc::now(),
+        };
+
+        Ok(DatabaseAccountLink {
+            player_id: row.get(0)?,
+            discord_id: row.get(1)?,
+            is_primary: row.get(2)?,
+            linked_at,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+/// Account link modification
+// MARK: (obj) Account link modification
+pub struct DatabaseModifyAccountLink {
+    pub discord_id: String,
+    pub player_id: u64,
+}
@@ -1,13 +1,18 @@
 pub(crate) mod structs;
 
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng as _};
 use rusqlite::{Connection, Result};
 
+use crate::application::features::metrics;
 use crate::logger::{LogLevel, Logger};
 use crate::{log_d, log_e, log_x};
 
 use self::structs::DatabasePunishment;
 
-use super::database::DatabaseOperations;
+use super::player::identity::PlayerIdentity;
+
+use super::database::{open_reader, DatabaseOperations};
 
 #[derive(Debug)]
 pub struct PunishmentDatabase {
@@ -25,7 +30,7 @@ impl Clone for PunishmentDatabase {
 impl DatabaseOperations for PunishmentDatabase {
     fn setup(db_path: &str) -> Result<Self> {
         log_d!("Starting 'Punishment' database");
-        let conn = match Connection::open(db_path) {
+        let mut conn = match Connection::open(db_path) {
             Ok(val) => val,
             Err(e) => {
                 log_e!(format!(
@@ -35,6 +40,7 @@ impl DatabaseOperations for PunishmentDatabase {
                 return Err(e);
             }
         };
+        conn.profile(metrics::query_profiler("punishments"));
 
         if let Err(e) = conn.execute(
             r"CREATE TABLE IF NOT EXISTS Punishment (
@@ -65,10 +71,31 @@ impl DatabaseOperations for PunishmentDatabase {
             conn,
         })
     }
+
+    fn connection(&self) -> &Connection {
+        &self.conn
+    }
 }
 
 // MARK: (!) Impl Punishment Db
 impl PunishmentDatabase {
+    /// Read-only connection of its own, for exports that shouldn't hold the database lock
+    pub fn open_reader(&self) -> Result<Self> {
+        Ok(PunishmentDatabase {
+            path: self.path.clone(),
+            conn: open_reader(&self.path, "punishments")?,
+        })
+    }
+
+    // MARK: Generate punishment ID
+    pub fn generate_punishment_id() -> String {
+        thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect()
+    }
+
     // MARK: Get all punishments
     pub fn get_all_punishments(&self) -> rusqlite::Result<Vec<DatabasePunishment>> {
         let mut stmt = self.conn.prepare("SELECT * FROM Punishment")?;
@@ -80,6 +107,20 @@ impl PunishmentDatabase {
         punishments
     }
 
+    // MARK: Export punishments
+    /// Hands every punishment to `on_row` as it's read, see [`DatabaseOperations::for_each_row`]
+    pub fn export_punishments(
+        &self,
+        on_row: impl FnMut(DatabasePunishment) -> bool,
+    ) -> rusqlite::Result<()> {
+        self.for_each_row(
+            "SELECT * FROM Punishment",
+            [],
+            DatabasePunishment::from_row,
+            on_row,
+        )
+    }
+
     // MARK: Get punishment by punishment ID
     pub fn get_punishment_by_punishment_id(
         &self,
@@ -119,12 +160,12 @@ impl PunishmentDatabase {
     // MARK: Get punishments by steam ID
     pub fn get_punishments_by_steam_id(
         &self,
-        steam_id: String,
+        identity: &PlayerIdentity,
     ) -> rusqlite::Result<Vec<DatabasePunishment>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT * FROM Punishment WHERE steam_id = ?1")?;
-        let punishment_iter = stmt.query_map([steam_id], |row| {
+            .prepare("SELECT * FROM Punishment WHERE steam_id IN (?1, ?2)")?;
+        let punishment_iter = stmt.query_map([identity.user_id(), identity.native_id.clone()], |row| {
             Ok(DatabasePunishment::from_row(row).unwrap())
         })?;
 
@@ -163,6 +204,15 @@ impl PunishmentDatabase {
         punishments
     }
 
+    // MARK: Delete punishment
+    pub fn delete_punishment(&self, punishment_id: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "DELETE FROM Punishment WHERE punishment_id = ?1",
+            [punishment_id],
+        )?;
+        Ok(())
+    }
+
     // MARK: New punishment
     pub fn create_new_punishment(&self, data: &DatabasePunishment) -> rusqlite::Result<()> {
         self.conn.execute("INSERT INTO Punishment (`punishment_id`, `player_id`, `username`, `steam_id`, `ip`, `reason`, `punishment_duration`, `punishment_created_at`, `issuer_steam_id`, `issuer_name`, `issuer_ip`, `punishment_type`)\
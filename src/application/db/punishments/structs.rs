@@ -5,11 +5,12 @@ use rusqlite::{
 };
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use utoipa::ToSchema;
 
 use crate::application::utils;
 
 #[repr(u8)]
-#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq)]
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, ToSchema)]
 pub enum PunishmentType {
     None = 0,
     Ban = 1,
@@ -42,7 +43,7 @@ impl FromSql for PunishmentType {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
 pub struct DatabasePunishment {
     pub punishment_id: String,
     pub player_id: u64,
@@ -0,0 +1,239 @@
+pub(crate) mod structs;
+
+use chrono::DateTime;
+use rusqlite::{params, Connection, OptionalExtension as _, Result};
+
+use self::structs::{DatabaseServerIncident, DatabaseStatusSample, UptimeReport};
+
+use super::database::DatabaseOperations;
+use crate::application::features::metrics;
+use crate::logger::{LogLevel, Logger};
+use crate::{log_d, log_e, log_x};
+
+/// Most samples returned by one history query
+const MAX_HISTORY_SAMPLES: u32 = 10_000;
+
+#[derive(Debug)]
+pub struct StatusDatabase {
+  path: String,
+  conn: Connection,
+}
+
+impl Clone for StatusDatabase {
+  fn clone(&self) -> Self {
+    StatusDatabase::setup(&self.path).unwrap()
+  }
+}
+
+// MARK: (!) Init Status Db
+impl DatabaseOperations for StatusDatabase {
+  fn setup(db_path: &str) -> Result<Self> {
+    log_d!("Starting 'Status' database");
+    let mut conn = match Connection::open(db_path) {
+      Ok(val) => val,
+      Err(e) => {
+        log_e!(format!(
+          "Database 'Status' threw error while opening: {}",
+          e
+        ));
+        return Err(e);
+      }
+    };
+    conn.profile(metrics::query_profiler("status"));
+
+    if let Err(e) = conn.execute_batch(
+      r"CREATE TABLE IF NOT EXISTS ServerStatusSample (
+              server_id INT NOT NULL,
+              timestamp INT NOT NULL,
+              open TINYINT NOT NULL,
+              tps TINYINT NOT NULL,
+              max_tps TINYINT NOT NULL,
+              player_count INT NOT NULL,
+              max_player_count INT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS ServerStatusSampleTime ON ServerStatusSample (server_id, timestamp);",
+    ) {
+      log_e!(format!(
+        "Database 'Status' threw error while creating table 'ServerStatusSample': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    if let Err(e) = conn.execute(
+      r"CREATE TABLE IF NOT EXISTS ServerIncident (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              server_id INT NOT NULL,
+              started_at INT NOT NULL,
+              ended_at INT,
+              reason TEXT NOT NULL
+            );",
+      (),
+    ) {
+      log_e!(format!(
+        "Database 'Status' threw error while creating table 'ServerIncident': {}",
+        e
+      ));
+      return Err(e);
+    }
+
+    Ok(Self {
+      path: db_path.to_string(),
+      conn,
+    })
+  }
+
+  fn connection(&self) -> &Connection {
+    &self.conn
+  }
+}
+
+// MARK: (!) Impl Status Db
+impl StatusDatabase {
+  // MARK: Add sample
+  pub fn add_sample(&self, sample: &DatabaseStatusSample) -> Result<()> {
+    self.conn.execute(
+      "INSERT INTO ServerStatusSample (server_id, timestamp, open, tps, max_tps, player_count, max_player_count) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+      params![
+        sample.server_id,
+        sample.timestamp,
+        sample.open,
+        sample.tps,
+        sample.max_tps,
+        sample.player_count,
+        sample.max_player_count
+      ],
+    )?;
+    Ok(())
+  }
+
+  // MARK: Get samples
+  /// Samples of the server within `from..=to`, oldest first
+  pub fn get_samples(
+    &self,
+    server_id: u64,
+    from: i64,
+    to: i64,
+  ) -> Result<Vec<DatabaseStatusSample>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT server_id, timestamp, open, tps, max_tps, player_count, max_player_count FROM ServerStatusSample
+       WHERE server_id = ?1 AND timestamp >= ?2 AND timestamp <= ?3 ORDER BY timestamp LIMIT ?4",
+    )?;
+    let data_iter = stmt.query_map(
+      params![server_id, from, to, MAX_HISTORY_SAMPLES],
+      DatabaseStatusSample::from_row,
+    )?;
+
+    let data: Result<Vec<_>, _> = data_iter.collect();
+    data
+  }
+
+  // MARK: Open incident
+  /// Starts an incident, unless the server already has one going on
+  pub fn open_incident(&self, server_id: u64, started_at: i64, reason: &str) -> Result<()> {
+    self.conn.execute(
+      "INSERT INTO ServerIncident (server_id, started_at, reason)
+       SELECT ?1, ?2, ?3 WHERE NOT EXISTS (SELECT 1 FROM ServerIncident WHERE server_id = ?1 AND ended_at IS NULL)",
+      params![server_id, started_at, reason],
+    )?;
+    Ok(())
+  }
+
+  // MARK: Close incidents
+  pub fn close_incidents(&self, server_id: u64, ended_at: i64) -> Result<()> {
+    self.conn.execute(
+      "UPDATE ServerIncident SET ended_at = ?2 WHERE server_id = ?1 AND ended_at IS NULL",
+      params![server_id, ended_at],
+    )?;
+    Ok(())
+  }
+
+  // MARK: Get incidents
+  /// Incidents of the server overlapping `from..to`, oldest first
+  pub fn get_incidents(
+    &self,
+    server_id: u64,
+    from: i64,
+    to: i64,
+  ) -> Result<Vec<DatabaseServerIncident>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT id, server_id, started_at, ended_at, reason FROM ServerIncident
+       WHERE server_id = ?1 AND started_at < ?3 AND (ended_at IS NULL OR ended_at > ?2) ORDER BY started_at",
+    )?;
+    let data_iter = stmt.query_map(
+      params![server_id, from, to],
+      DatabaseServerIncident::from_row,
+    )?;
+
+    let data: Result<Vec<_>, _> = data_iter.collect();
+    data
+  }
+
+  // MARK: Get uptime report
+  /// Uptime over `from..to`, counted from the first status the server ever sent
+  pub fn get_uptime_report(
+    &self,
+    server_id: u64,
+    from: i64,
+    to: i64,
+    now: i64,
+  ) -> Result<UptimeReport> {
+    let first_sample: Option<i64> = self
+      .conn
+      .query_row(
+        "SELECT MIN(timestamp) FROM ServerStatusSample WHERE server_id = ?1",
+        [server_id],
+        |row| row.get(0),
+      )
+      .optional()?
+      .flatten();
+    let tracked_from = first_sample.map_or(to, |first_sample| first_sample.max(from));
+    let tracked_to = to.min(now);
+    let tracked_secs = (tracked_to - tracked_from).max(0);
+
+    let incidents = self.get_incidents(server_id, tracked_from, tracked_to)?;
+    let downtime_secs: i64 = incidents
+      .iter()
+      .map(|incident| incident.downtime_within(tracked_from, tracked_to, now))
+      .sum::<i64>()
+      .min(tracked_secs);
+
+    let (average_tps, average_players, peak_players, samples) = self.conn.query_row(
+      "SELECT AVG(tps), AVG(player_count), MAX(player_count), COUNT(*) FROM ServerStatusSample
+       WHERE server_id = ?1 AND timestamp >= ?2 AND timestamp <= ?3 AND open = 1",
+      params![server_id, from, to],
+      |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )?;
+
+    Ok(UptimeReport {
+      server_id,
+      from: DateTime::from_timestamp(from, 0).unwrap_or_default(),
+      to: DateTime::from_timestamp(to, 0).unwrap_or_default(),
+      tracked_secs,
+      downtime_secs,
+      uptime_percent: match tracked_secs {
+        0 => None,
+        _ => Some(100.0 * (tracked_secs - downtime_secs) as f64 / tracked_secs as f64),
+      },
+      incidents: incidents.len() as u32,
+      average_tps,
+      average_players,
+      peak_players,
+      samples,
+    })
+  }
+
+  // MARK: Remove old history
+  /// Drops samples taken and incidents ended before `timestamp`, ongoing incidents are kept
+  pub fn remove_history_before(&self, timestamp: i64) -> Result<usize> {
+    let samples = self.conn.execute(
+      "DELETE FROM ServerStatusSample WHERE timestamp < ?1",
+      [timestamp],
+    )?;
+    let incidents = self.conn.execute(
+      "DELETE FROM ServerIncident WHERE ended_at IS NOT NULL AND ended_at < ?1",
+      [timestamp],
+    )?;
+    Ok(samples + incidents)
+  }
+}
@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use rusqlite::Result;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Clone, Serialize, ToSchema)]
+/// Status of a server at one point in time
+// MARK: (struct) Status sample
+pub struct DatabaseStatusSample {
+  pub server_id: u64,
+  /// Unix time in seconds
+  pub timestamp: i64,
+  pub open: bool,
+  pub tps: u8,
+  pub max_tps: u8,
+  pub player_count: u16,
+  pub max_player_count: u16,
+}
+
+impl DatabaseStatusSample {
+  pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+    Ok(DatabaseStatusSample {
+      server_id: row.get(0)?,
+      timestamp: row.get(1)?,
+      open: row.get(2)?,
+      tps: row.get(3)?,
+      max_tps: row.get(4)?,
+      player_count: row.get(5)?,
+      max_player_count: row.get(6)?,
+    })
+  }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+/// Time a server was down, `ended_at` is not set while it still is
+// MARK: (struct) Server incident
+pub struct DatabaseServerIncident {
+  pub id: u64,
+  pub server_id: u64,
+  pub started_at: DateTime<Utc>,
+  pub ended_at: Option<DateTime<Utc>>,
+  /// `closed` when the server reported it, `timed_out` when it stopped sending its status
+  pub reason: String,
+}
+
+impl DatabaseServerIncident {
+  pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+    Ok(DatabaseServerIncident {
+      id: row.get(0)?,
+      server_id: row.get(1)?,
+      started_at: DateTime::from_timestamp(row.get(2)?, 0).unwrap_or_default(),
+      ended_at: row
+        .get::<_, Option<i64>>(3)?
+        .and_then(|ended_at| DateTime::from_timestamp(ended_at, 0)),
+      reason: row.get(4)?,
+    })
+  }
+
+  /// Seconds of the incident that fall into `from..to`, incidents still going on last until `now`
+  pub fn downtime_within(&self, from: i64, to: i64, now: i64) -> i64 {
+    let started_at = self.started_at.timestamp().max(from);
+    let ended_at = self.ended_at.map_or(now, |val| val.timestamp()).min(to);
+    (ended_at - started_at).max(0)
+  }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+/// Uptime and performance of a server over a period
+// MARK: (struct) Uptime report
+pub struct UptimeReport {
+  pub server_id: u64,
+  pub from: DateTime<Utc>,
+  pub to: DateTime<Utc>,
+  /// Seconds of the period the server was tracked, history starts with the first status it sent
+  pub tracked_secs: i64,
+  pub downtime_secs: i64,
+  /// Not set when the server wasn't tracked during the period
+  pub uptime_percent: Option<f64>,
+  pub incidents: u32,
+  /// Averages of the samples taken while the server was open
+  pub average_tps: Option<f64>,
+  pub average_players: Option<f64>,
+  pub peak_players: Option<u16>,
+  pub samples: u32,
+}
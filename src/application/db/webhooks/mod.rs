@@ -0,0 +1,163 @@
+pub(crate) mod structs;
+
+use rusqlite::{params, Connection, Result};
+
+use self::structs::{DatabaseWebhookDelivery, DeliveryAttempt, WebhookDeliveryStatus};
+
+use super::database::DatabaseOperations;
+use crate::application::features::metrics;
+use crate::logger::{LogLevel, Logger};
+use crate::{log_d, log_e, log_x};
+
+#[derive(Debug)]
+pub struct WebhookDatabase {
+  path: String,
+  conn: Connection,
+}
+
+impl Clone for WebhookDatabase {
+  fn clone(&self) -> Self {
+    WebhookDatabase::setup(&self.path).unwrap()
+  }
+}
+
+// MARK: (!) Init Webhook Db
+impl DatabaseOperations for WebhookDatabase {
+  fn setup(db_path: &str) -> Result<Self> {
+    log_d!("Starting 'Webhook' database");
+    let mut conn = match Connection::open(db_path) {
+      Ok(val) => val,
+      Err(e) => {
+        log_e!(format!(
+          "Database 'Webhook' threw error while opening: {}",
+          e
+        ));
+        return Err(e);
+      }
+    };
+    conn.profile(metrics::query_profiler("webhooks"));
+
+    if let Err(e) = conn.execute_batch(
+      r"CREATE TABLE IF NOT EXISTS WebhookDelivery (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              webhook TEXT NOT NULL,
+              event TEXT NOT NULL,
+              payload TEXT NOT NULL,
+              status TEXT NOT NULL,
+              attempts INT NOT NULL DEFAULT 0,
+              response_status INT,
+              error TEXT,
+              created_at INT NOT NULL,
+              last_attempt_at INT,
+              next_attempt_at INT
+            );
+            CREATE INDEX IF NOT EXISTS WebhookDeliveryCreated ON WebhookDelivery (created_at);
+            CREATE INDEX IF NOT EXISTS WebhookDeliveryNextAttempt ON WebhookDelivery (status, next_attempt_at);",
+    ) {
+      log_e!(format!(
+        "Database 'Webhook' threw error while creating table 'WebhookDelivery': {}",
+        e
+      ));
+      return Err(e);
+    }
+    Ok(Self {
+      path: db_path.to_string(),
+      conn,
+    })
+  }
+
+  fn connection(&self) -> &Connection {
+    &self.conn
+  }
+}
+
+// MARK: (!) Impl Webhook Db
+impl WebhookDatabase {
+  // MARK: Add delivery
+  /// Logs a pending delivery due right away, returns its ID
+  pub fn add_delivery(
+    &self,
+    webhook: &str,
+    event: &str,
+    payload: &str,
+    created_at: i64,
+  ) -> Result<u64> {
+    self.conn.execute(
+      "INSERT INTO WebhookDelivery (webhook, event, payload, status, created_at, next_attempt_at) VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+      params![webhook, event, payload, WebhookDeliveryStatus::Pending, created_at],
+    )?;
+    Ok(self.conn.last_insert_rowid() as u64)
+  }
+
+  // MARK: Record attempt
+  /// Stores the outcome of an attempt, `next_attempt_at` is only set while the delivery stays pending
+  pub fn record_attempt(&self, id: u64, attempt: &DeliveryAttempt) -> Result<()> {
+    self.conn.execute(
+      "UPDATE WebhookDelivery SET status = ?2, attempts = ?3, response_status = ?4, error = ?5, last_attempt_at = ?6, next_attempt_at = ?7 WHERE id = ?1",
+      params![
+        id,
+        attempt.status,
+        attempt.attempts,
+        attempt.response_status,
+        attempt.error,
+        attempt.attempted_at,
+        attempt.next_attempt_at
+      ],
+    )?;
+    Ok(())
+  }
+
+  // MARK: Get due deliveries
+  /// Pending deliveries whose next attempt is due, oldest first
+  pub fn get_due_deliveries(&self, now: i64, limit: u32) -> Result<Vec<DatabaseWebhookDelivery>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT * FROM WebhookDelivery WHERE status = ?1 AND next_attempt_at <= ?2
+       ORDER BY next_attempt_at, id LIMIT ?3",
+    )?;
+    let data_iter = stmt.query_map(
+      params![WebhookDeliveryStatus::Pending, now, limit],
+      DatabaseWebhookDelivery::from_row,
+    )?;
+
+    let data: Result<Vec<_>, _> = data_iter.collect();
+    data
+  }
+
+  // MARK: Get delivery
+  pub fn get_delivery(&self, id: u64) -> Result<DatabaseWebhookDelivery> {
+    self.conn.query_row(
+      "SELECT * FROM WebhookDelivery WHERE id = ?1",
+      [id],
+      DatabaseWebhookDelivery::from_row,
+    )
+  }
+
+  // MARK: Get deliveries
+  /// Latest deliveries first, optionally of one webhook or with one status
+  pub fn get_deliveries(
+    &self,
+    webhook: Option<&str>,
+    status: Option<WebhookDeliveryStatus>,
+    limit: u32,
+  ) -> Result<Vec<DatabaseWebhookDelivery>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT * FROM WebhookDelivery WHERE (?1 IS NULL OR webhook = ?1) AND (?2 IS NULL OR status = ?2)
+       ORDER BY id DESC LIMIT ?3",
+    )?;
+    let data_iter = stmt.query_map(
+      params![webhook, status, limit],
+      DatabaseWebhookDelivery::from_row,
+    )?;
+
+    let data: Result<Vec<_>, _> = data_iter.collect();
+    data
+  }
+
+  // MARK: Remove old deliveries
+  pub fn remove_deliveries_before(&self, timestamp: i64) -> Result<usize> {
+    self.conn.execute(
+      "DELETE FROM WebhookDelivery WHERE created_at < ?1 AND status != ?2",
+      params![timestamp, WebhookDeliveryStatus::Pending],
+    )
+  }
+}
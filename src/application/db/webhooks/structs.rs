@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{
+  types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+  Result, ToSql,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+/// # Delivery status
+// MARK: (enum) Delivery status
+pub enum WebhookDeliveryStatus {
+  /// Waiting for its first or next attempt
+  Pending,
+  Delivered,
+  /// Every attempt failed
+  Failed,
+}
+
+impl WebhookDeliveryStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      WebhookDeliveryStatus::Pending => "pending",
+      WebhookDeliveryStatus::Delivered => "delivered",
+      WebhookDeliveryStatus::Failed => "failed",
+    }
+  }
+
+  pub fn from_name(status: &str) -> Option<Self> {
+    [
+      WebhookDeliveryStatus::Pending,
+      WebhookDeliveryStatus::Delivered,
+      WebhookDeliveryStatus::Failed,
+    ]
+    .into_iter()
+    .find(|val| val.as_str() == status)
+  }
+}
+
+impl ToSql for WebhookDeliveryStatus {
+  fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+    Ok(ToSqlOutput::from(self.as_str()))
+  }
+}
+
+impl FromSql for WebhookDeliveryStatus {
+  fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+    let status = value.as_str()?;
+    WebhookDeliveryStatus::from_name(status)
+      .ok_or_else(|| FromSqlError::Other(format!("Unknown delivery status '{}'", status).into()))
+  }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+/// One event sent to one webhook, with the outcome of its last attempt
+// MARK: (struct) Webhook delivery
+pub struct DatabaseWebhookDelivery {
+  pub id: u64,
+  /// Name of the webhook in the config
+  pub webhook: String,
+  pub event: String,
+  /// JSON body that was sent
+  pub payload: String,
+  pub status: WebhookDeliveryStatus,
+  pub attempts: u32,
+  /// HTTP status the endpoint answered the last attempt with
+  pub response_status: Option<u16>,
+  /// Why the last attempt failed
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub last_attempt_at: Option<DateTime<Utc>>,
+  /// When a pending delivery is tried next
+  pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+impl DatabaseWebhookDelivery {
+  pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
+    Ok(DatabaseWebhookDelivery {
+      id: row.get(0)?,
+      webhook: row.get(1)?,
+      event: row.get(2)?,
+      payload: row.get(3)?,
+      status: row.get(4)?,
+      attempts: row.get(5)?,
+      response_status: row.get(6)?,
+      error: row.get(7)?,
+      created_at: DateTime::from_timestamp(row.get(8)?, 0).unwrap_or_default(),
+      last_attempt_at: row
+        .get::<_, Option<i64>>(9)?
+        .and_then(|last_attempt_at| DateTime::from_timestamp(last_attempt_at, 0)),
+      next_attempt_at: row
+        .get::<_, Option<i64>>(10)?
+        .and_then(|next_attempt_at| DateTime::from_timestamp(next_attempt_at, 0)),
+    })
+  }
+}
+
+/// Outcome of one delivery attempt
+pub struct DeliveryAttempt {
+  pub status: WebhookDeliveryStatus,
+  /// Attempts made so far, this one included
+  pub attempts: u32,
+  pub response_status: Option<u16>,
+  pub error: Option<String>,
+  pub attempted_at: i64,
+  pub next_attempt_at: Option<i64>,
+}
@@ -0,0 +1,72 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::application::db::{audit::structs::AuditActor, database_handler::DatabaseHandler};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_w, log_x};
+
+/// Actions written to the audit log, `<subject>.<verb>`
+pub mod actions {
+  pub const PUNISHMENT_CREATE: &str = "punishment.create";
+  pub const PUNISHMENT_REVOKE: &str = "punishment.revoke";
+  pub const VERIFICATION_ADD: &str = "verification.add";
+  pub const VERIFICATION_VERIFY: &str = "verification.verify";
+  pub const VERIFICATION_SET: &str = "verification.set";
+  pub const VERIFICATION_REVIEW: &str = "verification.review";
+  pub const VERIFICATION_DISCORD_SYNC: &str = "verification.discord_sync";
+  pub const LINK_PRIMARY: &str = "link.primary";
+  pub const LINK_UNLINK: &str = "link.unlink";
+  pub const LEADERBOARD_CLEAR: &str = "leaderboard.clear";
+  pub const LEADERBOARD_REMOVE: &str = "leaderboard.remove";
+  pub const SERVER_COMMAND: &str = "server.command";
+  pub const API_KEY_MINT: &str = "api_key.mint";
+  pub const WEBHOOK_TEST: &str = "webhook.test";
+}
+
+/// Mutating routes without audit entries (OpenAPI paths), game server telemetry.
+/// Servers send it on every join, tick and log line with their own key, entries for it would
+/// bury staff actions and repeat data the player, status and leaderboard databases keep anyway
+pub const UNAUDITED_ROUTES: &[&str] = &[
+  "/api/v1/servers/{server_id}/joins",
+  "/api/v1/servers/{server_id}/joins/batch",
+  "/api/v1/servers/{server_id}/status",
+  "/api/v1/servers/{server_id}/logs",
+  "/api/v1/servers/{server_id}/logs/{translation}",
+  "/api/v1/player-counts",
+  "/api/v1/leaderboards",
+  "/api/v1/leaderboards/batch",
+];
+
+/// `<kind>:<id>` target of an audit entry
+pub fn target(kind: &str, id: impl std::fmt::Display) -> Option<String> {
+  Some(format!("{}:{}", kind, id))
+}
+
+/// Appends to the audit log, pass `()` for a missing snapshot.
+/// A failed write is logged, it doesn't undo the change
+// MARK: Record
+pub fn record(
+  db_handler: &DatabaseHandler,
+  actor: &AuditActor,
+  action: &str,
+  target: Option<String>,
+  before: impl Serialize,
+  after: impl Serialize,
+) {
+  let before = serde_json::to_value(before).unwrap_or(Value::Null);
+  let after = serde_json::to_value(after).unwrap_or(Value::Null);
+  if let Err(e) = db_handler.audit_database.add_entry(
+    Utc::now().timestamp(),
+    actor,
+    action,
+    target.as_deref(),
+    &before,
+    &after,
+  ) {
+    log_w!(format!(
+      "Couldn't write '{}' by {} to the audit log: {}",
+      action, actor.name, e
+    ));
+  }
+}
@@ -0,0 +1,137 @@
+use std::{
+  collections::VecDeque,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+  },
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+/// Events kept for `Last-Event-ID` resume
+const EVENT_BUFFER_SIZE: usize = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+/// # Event kind
+// MARK: (enum) Event kind
+pub enum EventKind {
+  ServerStatus,
+  PlayerJoin,
+  PunishmentCreated,
+  PunishmentRevoked,
+  VerificationChanged,
+  ServerUp,
+  ServerDown,
+  Log,
+}
+
+impl EventKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      EventKind::ServerStatus => "server_status",
+      EventKind::PlayerJoin => "player_join",
+      EventKind::PunishmentCreated => "punishment_created",
+      EventKind::PunishmentRevoked => "punishment_revoked",
+      EventKind::VerificationChanged => "verification_changed",
+      EventKind::ServerUp => "server_up",
+      EventKind::ServerDown => "server_down",
+      EventKind::Log => "log",
+    }
+  }
+
+  pub fn from_name(kind: &str) -> Option<Self> {
+    [
+      EventKind::ServerStatus,
+      EventKind::PlayerJoin,
+      EventKind::PunishmentCreated,
+      EventKind::PunishmentRevoked,
+      EventKind::VerificationChanged,
+      EventKind::ServerUp,
+      EventKind::ServerDown,
+      EventKind::Log,
+    ]
+    .into_iter()
+    .find(|val| val.as_str() == kind)
+  }
+}
+
+/// # District event
+/// Something that happened, as sent to `/api/v1/events` subscribers
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct DistrictEvent {
+  pub id: u64,
+  pub kind: EventKind,
+  /// Server the event happened on, if it is tied to one
+  pub server_id: Option<u64>,
+  pub timestamp: DateTime<Utc>,
+  pub data: Value,
+}
+
+/// # Event bus
+/// Broadcasts events to subscribers and keeps the last [`EVENT_BUFFER_SIZE`] of them
+// MARK: Event bus
+#[derive(Debug)]
+pub struct EventBus {
+  sender: broadcast::Sender<DistrictEvent>,
+  buffer: Mutex<VecDeque<DistrictEvent>>,
+  /// Starts at boot time in milliseconds, so ids keep growing across restarts
+  next_id: AtomicU64,
+}
+
+impl EventBus {
+  pub fn new() -> Self {
+    let (sender, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+    EventBus {
+      sender,
+      buffer: Mutex::new(VecDeque::with_capacity(EVENT_BUFFER_SIZE)),
+      next_id: AtomicU64::new(Utc::now().timestamp_millis() as u64),
+    }
+  }
+
+  pub fn publish(&self, kind: EventKind, server_id: Option<u64>, data: impl Serialize) {
+    let event = DistrictEvent {
+      id: self.next_id.fetch_add(1, Ordering::SeqCst),
+      kind,
+      server_id,
+      timestamp: Utc::now(),
+      data: serde_json::to_value(data).unwrap_or(Value::Null),
+    };
+
+    let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+    if buffer.len() >= EVENT_BUFFER_SIZE {
+      buffer.pop_front();
+    }
+    buffer.push_back(event.clone());
+    // No subscribers is not an error
+    let _ = self.sender.send(event);
+  }
+
+  /// Buffered events after `last_event_id` and a receiver for the following ones
+  pub fn subscribe(
+    &self,
+    last_event_id: Option<u64>,
+  ) -> (Vec<DistrictEvent>, broadcast::Receiver<DistrictEvent>) {
+    let buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+    let receiver = self.sender.subscribe();
+    let backlog = match last_event_id {
+      Some(last_event_id) => buffer
+        .iter()
+        .filter(|event| event.id > last_event_id)
+        .cloned()
+        .collect(),
+      None => vec![],
+    };
+    (backlog, receiver)
+  }
+}
+
+impl Default for EventBus {
+  fn default() -> Self {
+    EventBus::new()
+  }
+}
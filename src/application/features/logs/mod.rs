@@ -7,7 +7,12 @@ use serde::{Deserialize, Deserializer};
 use serde_json::{Number, Value};
 
 use tokio::sync::Mutex;
+use utoipa::{
+  openapi::{schema::Type, ArrayBuilder, ObjectBuilder, OneOfBuilder, RefOr, Schema},
+  PartialSchema, ToSchema,
+};
 
+use super::events::EventKind;
 use super::lang::get_translation;
 
 #[derive(Clone, Eq, PartialEq)]
@@ -19,6 +24,21 @@ pub enum JsonValueNotObject {
   Array(Vec<String>),
 }
 
+impl PartialSchema for JsonValueNotObject {
+  fn schema() -> RefOr<Schema> {
+    OneOfBuilder::new()
+      .item(ObjectBuilder::new().schema_type(Type::Null))
+      .item(ObjectBuilder::new().schema_type(Type::Boolean))
+      .item(ObjectBuilder::new().schema_type(Type::Number))
+      .item(ObjectBuilder::new().schema_type(Type::String))
+      .item(ArrayBuilder::new().items(ObjectBuilder::new().schema_type(Type::String)))
+      .description(Some("Any JSON value except objects"))
+      .into()
+  }
+}
+
+impl ToSchema for JsonValueNotObject {}
+
 impl<'de> Deserialize<'de> for JsonValueNotObject {
   fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
   where
@@ -73,12 +93,6 @@ pub async fn handle_log_with_data(
     return Ok(());
   }
 
-  message = format!(
-    "{}: {}",
-    get_discord_timestamp(application.translations.as_ref()),
-    message
-  );
-
   for (key, value) in parsed_data.iter() {
     let value_str = match value {
       JsonValueNotObject::String(s) => s.clone(),
@@ -90,6 +104,17 @@ pub async fn handle_log_with_data(
     message = message.replace(&format!("{{{}}}", key), &value_str);
   }
 
+  application.events.publish(
+    EventKind::Log,
+    Some(server_id),
+    serde_json::json!({ "translation": translation, "message": message }),
+  );
+  message = format!(
+    "{}: {}",
+    get_discord_timestamp(application.translations.as_ref()),
+    message
+  );
+
   match server.send_message(message).await {
     Ok(_) => Ok(()),
     Err(e) => Err(e.to_string()),
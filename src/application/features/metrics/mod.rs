@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use prometheus::{
+  Encoder as _, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+  TextEncoder,
+};
+
+use crate::application::server::server::DistrictServer;
+
+lazy_static! {
+  /// Metrics of the whole application, served on `/metrics`
+  pub static ref METRICS: Metrics = Metrics::new();
+}
+
+/// # Metrics
+/// Prometheus collectors, the server gauges are filled in when scraped
+// MARK: Metrics
+pub struct Metrics {
+  registry: Registry,
+  server_online: IntGaugeVec,
+  server_players: IntGaugeVec,
+  server_max_players: IntGaugeVec,
+  server_tps: IntGaugeVec,
+  server_max_tps: IntGaugeVec,
+  server_last_heard: IntGaugeVec,
+  discord_buffer_size: IntGaugeVec,
+  discord_send_failures: IntCounterVec,
+  websocket_connections: IntGaugeVec,
+  http_requests: IntCounterVec,
+  http_request_duration: HistogramVec,
+  db_query_duration: HistogramVec,
+}
+
+impl Metrics {
+  fn new() -> Self {
+    let server_labels = &["server_id", "server"];
+    let metrics = Metrics {
+      registry: Registry::new_custom(Some("district".to_string()), None)
+        .expect("metrics prefix is valid"),
+      server_online: gauge(
+        "server_online",
+        "1 when the server reported it's open",
+        server_labels,
+      ),
+      server_players: gauge("server_players", "Players on the server", server_labels),
+      server_max_players: gauge(
+        "server_max_players",
+        "Player slots of the server",
+        server_labels,
+      ),
+      server_tps: gauge(
+        "server_tps",
+        "Ticks per second of the server",
+        server_labels,
+      ),
+      server_max_tps: gauge(
+        "server_max_tps",
+        "Target ticks per second of the server",
+        server_labels,
+      ),
+      server_last_heard: gauge(
+        "server_last_heard_timestamp_seconds",
+        "When the server last sent its status",
+        server_labels,
+      ),
+      discord_buffer_size: gauge(
+        "discord_buffer_size",
+        "Messages waiting to be sent to the server's Discord channel",
+        server_labels,
+      ),
+      discord_send_failures: IntCounterVec::new(
+        Opts::new(
+          "discord_send_failures_total",
+          "Messages that couldn't be sent to Discord",
+        ),
+        &["server_id"],
+      )
+      .expect("metric is valid"),
+      websocket_connections: gauge(
+        "websocket_connections",
+        "Open websocket connections",
+        &["server_id"],
+      ),
+      http_requests: IntCounterVec::new(
+        Opts::new("http_requests_total", "Handled HTTP requests"),
+        &["method", "route", "status"],
+      )
+      .expect("metric is valid"),
+      http_request_duration: HistogramVec::new(
+        HistogramOpts::new(
+          "http_request_duration_seconds",
+          "Time spent handling HTTP requests",
+        ),
+        &["method", "route"],
+      )
+      .expect("metric is valid"),
+      db_query_duration: HistogramVec::new(
+        HistogramOpts::new(
+          "db_query_duration_seconds",
+          "Time spent running database statements",
+        )
+        .buckets(vec![
+          0.0001, 0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+        ]),
+        &["database", "statement"],
+      )
+      .expect("metric is valid"),
+    };
+
+    let collectors: Vec<Box<dyn prometheus::core::Collector>> = vec![
+      Box::new(metrics.server_online.clone()),
+      Box::new(metrics.server_players.clone()),
+      Box::new(metrics.server_max_players.clone()),
+      Box::new(metrics.server_tps.clone()),
+      Box::new(metrics.server_max_tps.clone()),
+      Box::new(metrics.server_last_heard.clone()),
+      Box::new(metrics.discord_buffer_size.clone()),
+      Box::new(metrics.discord_send_failures.clone()),
+      Box::new(metrics.websocket_connections.clone()),
+      Box::new(metrics.http_requests.clone()),
+      Box::new(metrics.http_request_duration.clone()),
+      Box::new(metrics.db_query_duration.clone()),
+    ];
+    for collector in collectors {
+      metrics
+        .registry
+        .register(collector)
+        .expect("metric names are unique");
+    }
+    metrics
+  }
+
+  /// Sets the status and Discord buffer gauges of a server
+  pub fn observe_server(&self, server: &DistrictServer) {
+    let id = server.id.to_string();
+    let labels = [id.as_str(), server.name.as_str()];
+    let status = &server.status;
+    self
+      .server_online
+      .with_label_values(&labels)
+      .set(status.open as i64);
+    self
+      .server_players
+      .with_label_values(&labels)
+      .set(status.player_count as i64);
+    self
+      .server_max_players
+      .with_label_values(&labels)
+      .set(status.max_player_count as i64);
+    self
+      .server_tps
+      .with_label_values(&labels)
+      .set(status.tps as i64);
+    self
+      .server_max_tps
+      .with_label_values(&labels)
+      .set(status.max_tps as i64);
+    self
+      .server_last_heard
+      .with_label_values(&labels)
+      .set(status.last_heard.unwrap_or_default());
+    self
+      .discord_buffer_size
+      .with_label_values(&labels)
+      .set(server.buffered_messages() as i64);
+  }
+
+  pub fn discord_send_failed(&self, server_id: u64) {
+    self
+      .discord_send_failures
+      .with_label_values(&[&server_id.to_string()])
+      .inc();
+  }
+
+  pub fn websocket_opened(&self, server_id: u64) {
+    self
+      .websocket_connections
+      .with_label_values(&[&server_id.to_string()])
+      .inc();
+  }
+
+  pub fn websocket_closed(&self, server_id: u64) {
+    self
+      .websocket_connections
+      .with_label_values(&[&server_id.to_string()])
+      .dec();
+  }
+
+  /// Records a handled request, `route` is the route template (`/api/v1/players/<player_id>`)
+  pub fn observe_request(&self, method: &str, route: &str, status: u16, duration: Duration) {
+    self
+      .http_requests
+      .with_label_values(&[method, route, &status.to_string()])
+      .inc();
+    self
+      .http_request_duration
+      .with_label_values(&[method, route])
+      .observe(duration.as_secs_f64());
+  }
+
+  /// Records a statement run on `database`, labeled by its first keyword (`SELECT`, `INSERT`, ...)
+  pub fn observe_query(&self, database: &str, sql: &str, duration: Duration) {
+    let statement = sql
+      .split_whitespace()
+      .next()
+      .unwrap_or_default()
+      .to_uppercase();
+    self
+      .db_query_duration
+      .with_label_values(&[database, &statement])
+      .observe(duration.as_secs_f64());
+  }
+
+  /// Metrics in the Prometheus text format
+  pub fn render(&self) -> String {
+    let mut buffer = vec![];
+    if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+      return format!("# Couldn't encode metrics: {}\n", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+  }
+}
+
+fn gauge(name: &str, help: &str, labels: &[&str]) -> IntGaugeVec {
+  IntGaugeVec::new(Opts::new(name, help), labels).expect("metric is valid")
+}
+
+/// Profiler for [`rusqlite::Connection::profile`], which only takes plain functions
+pub fn query_profiler(database: &str) -> Option<fn(&str, Duration)> {
+  match database {
+    "players" => Some(|sql, duration| METRICS.observe_query("players", sql, duration)),
+    "punishments" => Some(|sql, duration| METRICS.observe_query("punishments", sql, duration)),
+    "leaderboards" => Some(|sql, duration| METRICS.observe_query("leaderboards", sql, duration)),
+    "auth" => Some(|sql, duration| METRICS.observe_query("auth", sql, duration)),
+    "status" => Some(|sql, duration| METRICS.observe_query("status", sql, duration)),
+    "webhooks" => Some(|sql, duration| METRICS.observe_query("webhooks", sql, duration)),
+    "audit" => Some(|sql, duration| METRICS.observe_query("audit", sql, duration)),
+    _ => None,
+  }
+}
@@ -1,2 +1,9 @@
+pub(crate) mod audit;
+pub(crate) mod events;
 pub(crate) mod lang;
 pub mod logs;
+pub(crate) mod metrics;
+pub(crate) mod rate_limit;
+pub(crate) mod uptime;
+pub(crate) mod verification;
+pub(crate) mod webhooks;
@@ -0,0 +1,188 @@
+use std::{collections::HashMap, time::Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::application::config::rate_limit_config::{ConfigRateLimit, ConfigRateLimits};
+
+/// When there are more buckets than this, the idle ones are dropped
+const MAX_BUCKETS: usize = 10_000;
+/// Seconds a counter that limited requests is kept after the last one, once its bucket is dropped
+const LIMITED_COUNTER_RETENTION_SECS: i64 = 3600;
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+  tokens: f64,
+  updated: Instant,
+}
+
+impl TokenBucket {
+  fn new(limit: &ConfigRateLimit, now: Instant) -> Self {
+    TokenBucket {
+      tokens: limit.capacity as f64,
+      updated: now,
+    }
+  }
+
+  fn refill(&mut self, limit: &ConfigRateLimit, now: Instant) {
+    let elapsed = now.duration_since(self.updated).as_secs_f64();
+    self.tokens = (self.tokens + elapsed * limit.refill_per_second).min(limit.capacity as f64);
+    self.updated = now;
+  }
+
+  /// Takes a token, or returns in how many seconds there will be one
+  fn take(&mut self, limit: &ConfigRateLimit, now: Instant) -> Result<(), u64> {
+    self.refill(limit, now);
+    if self.tokens >= 1.0 {
+      self.tokens -= 1.0;
+      return Ok(());
+    }
+    if limit.refill_per_second <= 0.0 {
+      return Err(u64::MAX);
+    }
+    Err(
+      ((1.0 - self.tokens) / limit.refill_per_second)
+        .ceil()
+        .max(1.0) as u64,
+    )
+  }
+}
+
+/// # Rate limit counter
+/// Requests allowed and rejected by one bucket
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RateLimitCounter {
+  /// `ip`, `key` or `group`
+  pub kind: String,
+  /// Client IP or API key name, with group name in front for group limits (`logs:plugin-1`)
+  pub subject: String,
+  pub allowed: u64,
+  pub limited: u64,
+  pub last_limited_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitExceeded {
+  /// Name of the bucket that ran out (`ip`, `key` or `group:<name>`)
+  pub limit: String,
+  pub retry_after: u64,
+}
+
+/// # Rate limiter
+/// Token buckets for client IPs, API keys and route groups, kept in memory
+// MARK: Rate limiter
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+  buckets: HashMap<String, TokenBucket>,
+  counters: HashMap<String, RateLimitCounter>,
+}
+
+impl RateLimiter {
+  /// Counts the request against the `per_ip` limit
+  pub fn check_ip(&mut self, limits: &ConfigRateLimits, ip: &str) -> Result<(), RateLimitExceeded> {
+    match &limits.per_ip {
+      Some(limit) => self.check("ip", ip, limit),
+      None => Ok(()),
+    }
+  }
+
+  /// Counts the request against the `per_key` limit and the limit of the route group
+  pub fn check_key(
+    &mut self,
+    limits: &ConfigRateLimits,
+    key_name: &str,
+    group: &str,
+  ) -> Result<(), RateLimitExceeded> {
+    if let Some(limit) = &limits.per_key {
+      self.check("key", key_name, limit)?;
+    }
+    if let Some(limit) = limits.groups.as_ref().and_then(|groups| groups.get(group)) {
+      self.check("group", &format!("{}:{}", group, key_name), limit)?;
+    }
+    Ok(())
+  }
+
+  fn check(
+    &mut self,
+    kind: &str,
+    subject: &str,
+    limit: &ConfigRateLimit,
+  ) -> Result<(), RateLimitExceeded> {
+    let now = Instant::now();
+    if self.buckets.len() > MAX_BUCKETS || self.counters.len() > MAX_BUCKETS {
+      self.prune(now);
+    }
+
+    let id = format!("{}/{}", kind, subject);
+    let result = self
+      .buckets
+      .entry(id.clone())
+      .or_insert_with(|| TokenBucket::new(limit, now))
+      .take(limit, now);
+
+    let counter = self.counters.entry(id).or_insert_with(|| RateLimitCounter {
+      kind: kind.to_string(),
+      subject: subject.to_string(),
+      allowed: 0,
+      limited: 0,
+      last_limited_at: None,
+    });
+    match result {
+      Ok(()) => {
+        counter.allowed += 1;
+        Ok(())
+      }
+      Err(retry_after) => {
+        counter.limited += 1;
+        counter.last_limited_at = Some(Utc::now());
+        Err(RateLimitExceeded {
+          limit: match kind {
+            "group" => format!("group:{}", subject.split(':').next().unwrap_or_default()),
+            _ => kind.to_string(),
+          },
+          retry_after,
+        })
+      }
+    }
+  }
+
+  /// Drops buckets unused for a minute, with their counters unless they limited a request
+  /// within the retention window
+  fn prune(&mut self, now: Instant) {
+    self
+      .buckets
+      .retain(|_, bucket| now.duration_since(bucket.updated).as_secs() < 60);
+    let retained_since = Utc::now() - chrono::Duration::seconds(LIMITED_COUNTER_RETENTION_SECS);
+    let buckets = &self.buckets;
+    self.counters.retain(|id, counter| {
+      buckets.contains_key(id)
+        || counter
+          .last_limited_at
+          .is_some_and(|limited_at| limited_at >= retained_since)
+    });
+  }
+
+  pub fn counters(&self) -> Vec<RateLimitCounter> {
+    let mut counters: Vec<RateLimitCounter> = self.counters.values().cloned().collect();
+    counters.sort_by(|a, b| (&a.kind, &a.subject).cmp(&(&b.kind, &b.subject)));
+    counters
+  }
+}
+
+/// Route group of an API path, used for group limits
+///
+/// It's the first segment after `/api/v1` (`players`, `verifications`, `links`, ...),
+/// for server routes the segment after the server id (`joins`, `status`, `logs`, `ws`)
+pub fn route_group(path: &str) -> String {
+  let segments: Vec<&str> = path
+    .trim_start_matches('/')
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .collect();
+  match segments.as_slice() {
+    ["api", _, "servers", _, group, ..] => group.to_string(),
+    ["api", _, group, ..] => group.to_string(),
+    _ => "other".to_string(),
+  }
+}
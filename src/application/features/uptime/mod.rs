@@ -0,0 +1,89 @@
+use crate::application::{
+  db::status::{structs::DatabaseStatusSample, StatusDatabase},
+  features::events::{EventBus, EventKind},
+  server::server::DistrictServer,
+};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_w, log_x};
+
+/// Seconds between samples of a server whose open state didn't change
+const SAMPLE_INTERVAL_SECS: i64 = 60;
+
+pub const INCIDENT_CLOSED: &str = "closed";
+pub const INCIDENT_TIMED_OUT: &str = "timed_out";
+
+/// Persists the status the server just sent, opening or closing its downtime incident
+/// when it went from open to closed or back
+pub fn record_status(db: &StatusDatabase, server: &mut DistrictServer, was_open: bool, now: i64) {
+  let status = &server.status;
+  let transition = status.open != was_open;
+  let sample_due = server
+    .last_status_sample
+    .is_none_or(|last_sample| now - last_sample >= SAMPLE_INTERVAL_SECS);
+  if !transition && !sample_due {
+    return;
+  }
+
+  let result = db
+    .add_sample(&DatabaseStatusSample {
+      server_id: server.id,
+      timestamp: now,
+      open: status.open,
+      tps: status.tps,
+      max_tps: status.max_tps,
+      player_count: status.player_count,
+      max_player_count: status.max_player_count,
+    })
+    .and_then(|_| match (transition, status.open) {
+      (true, true) => db.close_incidents(server.id, now),
+      (true, false) => db.open_incident(server.id, now, INCIDENT_CLOSED),
+      (false, _) => Ok(()),
+    });
+  match result {
+    Ok(_) => server.last_status_sample = Some(now),
+    Err(e) => log_w!(format!(
+      "Couldn't record status of server {}: {}",
+      server.id, e
+    )),
+  }
+}
+
+/// Records that the server stopped sending its status, the incident starts when it was last heard
+pub fn record_timeout(db: &StatusDatabase, server: &DistrictServer, last_heard: i64) {
+  let result = db
+    .add_sample(&DatabaseStatusSample {
+      server_id: server.id,
+      timestamp: last_heard,
+      open: false,
+      tps: 0,
+      max_tps: server.status.max_tps,
+      player_count: 0,
+      max_player_count: server.status.max_player_count,
+    })
+    .and_then(|_| db.open_incident(server.id, last_heard, INCIDENT_TIMED_OUT));
+  if let Err(e) = result {
+    log_w!(format!(
+      "Couldn't record timeout of server {}: {}",
+      server.id, e
+    ));
+  }
+}
+
+/// Publishes `server_up` when the server opened, or `server_down` with why it went down
+pub fn publish_transition(events: &EventBus, server: &DistrictServer, reason: Option<&str>) {
+  let kind = if server.status.open {
+    EventKind::ServerUp
+  } else {
+    EventKind::ServerDown
+  };
+  events.publish(
+    kind,
+    Some(server.id),
+    serde_json::json!({
+      "name": server.name,
+      "reason": reason,
+      "player_count": server.status.player_count,
+      "max_player_count": server.status.max_player_count,
+    }),
+  );
+}
@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use serenity::all::User;
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::Application,
+  config::bots::verification_sync_config::ConfigVerificationSync,
+  db::{
+    audit::structs::AuditActor,
+    player::structs::{DatabasePlayer, DatabasePlayerVerification, PlayerVerification},
+    punishments::{
+      structs::{DatabasePunishment, PunishmentType},
+      PunishmentDatabase,
+    },
+  },
+  features::audit::{self, actions},
+};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_i, log_w, log_x};
+
+use super::on_verification_changed;
+
+/// Audit log actor of changes made by Discord guild events
+const AUDIT_ACTOR: &str = "discord_sync";
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum GuildMembershipEvent {
+  Ban,
+  Unban,
+  Leave,
+}
+
+// MARK: Handle guild event
+/// Maps Discord guild ban, unban and leave events onto verification of every player linked to the user
+pub(crate) async fn handle_guild_event(
+  app_arc: &Arc<Mutex<Application>>,
+  event: GuildMembershipEvent,
+  user: &User,
+  cfg: &ConfigVerificationSync,
+) {
+  let enabled = match event {
+    GuildMembershipEvent::Ban => cfg.on_ban,
+    GuildMembershipEvent::Unban => cfg.on_unban,
+    GuildMembershipEvent::Leave => cfg.on_leave,
+  };
+  if !enabled {
+    return;
+  }
+
+  let mut changed_players: Vec<u64> = vec![];
+  {
+    let app = app_arc.lock().await;
+    let Some(db_lock) = app.databases.as_ref() else {
+      return;
+    };
+    let mut db_handler = db_lock.lock().await;
+    let players = match db_handler
+      .player_database
+      .get_players_by_discord(&user.id.to_string())
+    {
+      Ok(players) => players,
+      Err(e) => {
+        log_w!(format!(
+          "Couldn't load players of discord user {}: {}",
+          user.id, e
+        ));
+        return;
+      }
+    };
+
+    for player in players {
+      let Some(mut new_status) = next_verification_status(event, &player) else {
+        continue;
+      };
+      if new_status == PlayerVerification::Success {
+        // Restore primary account as primary
+        new_status = db_handler
+          .player_database
+          .linked_verification_status(player.player_id)
+          .unwrap_or(PlayerVerification::Success);
+      }
+
+      let punishment = (matches!(event, GuildMembershipEvent::Ban) && cfg.create_punishment)
+        .then(|| discord_ban_punishment(&player, cfg));
+      let handler = &mut *db_handler;
+      let result = handler.player_database.in_savepoint("discord_sync", |db| {
+        db.set_player_verification(player.player_id, new_status, None, None)?;
+        if let Some(data) = punishment.as_ref() {
+          db.add_ban_id(player.player_id, &data.punishment_id)?;
+          // Separate database, inserted last so its failure rolls back the player
+          handler.punishment_database.create_new_punishment(data)?;
+        }
+        Ok(())
+      });
+      if let Err(e) = result {
+        log_w!(format!(
+          "Couldn't update verification of player {}: {}",
+          player.player_id, e
+        ));
+        continue;
+      }
+      audit::record(
+        &db_handler,
+        &AuditActor::system(AUDIT_ACTOR),
+        actions::VERIFICATION_DISCORD_SYNC,
+        audit::target("player", player.player_id),
+        DatabasePlayerVerification::from(player.clone()),
+        db_handler
+          .player_database
+          .get_player_verification(player.player_id)
+          .ok(),
+      );
+      if let Some(data) = punishment.as_ref() {
+        audit::record(
+          &db_handler,
+          &AuditActor::system(AUDIT_ACTOR),
+          actions::PUNISHMENT_CREATE,
+          audit::target("player", player.player_id),
+          (),
+          data,
+        );
+      }
+      changed_players.push(player.player_id);
+    }
+  }
+
+  if !changed_players.is_empty() {
+    log_i!(format!(
+      "Discord {:?} of {} changed verification of {} player(s)",
+      event,
+      user.name,
+      changed_players.len()
+    ));
+  }
+  for player_id in changed_players {
+    on_verification_changed(app_arc, player_id);
+  }
+}
+
+fn next_verification_status(
+  event: GuildMembershipEvent,
+  player: &DatabasePlayer,
+) -> Option<PlayerVerification> {
+  match event {
+    GuildMembershipEvent::Ban => (player.verified_status != Some(PlayerVerification::Banned))
+      .then_some(PlayerVerification::Banned),
+    GuildMembershipEvent::Unban => (player.verified_status == Some(PlayerVerification::Banned))
+      .then_some(PlayerVerification::Success),
+    GuildMembershipEvent::Leave => player
+      .is_verified()
+      .then_some(PlayerVerification::Suspended),
+  }
+}
+
+// MARK: Discord ban punishment
+fn discord_ban_punishment(
+  player: &DatabasePlayer,
+  cfg: &ConfigVerificationSync,
+) -> DatabasePunishment {
+  DatabasePunishment {
+    punishment_id: PunishmentDatabase::generate_punishment_id(),
+    player_id: player.player_id,
+    username: player.usernames.last().cloned().unwrap_or_default(),
+    steam_id: player.steam_id.clone(),
+    ip: player.ips.last().cloned().unwrap_or_default(),
+    reason: String::from("Banned on Discord"),
+    punishment_duration: cfg.punishment_duration.unwrap_or(0),
+    punishment_created_at: Utc::now(),
+    issuer_steam_id: String::new(),
+    issuer_name: String::from("Discord"),
+    issuer_ip: String::new(),
+    punishment_type: PunishmentType::Ban,
+  }
+}
@@ -0,0 +1,55 @@
+pub(crate) mod discord_sync;
+pub(crate) mod review;
+pub(crate) mod roles;
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::application::{application::Application, features::events::EventKind};
+
+/// Notifies dependent subsystems (Discord roles, ...) that verification of a player has changed.
+///
+/// Work is spawned in the background, so this can be called while still holding the application lock.
+pub(crate) fn on_verification_changed(app: &Arc<Mutex<Application>>, player_id: u64) {
+  let app_arc = Arc::clone(app);
+  tokio::spawn(async move {
+    publish_verification_event(&app_arc, player_id).await;
+    roles::sync_player_roles(&app_arc, player_id).await;
+  });
+}
+
+async fn publish_verification_event(app: &Arc<Mutex<Application>>, player_id: u64) {
+  let app = app.lock().await;
+  let verification = match app.databases.as_ref() {
+    Some(db_handler_lock) => db_handler_lock
+      .lock()
+      .await
+      .player_database
+      .get_player_verification(player_id)
+      .ok(),
+    None => None,
+  };
+  let data = match verification {
+    Some(verification) => serde_json::json!({
+      "player_id": verification.player_id,
+      "steam_id": verification.steam_id,
+      "verified_status": verification.verified_status,
+      "discord_id": verification.discord_id,
+      "is_considered_verified": verification.is_considered_verified,
+    }),
+    None => serde_json::json!({ "player_id": player_id }),
+  };
+  app
+    .events
+    .publish(EventKind::VerificationChanged, None, data);
+}
+
+/// Notifies dependent subsystems that verification of a Discord user has changed,
+/// used when the player is no longer linked to the Discord user
+pub(crate) fn on_discord_user_changed(app: &Arc<Mutex<Application>>, discord_id: String) {
+  let app_arc = Arc::clone(app);
+  tokio::spawn(async move {
+    roles::sync_discord_user_roles(&app_arc, &discord_id).await;
+  });
+}
@@ -0,0 +1,326 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use serenity::all::{
+  ButtonStyle, ChannelId, Colour, ComponentInteraction, Context, CreateActionRow, CreateButton,
+  CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, RoleId,
+  UserId,
+};
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::{self, Application},
+  config::verification_config::ConfigVerificationReview,
+  db::{
+    audit::structs::AuditActor,
+    database_handler::DatabaseHandler,
+    player::structs::{
+      DatabasePlayer, DatabasePlayerVerification, DatabaseVerificationReview, PlayerVerification,
+      ReviewStatus,
+    },
+    punishments::structs::PunishmentType,
+  },
+  features::audit::{self, actions},
+};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_i, log_w, log_x};
+
+use super::on_verification_changed;
+
+pub(crate) const REVIEW_COMPONENT_PREFIX: &str = "verification_review";
+
+// MARK: Suspicious checks
+/// Collects reasons why verification of the player should be reviewed by staff,
+/// returns empty [`Vec`] when verification looks fine
+pub(crate) fn find_suspicious_reasons(
+  db_handler: &DatabaseHandler,
+  player: &DatabasePlayer,
+  discord_id: &str,
+  cfg: &ConfigVerificationReview,
+) -> Vec<String> {
+  let mut reasons: Vec<String> = vec![];
+
+  if let (Some(min_days), Ok(user_id)) = (cfg.min_account_age_days, discord_id.parse::<u64>()) {
+    let created_at = UserId::new(user_id).created_at().unix_timestamp();
+    let age_days = (Utc::now().timestamp() - created_at) / 86400;
+    if age_days < min_days as i64 {
+      reasons.push(format!(
+        "Discord account is {} days old (minimum is {})",
+        age_days, min_days
+      ));
+    }
+  }
+
+  if cfg.check_banned_ips {
+    let banned_players: Vec<DatabasePlayer> = db_handler
+      .player_database
+      .get_all_players()
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|other| {
+        other.player_id != player.player_id
+          && other.verified_status == Some(PlayerVerification::Banned)
+      })
+      .collect();
+
+    // One reason per banned player, listing every IP shared with it
+    let mut banned_player_ips: Vec<(u64, Vec<&str>)> = vec![];
+    let mut punished_ips: Vec<&str> = vec![];
+    for ip in player.ips.iter() {
+      if let Some(other) = banned_players.iter().find(|other| other.ips.contains(ip)) {
+        match banned_player_ips
+          .iter_mut()
+          .find(|(player_id, _)| *player_id == other.player_id)
+        {
+          Some((_, ips)) => ips.push(ip),
+          None => banned_player_ips.push((other.player_id, vec![ip])),
+        }
+        continue;
+      }
+      let ip_banned = db_handler
+        .punishment_database
+        .get_punishments_by_ip(ip.clone())
+        .unwrap_or_default()
+        .iter()
+        .any(|punishment| {
+          punishment.player_id != player.player_id
+            && punishment.punishment_type == PunishmentType::Ban
+        });
+      if ip_banned {
+        punished_ips.push(ip);
+      }
+    }
+
+    for (player_id, ips) in banned_player_ips {
+      reasons.push(format!(
+        "IP {} is shared with verification banned player {}",
+        ips.join(", "),
+        player_id
+      ));
+    }
+    if !punished_ips.is_empty() {
+      reasons.push(format!(
+        "IP {} is shared with a banned player",
+        punished_ips.join(", ")
+      ));
+    }
+  }
+
+  reasons
+}
+
+// MARK: Post review
+/// Posts the review to the configured staff channel in the background
+pub(crate) fn queue_review(app: &Arc<Mutex<Application>>, review_id: u64) {
+  let app_arc = Arc::clone(app);
+  tokio::spawn(async move {
+    if let Err(e) = post_review(&app_arc, review_id).await {
+      log_w!(format!(
+        "Couldn't post verification review {}: {}",
+        review_id, e
+      ));
+    }
+  });
+}
+
+async fn post_review(app_arc: &Arc<Mutex<Application>>, review_id: u64) -> Result<(), String> {
+  let (review, player, channel_id, ctx_manager) = {
+    let app = app_arc.lock().await;
+    let cfg = review_config(&app).ok_or("Verification review is not configured")?;
+    let ctx_manager = app
+      .main_bot
+      .as_ref()
+      .map(|bot| Arc::clone(&bot.ctx_manager))
+      .ok_or("Main bot is not running")?;
+    let db_lock = app.databases.as_ref().ok_or("No databases loaded")?;
+    let db_handler = db_lock.lock().await;
+    let review = db_handler
+      .player_database
+      .get_verification_review(review_id)
+      .map_err(|e| e.to_string())?;
+    let player = db_handler
+      .player_database
+      .get_player_by_id(review.player_id)
+      .map_err(|e| e.to_string())?;
+    (review, player, ChannelId::new(cfg.channel_id), ctx_manager)
+  };
+
+  let ctx_lock = ctx_manager.get_ctx().await.ok_or("Main bot is not ready")?;
+  let http = Arc::clone(&ctx_lock.lock().await.http);
+  channel_id
+    .send_message(
+      &http,
+      CreateMessage::new()
+        .embed(review_embed(&review, &player))
+        .components(vec![review_buttons(review.review_id)]),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+fn review_config(app: &Application) -> Option<ConfigVerificationReview> {
+  app
+    .config
+    .as_ref()
+    .and_then(|cfg| cfg.verification.as_ref())
+    .and_then(|cfg| cfg.review.clone())
+}
+
+fn review_buttons(review_id: u64) -> CreateActionRow {
+  CreateActionRow::Buttons(vec![
+    CreateButton::new(format!("{}:approve:{}", REVIEW_COMPONENT_PREFIX, review_id))
+      .label("Approve")
+      .style(ButtonStyle::Success),
+    CreateButton::new(format!("{}:reject:{}", REVIEW_COMPONENT_PREFIX, review_id))
+      .label("Reject")
+      .style(ButtonStyle::Danger),
+  ])
+}
+
+fn review_embed(review: &DatabaseVerificationReview, player: &DatabasePlayer) -> CreateEmbed {
+  let (colour, decision) = match review.status {
+    ReviewStatus::Pending => (Colour::from_rgb(245, 197, 66), String::from("Pending")),
+    ReviewStatus::Approved => (
+      Colour::from_rgb(87, 242, 135),
+      format!(
+        "Approved by <@{}>",
+        review.reviewer_id.clone().unwrap_or_default()
+      ),
+    ),
+    ReviewStatus::Rejected => (
+      Colour::from_rgb(237, 66, 69),
+      format!(
+        "Rejected by <@{}>",
+        review.reviewer_id.clone().unwrap_or_default()
+      ),
+    ),
+  };
+
+  CreateEmbed::new()
+    .title(format!("Verification review #{}", review.review_id))
+    .thumbnail(application::APPLICATION_ICON_URL)
+    .color(colour)
+    .description(format!(
+      "- **Player**: {} ({})\n- **Last username**: {}\n- **Discord**: <@{}>\n- **Created**: <t:{}:R>",
+      player.player_id,
+      player.steam_id,
+      player.usernames.last().cloned().unwrap_or_default(),
+      review.discord_id,
+      review.created_at.timestamp(),
+    ))
+    .field(
+      "Reasons:",
+      review
+        .reasons
+        .iter()
+        .map(|reason| format!("- {}", reason))
+        .collect::<Vec<_>>()
+        .join("\n"),
+      false,
+    )
+    .field("Decision:", decision, false)
+}
+
+// MARK: Handle decision
+/// Handles Approve/Reject buttons of the review message
+pub(crate) async fn handle_review_interaction(
+  app_arc: &Arc<Mutex<Application>>,
+  ctx: &Context,
+  component: &ComponentInteraction,
+) -> Result<(), String> {
+  let mut parts = component.data.custom_id.split(':').skip(1);
+  let decision = match parts.next() {
+    Some("approve") => ReviewStatus::Approved,
+    Some("reject") => ReviewStatus::Rejected,
+    _ => return Err(String::from("Unknown review action")),
+  };
+  let review_id = parts
+    .next()
+    .and_then(|val| val.parse::<u64>().ok())
+    .ok_or("Invalid review ID")?;
+  let reviewer_id = component.user.id.to_string();
+
+  let (review, player) = {
+    let app = app_arc.lock().await;
+    let cfg = review_config(&app).ok_or("Verification review is not configured")?;
+    if let Some(role_id) = cfg.reviewer_role_id.map(RoleId::new) {
+      let is_reviewer = component
+        .member
+        .as_ref()
+        .is_some_and(|member| member.roles.contains(&role_id));
+      if !is_reviewer {
+        return Err(String::from("You are not allowed to review verifications"));
+      }
+    }
+
+    let db_lock = app.databases.as_ref().ok_or("No databases loaded")?;
+    let mut db_handler = db_lock.lock().await;
+    let before = db_handler
+      .player_database
+      .get_verification_review(review_id)
+      .and_then(|review| {
+        db_handler
+          .player_database
+          .get_player_verification(review.player_id)
+      })
+      .ok();
+    db_handler
+      .player_database
+      .decide_verification_review(review_id, decision, &reviewer_id)
+      .map_err(|_| String::from("This review was already decided"))?;
+    let review = db_handler
+      .player_database
+      .get_verification_review(review_id)
+      .map_err(|e| e.to_string())?;
+
+    match decision {
+      ReviewStatus::Approved => db_handler
+        .player_database
+        .link_account(review.player_id, &review.discord_id)
+        .map(|_| ()),
+      // Rejected verification is dropped, the player can verify again (banning is a separate action)
+      _ => db_handler.player_database.set_player_verification(
+        review.player_id,
+        PlayerVerification::None,
+        Some("NULL".to_string()),
+        Some("NULL".to_string()),
+      ),
+    }
+    .map_err(|e| e.to_string())?;
+    let player = db_handler
+      .player_database
+      .get_player_by_id(review.player_id)
+      .map_err(|e| e.to_string())?;
+    audit::record(
+      &db_handler,
+      &AuditActor::discord(&reviewer_id),
+      actions::VERIFICATION_REVIEW,
+      audit::target("player", review.player_id),
+      &before,
+      serde_json::json!({
+        "review": &review,
+        "verification": DatabasePlayerVerification::from(player.clone()),
+      }),
+    );
+    (review, player)
+  };
+
+  log_i!(format!(
+    "Verification review {} of player {} decided by {}",
+    review.review_id, review.player_id, reviewer_id
+  ));
+  on_verification_changed(app_arc, review.player_id);
+
+  component
+    .create_response(
+      &ctx.http,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+          .embed(review_embed(&review, &player))
+          .components(vec![]),
+      ),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
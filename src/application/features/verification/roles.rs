@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use serenity::all::{GuildId, Http, RoleId, UserId};
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::Application,
+  bot::bot::{ContextManager, DistrictBot},
+  config::bots::verification_roles_config::ConfigVerificationRoles,
+};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_d, log_w, log_x};
+
+const ROLE_AUDIT_REASON: &str = "DISTRICT verification status changed";
+
+struct RoleTarget {
+  ctx_manager: Arc<ContextManager>,
+  guild_id: GuildId,
+  roles: ConfigVerificationRoles,
+}
+
+// MARK: Collect bots
+/// Collects every bot (main + server bots) that has verification roles configured, one per guild
+async fn collect_role_targets(app: &Application) -> Vec<RoleTarget> {
+  let mut targets: Vec<RoleTarget> = vec![];
+  let mut push_target = |bot: &DistrictBot| {
+    if let Some(roles) = bot.bot_config.get_verification_roles() {
+      if targets
+        .iter()
+        .any(|target| target.guild_id == bot.operational_guild && target.roles == roles)
+      {
+        return;
+      }
+      targets.push(RoleTarget {
+        ctx_manager: Arc::clone(&bot.ctx_manager),
+        guild_id: bot.operational_guild,
+        roles,
+      });
+    }
+  };
+
+  if let Some(bot) = app.main_bot.as_ref() {
+    push_target(bot);
+  }
+  for server_lock in app.servers.iter() {
+    let server = server_lock.lock().await;
+    if let Some(bot) = server.bot.as_ref() {
+      push_target(bot);
+    }
+  }
+  targets
+}
+
+// MARK: Sync player
+/// Grants or removes the configured verification roles for the Discord account linked to the player
+pub(crate) async fn sync_player_roles(app_arc: &Arc<Mutex<Application>>, player_id: u64) {
+  let discord_id = {
+    let app = app_arc.lock().await;
+    let Some(db_lock) = app.databases.as_ref() else {
+      return;
+    };
+    let db_handler = db_lock.lock().await;
+    match db_handler.player_database.get_player_by_id(player_id) {
+      Ok(player) => player.discord_id,
+      Err(e) => {
+        log_w!(format!(
+          "Couldn't load player {} for role sync: {}",
+          player_id, e
+        ));
+        return;
+      }
+    }
+  };
+
+  if let Some(discord_id) = discord_id {
+    sync_discord_user_roles(app_arc, &discord_id).await;
+  }
+}
+
+/// Grants or removes the configured verification roles for a Discord account.
+/// Account is considered verified, when at least one linked player is verified.
+pub(crate) async fn sync_discord_user_roles(app_arc: &Arc<Mutex<Application>>, discord_id: &str) {
+  let Ok(user_id) = discord_id.parse::<u64>().map(UserId::new) else {
+    return;
+  };
+
+  let (is_verified, targets) = {
+    let app = app_arc.lock().await;
+    let targets = collect_role_targets(&app).await;
+    if targets.is_empty() {
+      return;
+    }
+    let Some(db_lock) = app.databases.as_ref() else {
+      return;
+    };
+    let db_handler = db_lock.lock().await;
+    let is_verified = match db_handler
+      .player_database
+      .get_players_by_discord(discord_id)
+    {
+      Ok(players) => players.iter().any(|player| player.is_verified()),
+      Err(e) => {
+        log_w!(format!(
+          "Couldn't load players of discord user {} for role sync: {}",
+          discord_id, e
+        ));
+        return;
+      }
+    };
+    (is_verified, targets)
+  };
+
+  for target in targets.iter() {
+    if let Some(ctx_lock) = target.ctx_manager.get_ctx().await {
+      let http = Arc::clone(&ctx_lock.lock().await.http);
+      apply_member_roles(&http, target.guild_id, user_id, is_verified, &target.roles).await;
+    }
+  }
+}
+
+// MARK: Reconcile
+/// Re-applies verification roles for every player that has a Discord account linked
+pub(crate) async fn reconcile_roles(app_arc: &Arc<Mutex<Application>>) {
+  let (verified_users, targets) = {
+    let app = app_arc.lock().await;
+    let targets = collect_role_targets(&app).await;
+    if targets.is_empty() {
+      return;
+    }
+    let Some(db_lock) = app.databases.as_ref() else {
+      return;
+    };
+    let db_handler = db_lock.lock().await;
+    let players = match db_handler.player_database.get_all_players() {
+      Ok(players) => players,
+      Err(e) => {
+        log_w!(format!(
+          "Couldn't load players for role reconciliation: {}",
+          e
+        ));
+        return;
+      }
+    };
+
+    let mut verified_users: Vec<(UserId, bool)> = vec![];
+    for player in players.iter() {
+      let Some(user_id) = player
+        .discord_id
+        .as_ref()
+        .and_then(|id| id.parse::<u64>().ok())
+        .map(UserId::new)
+      else {
+        continue;
+      };
+      match verified_users.iter_mut().find(|(id, _)| *id == user_id) {
+        Some((_, verified)) => *verified |= player.is_verified(),
+        None => verified_users.push((user_id, player.is_verified())),
+      }
+    }
+    (verified_users, targets)
+  };
+
+  log_d!(format!(
+    "Reconciling verification roles of {} discord users",
+    verified_users.len()
+  ));
+  for target in targets.iter() {
+    let Some(ctx_lock) = target.ctx_manager.get_ctx().await else {
+      continue;
+    };
+    let http = Arc::clone(&ctx_lock.lock().await.http);
+    for (user_id, is_verified) in verified_users.iter() {
+      apply_member_roles(
+        &http,
+        target.guild_id,
+        *user_id,
+        *is_verified,
+        &target.roles,
+      )
+      .await;
+    }
+  }
+}
+
+// MARK: Apply roles
+async fn apply_member_roles(
+  http: &Http,
+  guild_id: GuildId,
+  user_id: UserId,
+  is_verified: bool,
+  roles: &ConfigVerificationRoles,
+) {
+  // Member may not be in this guild, nothing to do then
+  let Ok(member) = http.get_member(guild_id, user_id).await else {
+    return;
+  };
+
+  let (grant, revoke) = if is_verified {
+    (roles.verified_role_id, roles.unverified_role_id)
+  } else {
+    (roles.unverified_role_id, roles.verified_role_id)
+  };
+
+  if let Some(role_id) = grant.map(RoleId::new) {
+    if !member.roles.contains(&role_id) {
+      if let Err(e) = http
+        .add_member_role(guild_id, user_id, role_id, Some(ROLE_AUDIT_REASON))
+        .await
+      {
+        log_w!(format!(
+          "Couldn't add role {} to {} in guild {}: {}",
+          role_id, user_id, guild_id, e
+        ));
+      }
+    }
+  }
+  if let Some(role_id) = revoke.map(RoleId::new) {
+    if member.roles.contains(&role_id) {
+      if let Err(e) = http
+        .remove_member_role(guild_id, user_id, role_id, Some(ROLE_AUDIT_REASON))
+        .await
+      {
+        log_w!(format!(
+          "Couldn't remove role {} from {} in guild {}: {}",
+          role_id, user_id, guild_id, e
+        ));
+      }
+    }
+  }
+}
@@ -0,0 +1,295 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::{broadcast::error::RecvError, Mutex, Notify};
+
+use crate::application::{
+  application::Application,
+  config::webhook_config::{ConfigWebhook, WebhookEvent},
+  db::{
+    database_handler::DatabaseHandler,
+    webhooks::structs::{DatabaseWebhookDelivery, DeliveryAttempt, WebhookDeliveryStatus},
+  },
+  features::events::{DistrictEvent, EventKind},
+  routes::signing::{SIGNATURE_HEADER, TIMESTAMP_HEADER},
+};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_d, log_w, log_x};
+
+pub const EVENT_HEADER: &str = "X-District-Event";
+pub const DELIVERY_HEADER: &str = "X-District-Delivery";
+
+/// Seconds before the first retry, every following retry waits twice as long
+const RETRY_BASE_DELAY_SECS: u64 = 10;
+/// Longest wait between two attempts
+const RETRY_MAX_DELAY_SECS: u64 = 3600;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+/// Seconds between checks for due retries
+const POLL_INTERVAL_SECS: u64 = 5;
+/// Deliveries attempted per check
+const DUE_BATCH_SIZE: u32 = 50;
+/// Days finished deliveries are kept in the delivery log
+pub const DELIVERY_RETENTION_DAYS: i64 = 30;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Wakes the delivery worker when a delivery was logged
+static DELIVERY_QUEUED: Notify = Notify::const_new();
+
+/// Webhook event the district event is sent as, `None` for events webhooks can't subscribe to
+pub fn webhook_event(event: &DistrictEvent) -> Option<WebhookEvent> {
+  match event.kind {
+    EventKind::PlayerJoin => {
+      let first_join = event.data.get("times_joined").and_then(Value::as_u64) == Some(1);
+      first_join.then_some(WebhookEvent::PlayerFirstJoin)
+    }
+    EventKind::PunishmentCreated => Some(WebhookEvent::PunishmentCreated),
+    EventKind::PunishmentRevoked => Some(WebhookEvent::PunishmentRevoked),
+    EventKind::VerificationChanged => Some(WebhookEvent::VerificationChanged),
+    EventKind::ServerUp => Some(WebhookEvent::ServerUp),
+    EventKind::ServerDown => Some(WebhookEvent::ServerDown),
+    EventKind::ServerStatus | EventKind::Log => None,
+  }
+}
+
+/// Lowercase hex HMAC-SHA256 of `TIMESTAMP\nBODY` with the webhook secret
+pub fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+  let mut mac =
+    HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+  mac.update(format!("{}\n{}", timestamp, body).as_bytes());
+  hex::encode(mac.finalize().into_bytes())
+}
+
+/// Seconds to wait after the given number of failed attempts
+fn retry_delay_secs(attempts: u32) -> i64 {
+  let delay = RETRY_BASE_DELAY_SECS.saturating_mul(1 << attempts.saturating_sub(1).min(16));
+  delay.min(RETRY_MAX_DELAY_SECS) as i64
+}
+
+/// Forwards subscribed events from the event bus to the configured webhooks
+// MARK: Spawn dispatcher
+pub fn spawn_dispatcher(app: Arc<Mutex<Application>>) {
+  spawn_delivery_worker(Arc::clone(&app));
+  tokio::spawn(async move {
+    let (_, mut receiver) = app.lock().await.events.subscribe(None);
+    loop {
+      let event = match receiver.recv().await {
+        Ok(event) => event,
+        Err(RecvError::Lagged(skipped)) => {
+          log_w!(format!(
+            "Webhook dispatcher fell behind, {} events weren't delivered",
+            skipped
+          ));
+          continue;
+        }
+        Err(RecvError::Closed) => break,
+      };
+      let Some(webhook_event) = webhook_event(&event) else {
+        continue;
+      };
+
+      let app = app.lock().await;
+      let (Some(config), Some(databases)) = (app.config.as_ref(), app.databases.as_ref()) else {
+        continue;
+      };
+      for webhook in config
+        .webhooks
+        .iter()
+        .filter(|webhook| webhook.events.contains(&webhook_event))
+      {
+        if let Err(e) = enqueue(
+          databases,
+          webhook,
+          webhook_event,
+          event.server_id,
+          event.data.clone(),
+        )
+        .await
+        {
+          log_w!(format!(
+            "Couldn't log delivery of '{}' to webhook '{}': {}",
+            webhook_event.as_str(),
+            webhook.name,
+            e
+          ));
+        }
+      }
+    }
+  });
+}
+
+/// Logs the delivery and wakes the delivery worker, returns the delivery ID
+// MARK: Enqueue delivery
+pub async fn enqueue(
+  databases: &Arc<Mutex<DatabaseHandler>>,
+  webhook: &ConfigWebhook,
+  event: WebhookEvent,
+  server_id: Option<u64>,
+  data: Value,
+) -> rusqlite::Result<u64> {
+  let now = Utc::now();
+  let body = json!({
+    "event": event,
+    "server_id": server_id,
+    "timestamp": now,
+    "data": data,
+  })
+  .to_string();
+  let id = databases.lock().await.webhook_database.add_delivery(
+    &webhook.name,
+    event.as_str(),
+    &body,
+    now.timestamp(),
+  )?;
+  DELIVERY_QUEUED.notify_one();
+  Ok(id)
+}
+
+/// Sends due deliveries, the schedule lives in the database so deliveries pending
+/// when the server stopped are resumed after a restart
+// MARK: Delivery worker
+fn spawn_delivery_worker(app: Arc<Mutex<Application>>) {
+  tokio::spawn(async move {
+    let client = match reqwest::Client::builder()
+      .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+      .build()
+    {
+      Ok(client) => client,
+      Err(e) => {
+        log_w!(format!("Couldn't create webhook client: {}", e));
+        return;
+      }
+    };
+    loop {
+      deliver_due(&app, &client).await;
+      // Woken early by `enqueue`
+      let _ = tokio::time::timeout(
+        Duration::from_secs(POLL_INTERVAL_SECS),
+        DELIVERY_QUEUED.notified(),
+      )
+      .await;
+    }
+  });
+}
+
+/// Makes one attempt at every due delivery and records its outcome
+async fn deliver_due(app: &Arc<Mutex<Application>>, client: &reqwest::Client) {
+  let (webhooks, databases) = {
+    let app = app.lock().await;
+    let (Some(config), Some(databases)) = (app.config.as_ref(), app.databases.as_ref()) else {
+      return;
+    };
+    (config.webhooks.clone(), Arc::clone(databases))
+  };
+
+  let due = databases
+    .lock()
+    .await
+    .webhook_database
+    .get_due_deliveries(Utc::now().timestamp(), DUE_BATCH_SIZE);
+  let due = match due {
+    Ok(val) => val,
+    Err(e) => {
+      log_w!(format!("Couldn't load due webhook deliveries: {}", e));
+      return;
+    }
+  };
+
+  for delivery in due {
+    let attempt = match webhooks
+      .iter()
+      .find(|webhook| webhook.name == delivery.webhook)
+    {
+      Some(webhook) => attempt_delivery(client, webhook, &delivery).await,
+      None => DeliveryAttempt {
+        status: WebhookDeliveryStatus::Failed,
+        attempts: delivery.attempts,
+        response_status: None,
+        error: Some("Webhook is no longer configured".to_string()),
+        attempted_at: Utc::now().timestamp(),
+        next_attempt_at: None,
+      },
+    };
+
+    if let Err(e) = databases
+      .lock()
+      .await
+      .webhook_database
+      .record_attempt(delivery.id, &attempt)
+    {
+      log_w!(format!(
+        "Couldn't record attempt of delivery {}: {}",
+        delivery.id, e
+      ));
+    }
+    match attempt.status {
+      WebhookDeliveryStatus::Delivered => {
+        log_d!(format!(
+          "Delivered {} to webhook '{}'",
+          delivery.id, delivery.webhook
+        ));
+      }
+      WebhookDeliveryStatus::Failed => {
+        log_w!(format!(
+          "Gave up delivery {} to webhook '{}' after {} attempts: {}",
+          delivery.id,
+          delivery.webhook,
+          attempt.attempts,
+          attempt.error.unwrap_or_default()
+        ));
+      }
+      WebhookDeliveryStatus::Pending => {}
+    }
+  }
+}
+
+/// Posts the delivery once, a failed attempt is scheduled again until the attempts run out
+async fn attempt_delivery(
+  client: &reqwest::Client,
+  webhook: &ConfigWebhook,
+  delivery: &DatabaseWebhookDelivery,
+) -> DeliveryAttempt {
+  let attempts = delivery.attempts + 1;
+  let timestamp = Utc::now().timestamp().to_string();
+  let result = client
+    .post(&webhook.url)
+    .header("Content-Type", "application/json")
+    .header(EVENT_HEADER, &delivery.event)
+    .header(DELIVERY_HEADER, delivery.id.to_string())
+    .header(TIMESTAMP_HEADER, &timestamp)
+    .header(
+      SIGNATURE_HEADER,
+      sign(&webhook.secret, &timestamp, &delivery.payload),
+    )
+    .body(delivery.payload.clone())
+    .send()
+    .await;
+
+  let (response_status, error) = match result {
+    Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+    Ok(response) => (
+      Some(response.status().as_u16()),
+      Some(format!("Endpoint answered with {}", response.status())),
+    ),
+    Err(e) => (None, Some(e.to_string())),
+  };
+  let status = match (&error, attempts >= webhook.max_attempts.max(1)) {
+    (None, _) => WebhookDeliveryStatus::Delivered,
+    (Some(_), true) => WebhookDeliveryStatus::Failed,
+    (Some(_), false) => WebhookDeliveryStatus::Pending,
+  };
+  let now = Utc::now().timestamp();
+
+  DeliveryAttempt {
+    status,
+    attempts,
+    response_status,
+    error,
+    attempted_at: now,
+    next_attempt_at: (status == WebhookDeliveryStatus::Pending)
+      .then(|| now + retry_delay_secs(attempts)),
+  }
+}
@@ -0,0 +1,262 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use rocket::{
+  http::Status,
+  request::{self, FromRequest},
+  Request, State,
+};
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::Application,
+  config::auth_config::{ApiScope, ConfigApiKey},
+  db::auth::structs::DatabaseApiKey,
+  features::rate_limit,
+};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_w, log_x};
+
+#[derive(Debug, Clone)]
+pub enum AuthError {
+  Missing,
+  Invalid,
+  /// Key is valid, but lacks the scope
+  MissingScope(ApiScope),
+  /// Key is bound to other servers
+  ServerNotAllowed(u64),
+  /// Request signature is missing, stale, reused or doesn't match
+  BadSignature(String),
+  /// Rate limit ran out, with seconds until retry
+  RateLimited(String, u64),
+}
+
+/// # Authorized API key
+/// Key the request was authorized with
+#[derive(Debug, Clone)]
+pub struct AuthorizedKey {
+  pub name: String,
+  pub scopes: Vec<ApiScope>,
+  pub servers: Option<Vec<u64>>,
+}
+
+impl AuthorizedKey {
+  pub fn has_scope(&self, scope: ApiScope) -> bool {
+    self.scopes.contains(&ApiScope::Admin) || self.scopes.contains(&scope)
+  }
+
+  pub fn allows_server(&self, server_id: u64) -> bool {
+    self
+      .servers
+      .as_ref()
+      .is_none_or(|servers| servers.contains(&server_id))
+  }
+}
+
+impl From<ConfigApiKey> for AuthorizedKey {
+  fn from(key: ConfigApiKey) -> Self {
+    AuthorizedKey {
+      name: key.name,
+      scopes: key.scopes,
+      servers: key.servers,
+    }
+  }
+}
+
+impl From<DatabaseApiKey> for AuthorizedKey {
+  fn from(key: DatabaseApiKey) -> Self {
+    AuthorizedKey {
+      name: key.name,
+      scopes: key.scopes,
+      servers: key.servers,
+    }
+  }
+}
+
+/// Scope required by [`ApiAuth`]
+pub trait RequiredScope: Send + Sync + 'static {
+  const SCOPE: ApiScope;
+}
+
+macro_rules! scope_markers {
+  ($($name:ident),* $(,)?) => {
+    $(
+      pub struct $name;
+      impl RequiredScope for $name {
+        const SCOPE: ApiScope = ApiScope::$name;
+      }
+    )*
+  };
+}
+
+/// Marker types for [`ApiAuth`]
+pub mod scopes {
+  use super::{ApiScope, RequiredScope};
+
+  scope_markers!(
+    PlayersRead,
+    PlayersWrite,
+    PunishmentsRead,
+    PunishmentsWrite,
+    VerificationRead,
+    VerificationWrite,
+    LeaderboardsRead,
+    LeaderboardsWrite,
+    LogsWrite,
+    ServersRead,
+    ServersWrite,
+    ServersCommand,
+    WsConnect,
+    EventsRead,
+    MetricsRead,
+    Admin,
+  );
+}
+
+/// # Api auth guard
+/// Authorizes the request with an API key that has scope `S`,
+/// keys bound to servers are only accepted on their own `<server_id>` routes
+// MARK: Api auth guard
+pub struct ApiAuth<S: RequiredScope> {
+  pub key: AuthorizedKey,
+  _scope: PhantomData<S>,
+}
+
+/// Rejects the request and keeps the reason for the catchers
+pub(crate) fn auth_failure<T>(
+  request: &Request<'_>,
+  error: AuthError,
+) -> request::Outcome<T, AuthError> {
+  let status = match error {
+    AuthError::Missing | AuthError::Invalid | AuthError::BadSignature(_) => Status::Unauthorized,
+    AuthError::MissingScope(_) | AuthError::ServerNotAllowed(_) => Status::Forbidden,
+    AuthError::RateLimited(_, _) => Status::TooManyRequests,
+  };
+  request.local_cache(|| Some(error.clone()));
+  request::Outcome::Error((status, error))
+}
+
+/// Token from `Authorization` header (with or without `Bearer ` in front),
+/// websocket clients may also send it as `authorization` cookie
+fn request_token(request: &Request<'_>, scope: ApiScope) -> Option<String> {
+  if let Some(header) = request.headers().get_one("Authorization") {
+    return Some(header.strip_prefix("Bearer ").unwrap_or(header).to_string());
+  }
+  if scope == ApiScope::WsConnect {
+    return request
+      .cookies()
+      .get("authorization")
+      .map(|cookie| cookie.value().to_string());
+  }
+  None
+}
+
+/// Value of `<server_id>` segment of the matched route, if it has one
+pub(crate) fn routed_server_id(request: &Request<'_>) -> Option<u64> {
+  let route = request.route()?;
+  route
+    .uri
+    .path()
+    .trim_start_matches('/')
+    .split('/')
+    .position(|segment| segment == "<server_id>" || segment == "<_server_id>")
+    .and_then(|index| request.routed_segment(index))
+    .and_then(|segment| segment.parse().ok())
+}
+
+#[rocket::async_trait]
+impl<'r, S: RequiredScope> FromRequest<'r> for ApiAuth<S> {
+  type Error = AuthError;
+
+  async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+    let mut app = match request.guard::<&State<Arc<Mutex<Application>>>>().await {
+      request::Outcome::Success(state) => state.lock().await,
+      request::Outcome::Error(_) | request::Outcome::Forward(_) => {
+        return request::Outcome::Error((Status::InternalServerError, AuthError::Missing));
+      }
+    };
+    // Borrowing the fields separately, so the rate limiter can be updated while the databases are locked
+    let app = &mut *app;
+    let rate_limits = app.config.as_ref().and_then(|cfg| cfg.rate_limits.clone());
+
+    if let (Some(limits), Some(ip)) = (&rate_limits, request.client_ip()) {
+      if let Err(e) = app.rate_limiter.check_ip(limits, &ip.to_string()) {
+        return auth_failure(request, AuthError::RateLimited(e.limit, e.retry_after));
+      }
+    }
+
+    let token = match request_token(request, S::SCOPE) {
+      Some(val) => val,
+      None => return auth_failure(request, AuthError::Missing),
+    };
+    let db_handler = match app.databases.as_ref() {
+      Some(db_handler_lock) => Some(db_handler_lock.lock().await),
+      None => None,
+    };
+
+    let config_key = app.config.as_ref().and_then(|cfg| {
+      cfg
+        .auth
+        .api_keys()
+        .into_iter()
+        .find(|key| key.matches(&token))
+    });
+    let key: AuthorizedKey = match config_key {
+      Some(val) => val.into(),
+      None => {
+        let minted_key = match db_handler.as_ref() {
+          Some(db_handler) => match db_handler.auth_database.get_active_api_keys() {
+            Ok(keys) => keys.into_iter().find(|key| key.matches(&token)),
+            Err(e) => {
+              log_w!(format!("Couldn't load minted API keys: {}", e));
+              None
+            }
+          },
+          None => None,
+        };
+        match minted_key {
+          Some(val) => val.into(),
+          None => return auth_failure(request, AuthError::Invalid),
+        }
+      }
+    };
+
+    if let Some(limits) = &rate_limits {
+      let group = rate_limit::route_group(request.uri().path().as_str());
+      if let Err(e) = app.rate_limiter.check_key(limits, &key.name, &group) {
+        return auth_failure(request, AuthError::RateLimited(e.limit, e.retry_after));
+      }
+    }
+
+    if !key.has_scope(S::SCOPE) {
+      return auth_failure(request, AuthError::MissingScope(S::SCOPE));
+    }
+    if let Some(server_id) = routed_server_id(request) {
+      if !key.allows_server(server_id) {
+        return auth_failure(request, AuthError::ServerNotAllowed(server_id));
+      }
+    }
+
+    if let Some(db_handler) = db_handler {
+      let ip = request.client_ip().map(|ip| ip.to_string());
+      let route = request.route().map_or_else(
+        || request.uri().path().to_string(),
+        |route| route.uri.path().to_string(),
+      );
+      if let Err(e) = db_handler
+        .auth_database
+        .record_key_usage(&key.name, ip, &route)
+      {
+        log_w!(format!(
+          "Couldn't record usage of key '{}': {}",
+          key.name, e
+        ));
+      }
+    }
+
+    request.local_cache(|| Some(key.clone()));
+    request::Outcome::Success(ApiAuth {
+      key,
+      _scope: PhantomData,
+    })
+  }
+}
@@ -0,0 +1,68 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::application::routes::{errors::ApiError, messages::ApiErrorBody};
+
+/// Most items one batch request may carry
+pub const MAX_BATCH_SIZE: usize = 500;
+
+/// # Batch response
+/// Results of every item of a batch request, in the order they were sent
+#[derive(Serialize, ToSchema)]
+pub struct BatchResponse<T> {
+  pub succeeded: usize,
+  pub failed: usize,
+  pub items: Vec<BatchItemResult<T>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BatchItemResult<T> {
+  /// Position of the item in the request
+  pub index: usize,
+  pub ok: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub result: Option<T>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<ApiErrorBody>,
+}
+
+impl<T> BatchResponse<T> {
+  pub fn from_results(results: Vec<Result<T, ApiError>>) -> Self {
+    let items: Vec<BatchItemResult<T>> = results
+      .into_iter()
+      .enumerate()
+      .map(|(index, result)| match result {
+        Ok(val) => BatchItemResult {
+          index,
+          ok: true,
+          result: Some(val),
+          error: None,
+        },
+        Err(e) => BatchItemResult {
+          index,
+          ok: false,
+          result: None,
+          error: Some(e.body()),
+        },
+      })
+      .collect();
+    let succeeded = items.iter().filter(|item| item.ok).count();
+
+    BatchResponse {
+      succeeded,
+      failed: items.len() - succeeded,
+      items,
+    }
+  }
+}
+
+/// Rejects empty batches and batches over [`MAX_BATCH_SIZE`]
+pub fn check_batch_size(len: usize) -> Result<(), ApiError> {
+  if len == 0 || len > MAX_BATCH_SIZE {
+    return Err(ApiError::Validation(
+      format!("Batch must have 1 to {} items", MAX_BATCH_SIZE),
+      Some(serde_json::json!({ "items": len, "max_items": MAX_BATCH_SIZE })),
+    ));
+  }
+  Ok(())
+}
@@ -0,0 +1,197 @@
+use rocket::{
+  http::{Header, Status},
+  response::{self, Responder},
+  Request,
+};
+use serde_json::Value;
+
+use crate::application::routes::messages::{http_response_message_error, ApiErrorBody};
+use crate::application::routes::validation::FieldError;
+use crate::logger::{LogLevel, Logger};
+use crate::{log_e, log_x};
+
+/// # API error
+/// Error returned by HTTP routes, rendered as JSON body with `status`, `code`, `message` and `details`
+// MARK: (enum) Api error
+#[derive(Debug)]
+pub enum ApiError {
+  BadRequest(String),
+  Unauthorized(String),
+  Forbidden(String),
+  NotFound(String),
+  Conflict(String),
+  Validation(String, Option<Value>),
+  Locked(String),
+  /// Rate limit that ran out and seconds until the request can be retried
+  RateLimited(String, u64),
+  DatabaseUnavailable,
+  Storage(String),
+  Internal(String),
+  Other(Status, String),
+}
+
+impl ApiError {
+  pub fn status(&self) -> Status {
+    match self {
+      ApiError::BadRequest(_) => Status::BadRequest,
+      ApiError::Unauthorized(_) => Status::Unauthorized,
+      ApiError::Forbidden(_) => Status::Forbidden,
+      ApiError::NotFound(_) => Status::NotFound,
+      ApiError::Conflict(_) => Status::Conflict,
+      ApiError::Validation(_, _) => Status::UnprocessableEntity,
+      ApiError::Locked(_) => Status::Locked,
+      ApiError::RateLimited(_, _) => Status::TooManyRequests,
+      ApiError::DatabaseUnavailable => Status::FailedDependency,
+      ApiError::Storage(_) | ApiError::Internal(_) => Status::InternalServerError,
+      ApiError::Other(status, _) => *status,
+    }
+  }
+
+  /// Machine readable error code
+  pub fn code(&self) -> String {
+    match self {
+      ApiError::BadRequest(_) => "bad_request".to_string(),
+      ApiError::Unauthorized(_) => "unauthorized".to_string(),
+      ApiError::Forbidden(_) => "forbidden".to_string(),
+      ApiError::NotFound(_) => "not_found".to_string(),
+      ApiError::Conflict(_) => "conflict".to_string(),
+      ApiError::Validation(_, _) => "validation_failed".to_string(),
+      ApiError::Locked(_) => "locked".to_string(),
+      ApiError::RateLimited(_, _) => "rate_limited".to_string(),
+      ApiError::DatabaseUnavailable => "database_unavailable".to_string(),
+      ApiError::Storage(_) => "storage_error".to_string(),
+      ApiError::Internal(_) => "internal_error".to_string(),
+      ApiError::Other(status, _) => status_code_name(*status),
+    }
+  }
+
+  pub fn message(&self) -> String {
+    match self {
+      ApiError::BadRequest(message)
+      | ApiError::Unauthorized(message)
+      | ApiError::Forbidden(message)
+      | ApiError::NotFound(message)
+      | ApiError::Conflict(message)
+      | ApiError::Validation(message, _)
+      | ApiError::Locked(message)
+      | ApiError::Internal(message)
+      | ApiError::Other(_, message) => message.clone(),
+      ApiError::RateLimited(limit, _) => format!("Rate limit '{}' exceeded", limit),
+      ApiError::DatabaseUnavailable => "Databases are not configured".to_string(),
+      ApiError::Storage(_) => "Storage error".to_string(),
+    }
+  }
+
+  pub fn details(&self) -> Option<Value> {
+    match self {
+      ApiError::Validation(_, details) => details.clone(),
+      ApiError::RateLimited(limit, retry_after) => Some(serde_json::json!({
+        "limit": limit,
+        "retry_after": retry_after,
+      })),
+      _ => None,
+    }
+  }
+
+  /// Storage errors name tables and constraints, they go to the log instead of the client
+  fn log_storage_error(&self) {
+    if let ApiError::Storage(error) = self {
+      log_e!(format!("Route threw storage error: {}", error));
+    }
+  }
+
+  /// Error as response body, for errors reported inside a successful response
+  pub fn body(&self) -> ApiErrorBody {
+    self.log_storage_error();
+    ApiErrorBody {
+      status: self.status().code,
+      code: self.code(),
+      message: self.message(),
+      details: self.details(),
+    }
+  }
+
+  /// Error matching the given status, used by catchers
+  pub fn from_status(status: Status, message: impl Into<String>) -> Self {
+    let message = message.into();
+    match status.code {
+      400 => ApiError::BadRequest(message),
+      401 => ApiError::Unauthorized(message),
+      403 => ApiError::Forbidden(message),
+      404 => ApiError::NotFound(message),
+      409 => ApiError::Conflict(message),
+      422 => ApiError::Validation(message, None),
+      423 => ApiError::Locked(message),
+      500 => ApiError::Internal(message),
+      _ => ApiError::Other(status, message),
+    }
+  }
+
+  pub fn not_found(what: &str) -> Self {
+    ApiError::NotFound(format!("{} not found", what))
+  }
+
+  pub fn missing_field(field: &str) -> Self {
+    ApiError::Validation(
+      format!("Field '{}' is required", field),
+      Some(serde_json::json!({ "field": field })),
+    )
+  }
+
+  /// `422` listing every invalid field of the request body
+  pub fn invalid_fields(fields: Vec<FieldError>) -> Self {
+    ApiError::Validation(
+      format!("Request has {} invalid field(s)", fields.len()),
+      Some(serde_json::json!({ "fields": fields })),
+    )
+  }
+
+  pub fn leaderboards_disabled() -> Self {
+    ApiError::Other(
+      Status::ServiceUnavailable,
+      "Leaderboards are not enabled".to_string(),
+    )
+  }
+}
+
+fn status_code_name(status: Status) -> String {
+  status
+    .reason_lossy()
+    .to_ascii_lowercase()
+    .replace(['-', ' '], "_")
+    .replace('\'', "")
+}
+
+impl From<rusqlite::Error> for ApiError {
+  fn from(error: rusqlite::Error) -> Self {
+    match error {
+      rusqlite::Error::QueryReturnedNoRows => ApiError::NotFound("Record not found".to_string()),
+      rusqlite::Error::SqliteFailure(ref sqlite_error, _)
+        if sqlite_error.code == rusqlite::ErrorCode::ConstraintViolation =>
+      {
+        ApiError::Conflict("Record already exists".to_string())
+      }
+      error => ApiError::Storage(error.to_string()),
+    }
+  }
+}
+
+impl std::fmt::Display for ApiError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} ({})", self.message(), self.code())
+  }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+  fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+    self.log_storage_error();
+    let status = self.status();
+    let body = http_response_message_error(status, &self.code(), &self.message(), self.details());
+    let mut response = response::Response::build_from(body.respond_to(request)?);
+    response.status(status);
+    if let ApiError::RateLimited(_, retry_after) = self {
+      response.header(Header::new("Retry-After", retry_after.to_string()));
+    }
+    response.ok()
+  }
+}
@@ -0,0 +1,172 @@
+use rocket::{
+  http::{ContentType, Header},
+  response::stream::TextStream,
+  Responder,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::application::routes::errors::ApiError;
+use crate::logger::{LogLevel, Logger};
+use crate::{log_e, log_x};
+
+/// Encoded rows waiting to be sent, the database reader pauses when the client falls behind
+const EXPORT_BUFFER_ROWS: usize = 256;
+
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+  Csv,
+  Ndjson,
+}
+
+impl ExportFormat {
+  /// Reads the `format` query parameter, CSV when it's not set
+  pub fn parse(format: Option<&str>) -> Result<Self, ApiError> {
+    match format {
+      None | Some("csv") => Ok(ExportFormat::Csv),
+      Some("ndjson") => Ok(ExportFormat::Ndjson),
+      Some(other) => Err(ApiError::Validation(
+        format!("Unknown export format '{}'", other),
+        Some(serde_json::json!({ "field": "format", "allowed": ["csv", "ndjson"] })),
+      )),
+    }
+  }
+
+  fn content_type(&self) -> ContentType {
+    match self {
+      ExportFormat::Csv => ContentType::new("text", "csv").with_params(("charset", "utf-8")),
+      ExportFormat::Ndjson => ContentType::new("application", "x-ndjson"),
+    }
+  }
+
+  fn extension(&self) -> &'static str {
+    match self {
+      ExportFormat::Csv => "csv",
+      ExportFormat::Ndjson => "ndjson",
+    }
+  }
+}
+
+/// # Export encoder
+/// Turns rows into lines of the export, CSV columns are the fields of the first row
+struct ExportEncoder {
+  format: ExportFormat,
+  columns: Option<Vec<String>>,
+}
+
+impl ExportEncoder {
+  fn encode<T: Serialize>(&mut self, row: &T) -> String {
+    let value = serde_json::to_value(row).unwrap_or_default();
+    match self.format {
+      ExportFormat::Ndjson => format!("{}\n", value),
+      ExportFormat::Csv => {
+        let mut lines = String::new();
+        let Value::Object(fields) = value else {
+          return format!("{}\r\n", csv_cell(&value));
+        };
+        let columns = self.columns.get_or_insert_with(|| {
+          let columns: Vec<String> = fields.keys().cloned().collect();
+          lines.push_str(&csv_line(columns.iter().map(|column| csv_escape(column))));
+          columns
+        });
+        lines.push_str(&csv_line(
+          columns
+            .iter()
+            .map(|column| fields.get(column).map(csv_cell).unwrap_or_default()),
+        ));
+        lines
+      }
+    }
+  }
+}
+
+fn csv_line(cells: impl Iterator<Item = String>) -> String {
+  let mut line = cells.collect::<Vec<_>>().join(",");
+  line.push_str("\r\n");
+  line
+}
+
+/// Plain values as text, lists and objects as JSON
+fn csv_cell(value: &Value) -> String {
+  match value {
+    Value::Null => String::new(),
+    Value::String(val) => csv_escape(val),
+    Value::Bool(_) | Value::Number(_) => value.to_string(),
+    Value::Array(_) | Value::Object(_) => csv_escape(&value.to_string()),
+  }
+}
+
+fn csv_escape(text: &str) -> String {
+  // Spreadsheets would run text starting like a formula (usernames are picked by players)
+  let text = match text.starts_with(['=', '+', '-', '@']) {
+    true => format!("'{}", text),
+    false => text.to_string(),
+  };
+  if text.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", text.replace('"', "\"\""))
+  } else {
+    text
+  }
+}
+
+/// # Export response
+/// Streamed export, downloaded as `<name>.<format>`
+#[derive(Responder)]
+pub struct ExportResponse<R> {
+  inner: R,
+  content_type: ContentType,
+  disposition: Header<'static>,
+}
+
+/// Streams the rows `produce` reads as CSV or NDJSON.
+///
+/// `produce` runs on a blocking thread and gets a callback for every row, which returns `false`
+/// once the client went away. The first row is read before answering, so a query that fails
+/// right away is an error response. Later rows are sent while they're read, a database error
+/// after the first row can only end the export early, it's logged.
+pub async fn export_stream<T: Serialize + 'static>(
+  name: &'static str,
+  format: ExportFormat,
+  produce: impl FnOnce(&mut dyn FnMut(T) -> bool) -> rusqlite::Result<()> + Send + 'static,
+) -> Result<ExportResponse<TextStream![String]>, ApiError> {
+  let (sender, mut receiver) = mpsc::channel(EXPORT_BUFFER_ROWS);
+  tokio::task::spawn_blocking(move || {
+    let mut encoder = ExportEncoder {
+      format,
+      columns: None,
+    };
+    let result = produce(&mut |row| sender.blocking_send(Ok(encoder.encode(&row))).is_ok());
+    if let Err(e) = result {
+      let _ = sender.blocking_send(Err(e));
+    }
+  });
+
+  let first = match receiver.recv().await {
+    Some(Ok(lines)) => Some(lines),
+    Some(Err(e)) => return Err(ApiError::from(e)),
+    None => None,
+  };
+
+  Ok(ExportResponse {
+    inner: TextStream! {
+      if let Some(lines) = first {
+        yield lines;
+      }
+      while let Some(result) = receiver.recv().await {
+        match result {
+          Ok(lines) => yield lines,
+          Err(e) => {
+            log_e!(format!("Export of '{}' failed: {}", name, e));
+            break;
+          }
+        }
+      }
+    },
+    content_type: format.content_type(),
+    disposition: Header::new(
+      "Content-Disposition",
+      format!("attachment; filename=\"{}.{}\"", name, format.extension()),
+    ),
+  })
+}
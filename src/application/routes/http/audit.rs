@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use rocket::{serde::json::Json, State};
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::Application,
+  db::audit::structs::{AuditActorKind, AuditFilter, DatabaseAuditEntry},
+  routes::{
+    auth::{scopes, ApiAuth},
+    errors::ApiError,
+    messages::ApiErrorBody,
+  },
+};
+
+/// Entries returned when the request doesn't set `limit`
+const DEFAULT_AUDIT_LIMIT: u32 = 100;
+const MAX_AUDIT_LIMIT: u32 = 1000;
+
+/// Query of the audit log route, see its parameters
+#[derive(FromForm)]
+pub struct AuditQuery<'r> {
+  actor_kind: Option<&'r str>,
+  actor: Option<&'r str>,
+  action: Option<&'r str>,
+  target: Option<&'r str>,
+  from: Option<i64>,
+  to: Option<i64>,
+  limit: Option<u32>,
+}
+
+#[utoipa::path(
+  tag = "Audit",
+  params(
+    ("actor_kind" = Option<String>, Query, description = "Only entries of this kind of actor (api_key, discord, system)"),
+    ("actor" = Option<String>, Query, description = "Only entries of this API key name, Discord user ID or subsystem"),
+    ("action" = Option<String>, Query, description = "Only this action (punishment.create), or every action of a subject when it ends with `.` (punishment.)"),
+    ("target" = Option<String>, Query, description = "Only entries about this record (player:<id>, punishment:<id>, discord:<id>, server:<id>, api_key:<name>)"),
+    ("from" = Option<i64>, Query, description = "Only entries from this unix time on"),
+    ("to" = Option<i64>, Query, description = "Only entries until this unix time"),
+    ("limit" = Option<u32>, Query, description = "Entries to return, 100 by default and 1000 at most"),
+  ),
+  responses(
+    (status = 200, description = "Audit log, latest first", body = Vec<DatabaseAuditEntry>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["admin"]))
+)]
+#[get("/api/v1/audit?<query..>")]
+// MARK: Get audit log
+pub async fn get_audit_log(
+  _auth: ApiAuth<scopes::Admin>,
+  app_data: &State<Arc<Mutex<Application>>>,
+  query: AuditQuery<'_>,
+) -> Result<Json<Vec<DatabaseAuditEntry>>, ApiError> {
+  let actor_kind = match query.actor_kind {
+    Some(kind) => Some(
+      AuditActorKind::from_name(kind)
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown actor kind '{}'", kind)))?,
+    ),
+    None => None,
+  };
+  let filter = AuditFilter {
+    actor_kind,
+    actor: query.actor,
+    action: query.action,
+    target: query.target,
+    from: query.from,
+    to: query.to,
+  };
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_AUDIT_LIMIT)
+    .min(MAX_AUDIT_LIMIT);
+
+  let app = app_data.lock().await;
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => db_handler_lock
+      .lock()
+      .await
+      .audit_database
+      .get_entries(&filter, limit)
+      .map_err(ApiError::from)
+      .map(Json),
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
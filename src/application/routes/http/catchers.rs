@@ -0,0 +1,99 @@
+use rocket::{http::Status, Catcher, Request};
+
+use crate::application::routes::errors::ApiError;
+
+use crate::application::routes::auth::AuthError;
+use crate::application::routes::idempotency::IdempotencyError;
+use crate::application::routes::signing::BodyParseError;
+use crate::application::routes::validation::FieldError;
+
+/// 401 with the reason the auth guard rejected the request
+#[catch(401)]
+fn unauthorized_catcher(request: &Request) -> ApiError {
+  match request.local_cache(|| None::<AuthError>) {
+    Some(AuthError::Missing) => ApiError::Unauthorized("Authorization is missing".to_string()),
+    Some(AuthError::Invalid) => ApiError::Unauthorized("Authorization is invalid".to_string()),
+    Some(AuthError::BadSignature(reason)) => {
+      ApiError::Unauthorized(format!("Request signature rejected: {}", reason))
+    }
+    _ => ApiError::Unauthorized("Unauthorized".to_string()),
+  }
+}
+
+/// 403 with the scope or server the API key is missing
+#[catch(403)]
+fn forbidden_catcher(request: &Request) -> ApiError {
+  match request.local_cache(|| None::<AuthError>) {
+    Some(AuthError::MissingScope(scope)) => {
+      ApiError::Forbidden(format!("API key lacks '{}' scope", scope.as_str()))
+    }
+    Some(AuthError::ServerNotAllowed(server_id)) => ApiError::Forbidden(format!(
+      "API key is not allowed to act for server {}",
+      server_id
+    )),
+    _ => ApiError::Forbidden("Forbidden".to_string()),
+  }
+}
+
+/// 429 with the rate limit that ran out
+#[catch(429)]
+fn too_many_requests_catcher(request: &Request) -> ApiError {
+  match request.local_cache(|| None::<AuthError>) {
+    Some(AuthError::RateLimited(limit, retry_after)) => {
+      ApiError::RateLimited(limit.clone(), *retry_after)
+    }
+    _ => ApiError::from_status(Status::TooManyRequests, "Too many requests"),
+  }
+}
+
+#[catch(404)]
+fn not_found_catcher(request: &Request) -> ApiError {
+  ApiError::NotFound(format!(
+    "No route for {} {}",
+    request.method(),
+    request.uri().path()
+  ))
+}
+
+#[catch(422)]
+fn unprocessable_catcher(request: &Request) -> ApiError {
+  match request.local_cache(|| None::<IdempotencyError>) {
+    Some(IdempotencyError::Mismatch) => ApiError::Validation(
+      "Idempotency key was already used for another request".to_string(),
+      Some(serde_json::json!({ "header": "Idempotency-Key" })),
+    ),
+    _ => match request.local_cache(|| None::<BodyParseError>) {
+      // Malformed JSON, wrong types and user IDs of the wrong format
+      Some(BodyParseError(reason)) => ApiError::invalid_fields(vec![FieldError {
+        field: "body".to_string(),
+        message: reason.clone(),
+      }]),
+      None => ApiError::Validation("Request data could not be parsed".to_string(), None),
+    },
+  }
+}
+
+/// Every other status, using its canonical reason as message
+#[catch(default)]
+fn default_catcher(status: Status, request: &Request) -> ApiError {
+  match request.local_cache(|| None::<IdempotencyError>) {
+    Some(IdempotencyError::Invalid) => ApiError::BadRequest(
+      "Idempotency key must be 1 to 255 printable ASCII characters".to_string(),
+    ),
+    Some(IdempotencyError::InProgress) => {
+      ApiError::Conflict("Request with this idempotency key is still being processed".to_string())
+    }
+    _ => ApiError::from_status(status, status.reason_lossy()),
+  }
+}
+
+pub(crate) fn get_catchers() -> Vec<Catcher> {
+  catchers![
+    unauthorized_catcher,
+    forbidden_catcher,
+    not_found_catcher,
+    too_many_requests_catcher,
+    unprocessable_catcher,
+    default_catcher
+  ]
+}
@@ -1,25 +1,53 @@
 use chrono::DateTime;
-use rocket::{http::Status, serde::json::Json, State};
+use rocket::{response::stream::TextStream, serde::json::Json, State};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use utoipa::ToSchema;
 
 use crate::application::{
   application::Application,
-  db::leaderboards::structs::{LeaderboardRecord, LeaderboardRecordType},
-  routes::{http::DbAuthHeader, messages::http_response_message_200},
-  utils,
+  db::{
+    audit::structs::AuditActor,
+    database::BatchOperations as _,
+    leaderboards::{
+      structs::{LeaderboardRecord, LeaderboardRecordType},
+      LeaderboardDatabase,
+    },
+  },
+  features::audit::{self, actions},
+  routes::{
+    auth::{scopes, ApiAuth},
+    batch::{check_batch_size, BatchResponse},
+    errors::ApiError,
+    idempotency::Idempotent,
+    export::{export_stream, ExportFormat, ExportResponse},
+    messages::{http_response_message_200, ApiErrorBody, ApiMessageBody},
+    signing::{SignedJson, SignedRequest},
+    validation::{validated, FieldErrors, Validate},
+  },
 };
 
-#[get("/db/leaderboards/get?<player_id>&<type>")]
+#[utoipa::path(
+  tag = "Leaderboards",
+  params(("type" = Option<u8>, Query, description = "Leaderboard record type")),
+  responses(
+    (status = 200, description = "Success", body = Vec<LeaderboardRecord>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["leaderboards:read"]))
+)]
+#[get("/api/v1/leaderboards?<player_id>&<type>")]
 // MARK: Get player leaderboards by player_id and type
 pub async fn db_get_player_leaderboards(
-  _auth_header: DbAuthHeader,
+  _auth: ApiAuth<scopes::LeaderboardsRead>,
+  _signed: SignedRequest,
   app_data: &State<Arc<Mutex<Application>>>,
   player_id: Option<u64>,
   r#type: Option<u8>,
-) -> Result<Json<Vec<LeaderboardRecord>>, Status> {
+) -> Result<Json<Vec<LeaderboardRecord>>, ApiError> {
   let app = app_data.lock().await;
   let kind = r#type.and_then(|val| LeaderboardRecordType::from_u8(val).ok());
 
@@ -32,40 +60,95 @@ pub async fn db_get_player_leaderboards(
           if let (Some(p_id), Some(kind)) = (player_id, kind) {
             leaderboard_db
               .get_all_from_player_by_type(p_id, kind)
-              .map_err(|_| Status::InternalServerError)
+              .map_err(ApiError::from)
               .map(|val| Json(val))
           } else if let Some(p_id) = player_id {
             leaderboard_db
               .get_all_from_player(p_id)
-              .map_err(|_| Status::InternalServerError)
+              .map_err(ApiError::from)
               .map(|val| Json(val))
           } else if let Some(kind) = kind {
             leaderboard_db
               .get_all_by_type(kind)
-              .map_err(|_| Status::InternalServerError)
+              .map_err(ApiError::from)
               .map(|val| Json(val))
           } else {
             leaderboard_db
               .get_all_data()
-              .map_err(|_| Status::InternalServerError)
+              .map_err(ApiError::from)
               .map(|val| Json(val))
           }
         }
-        None => Err(Status::NoContent),
+        None => Err(ApiError::leaderboards_disabled()),
       }
     }
-    None => Err(Status::FailedDependency),
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Leaderboards",
+  params(
+    ("type" = Option<u8>, Query, description = "Leaderboard record type"),
+    ("format" = Option<String>, Query, description = "`csv` (default) or `ndjson`"),
+  ),
+  responses(
+    (status = 200, description = "Leaderboard records, streamed", content((String = "text/csv"), (String = "application/x-ndjson"))),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["leaderboards:read"]))
+)]
+#[get("/api/v1/leaderboards/export?<player_id>&<type>&<format>")]
+// MARK: Export leaderboards
+pub async fn db_export_leaderboards(
+  _auth: ApiAuth<scopes::LeaderboardsRead>,
+  _signed: SignedRequest,
+  app_data: &State<Arc<Mutex<Application>>>,
+  player_id: Option<u64>,
+  r#type: Option<u8>,
+  format: Option<&str>,
+) -> Result<ExportResponse<TextStream![String]>, ApiError> {
+  let format = ExportFormat::parse(format)?;
+  let app = app_data.lock().await;
+  let kind = r#type.and_then(|val| LeaderboardRecordType::from_u8(val).ok());
+
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => {
+      let leaderboard_db = match db_handler_lock.lock().await.leaderboard_database.as_ref() {
+        Some(val) => val
+          .open_reader()
+          .map_err(|_| ApiError::DatabaseUnavailable)?,
+        None => return Err(ApiError::leaderboards_disabled()),
+      };
+      export_stream("leaderboards", format, move |on_row| {
+        leaderboard_db.export_records(player_id, kind, on_row)
+      })
+      .await
+    }
+    None => Err(ApiError::DatabaseUnavailable),
   }
 }
 
-#[patch("/db/leaderboards/clear?<player_id>&<type>")]
+#[utoipa::path(
+  tag = "Leaderboards",
+  params(("type" = Option<u8>, Query, description = "Leaderboard record type")),
+  responses(
+    (status = 200, response = ApiMessageBody),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["leaderboards:write"]))
+)]
+#[patch("/api/v1/leaderboards/clear?<player_id>&<type>")]
 // MARK: Clear leaderboards
 pub async fn db_clear_leaderboards(
-  _auth_header: DbAuthHeader,
+  auth: ApiAuth<scopes::LeaderboardsWrite>,
+  _signed: SignedRequest,
   app_data: &State<Arc<Mutex<Application>>>,
   player_id: u64,
   r#type: Option<u8>,
-) -> Result<Json<Value>, Status> {
+) -> Result<Json<Value>, ApiError> {
   let app = app_data.lock().await;
   let kind = r#type.and_then(|val| LeaderboardRecordType::from_u8(val).ok());
 
@@ -75,33 +158,58 @@ pub async fn db_clear_leaderboards(
 
       match db_handler.leaderboard_database.as_ref() {
         Some(leaderboard_db) => {
-          if let Some(kind) = kind {
+          let removed = if let Some(kind) = kind {
+            let removed = leaderboard_db
+              .get_all_from_player_by_type(player_id, kind)
+              .map_err(ApiError::from)?;
             leaderboard_db
               .clear_all_from_player_by_type(player_id, kind)
-              .map_err(|_| Status::InternalServerError)
-              .map(|_| http_response_message_200())
+              .map_err(ApiError::from)?;
+            removed
           } else {
+            let removed = leaderboard_db
+              .get_all_from_player(player_id)
+              .map_err(ApiError::from)?;
             leaderboard_db
               .clear_all_from_player(player_id)
-              .map_err(|_| Status::InternalServerError)
-              .map(|_| http_response_message_200())
-          }
+              .map_err(ApiError::from)?;
+            removed
+          };
+          audit::record(
+            &db_handler,
+            &AuditActor::api_key(&auth.key.name),
+            actions::LEADERBOARD_CLEAR,
+            audit::target("player", player_id),
+            &removed,
+            (),
+          );
+          Ok(http_response_message_200())
         }
-        None => Err(Status::NoContent),
+        None => Err(ApiError::leaderboards_disabled()),
       }
     }
-    None => Err(Status::FailedDependency),
+    None => Err(ApiError::DatabaseUnavailable),
   }
 }
 
-#[patch("/db/leaderboards/remove?<player_id>&<timestamp>")]
+#[utoipa::path(
+  tag = "Leaderboards",
+  responses(
+    (status = 200, response = ApiMessageBody),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["leaderboards:write"]))
+)]
+#[patch("/api/v1/leaderboards/remove?<player_id>&<timestamp>")]
 // MARK: Remove leaderboard record by date
 pub async fn db_leaderboards_remove_by_date(
-  _auth_header: DbAuthHeader,
+  auth: ApiAuth<scopes::LeaderboardsWrite>,
+  _signed: SignedRequest,
   app_data: &State<Arc<Mutex<Application>>>,
   player_id: u64,
   timestamp: u64,
-) -> Result<Json<Value>, Status> {
+) -> Result<Json<Value>, ApiError> {
   let app = app_data.lock().await;
 
   match app.databases.as_ref() {
@@ -109,60 +217,152 @@ pub async fn db_leaderboards_remove_by_date(
       let db_handler = db_handler_lock.lock().await;
 
       match db_handler.leaderboard_database.as_ref() {
-        Some(leaderboard_db) => leaderboard_db
-          .remove_from_player_by_date(
-            player_id,
-            DateTime::from_timestamp(timestamp as i64, 0).unwrap(),
-          )
-          .map(|_| http_response_message_200())
-          .map_err(|_| Status::InternalServerError),
-        None => Err(Status::NoContent),
+        Some(leaderboard_db) => {
+          let date_time = DateTime::from_timestamp(timestamp as i64, 0).unwrap();
+          let removed: Vec<LeaderboardRecord> = leaderboard_db
+            .get_all_from_player(player_id)
+            .map_err(ApiError::from)?
+            .into_iter()
+            .filter(|record| record.date_time == date_time)
+            .collect();
+          leaderboard_db
+            .remove_from_player_by_date(player_id, date_time)
+            .map_err(ApiError::from)?;
+          audit::record(
+            &db_handler,
+            &AuditActor::api_key(&auth.key.name),
+            actions::LEADERBOARD_REMOVE,
+            audit::target("player", player_id),
+            &removed,
+            (),
+          );
+          Ok(http_response_message_200())
+        }
+        None => Err(ApiError::leaderboards_disabled()),
       }
     }
-    None => Err(Status::FailedDependency),
+    None => Err(ApiError::DatabaseUnavailable),
   }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub(crate) struct LeaderboardStatRequest {
   pub player_id: u64,
   #[serde(rename = "type")]
   pub r#type: LeaderboardRecordType,
   pub value: f64,
+  /// Unix timestamp in seconds
   pub date_time: u64,
 }
 
+impl Validate for LeaderboardStatRequest {
+  fn validate(&mut self, fields: &mut FieldErrors) {
+    if !self.value.is_finite() {
+      fields.invalid("value", "Must be a finite number");
+    }
+  }
+}
+
+/// Adds one stat, `date_time` is unix timestamp in seconds
+fn add_stat(
+  leaderboard_db: &LeaderboardDatabase,
+  stat: &LeaderboardStatRequest,
+) -> Result<LeaderboardRecord, ApiError> {
+  let date_time = i64::try_from(stat.date_time)
+    .ok()
+    .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+    .ok_or_else(|| {
+      ApiError::Validation(
+        "Field 'date_time' is not a valid unix timestamp".to_string(),
+        Some(serde_json::json!({ "field": "date_time" })),
+      )
+    })?;
+  leaderboard_db
+    .add_stat_to_player(stat.player_id, stat.r#type, stat.value, date_time)
+    .map_err(ApiError::from)?;
+  Ok(LeaderboardRecord {
+    player_id: stat.player_id,
+    r#type: stat.r#type,
+    value: stat.value,
+    date_time,
+  })
+}
+
+#[utoipa::path(
+  tag = "Leaderboards",
+  request_body = LeaderboardStatRequest,
+  responses(
+    (status = 200, response = ApiMessageBody),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["leaderboards:write"]))
+)]
 #[post(
-  "/db/leaderboards/add",
+  "/api/v1/leaderboards",
   format = "application/json",
   data = "<parsed_data>"
 )]
 // MARK: Add stat to player
 pub async fn db_add_stat_to_players_leaderboards(
-  _auth_header: DbAuthHeader,
+  _auth: ApiAuth<scopes::LeaderboardsWrite>,
+  _signed: SignedRequest,
+  _idempotent: Idempotent,
   app_data: &State<Arc<Mutex<Application>>>,
-  parsed_data: Json<LeaderboardStatRequest>,
-) -> Result<Json<Value>, Status> {
+  parsed_data: SignedJson<LeaderboardStatRequest>,
+) -> Result<Json<Value>, ApiError> {
+  let body = validated(parsed_data.0)?;
   let app = app_data.lock().await;
   match app.databases.as_ref() {
     Some(db_handler_lock) => {
       let db_handler = db_handler_lock.lock().await;
-      let date_time =
-        utils::time::parse_rfc3339_to_utc(parsed_data.0.date_time.to_string()).unwrap();
-
       match db_handler.leaderboard_database.as_ref() {
+        Some(leaderboard_db) => {
+          add_stat(leaderboard_db, &body).map(|_| http_response_message_200())
+        }
+        None => Err(ApiError::leaderboards_disabled()),
+      }
+    }
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Leaderboards",
+  request_body = Vec<LeaderboardStatRequest>,
+  responses(
+    (status = 200, description = "Result of every stat, processed in one transaction", body = BatchResponse<LeaderboardRecord>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["leaderboards:write"]))
+)]
+#[post(
+  "/api/v1/leaderboards/batch",
+  format = "application/json",
+  data = "<parsed_data>"
+)]
+// MARK: Add stats to players
+pub async fn db_add_stats_to_players_leaderboards(
+  _auth: ApiAuth<scopes::LeaderboardsWrite>,
+  _signed: SignedRequest,
+  _idempotent: Idempotent,
+  app_data: &State<Arc<Mutex<Application>>>,
+  parsed_data: SignedJson<Vec<LeaderboardStatRequest>>,
+) -> Result<Json<BatchResponse<LeaderboardRecord>>, ApiError> {
+  check_batch_size(parsed_data.0.len())?;
+  let app = app_data.lock().await;
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => {
+      let mut db_handler = db_handler_lock.lock().await;
+      match db_handler.leaderboard_database.as_mut() {
         Some(leaderboard_db) => leaderboard_db
-          .add_stat_to_player(
-            parsed_data.0.player_id,
-            parsed_data.0.r#type,
-            parsed_data.0.value,
-            date_time,
-          )
-          .map(|_| http_response_message_200())
-          .map_err(|_| Status::InternalServerError),
-        None => Err(Status::NoContent),
+          .run_batch(parsed_data.0, |db, stat| add_stat(db, &validated(stat)?))
+          .map_err(ApiError::from)
+          .map(|results| Json(BatchResponse::from_results(results))),
+        None => Err(ApiError::leaderboards_disabled()),
       }
     }
-    None => Err(Status::FailedDependency),
+    None => Err(ApiError::DatabaseUnavailable),
   }
 }
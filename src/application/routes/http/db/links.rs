@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use rocket::serde::json::Json;
+use rocket::State;
+use tokio::sync::Mutex;
+
+use crate::application::application::Application;
+use crate::application::db::audit::structs::AuditActor;
+use crate::application::db::player::structs::{DatabaseAccountLink, DatabaseModifyAccountLink};
+use crate::application::features::audit::{self, actions};
+use crate::application::features::verification::{
+    on_discord_user_changed, on_verification_changed,
+};
+use crate::application::routes::errors::ApiError;
+use crate::application::routes::messages::ApiErrorBody;
+use crate::application::routes::auth::{scopes, ApiAuth};
+use crate::application::routes::idempotency::Idempotent;
+use crate::application::routes::signing::{SignedJson, SignedRequest};
+use crate::application::routes::validation::{
+    discord_id_param, validated, FieldErrors, Validate,
+};
+
+impl Validate for DatabaseModifyAccountLink {
+    fn validate(&mut self, fields: &mut FieldErrors) {
+        fields.discord_id("discord_id", &mut self.discord_id);
+    }
+}
+
+#[utoipa::path(
+    tag = "Account links",
+    params(("discord_id" = String, Path, description = "Discord user ID of 17 to 20 digits")),
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabaseAccountLink>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:read"]))
+)]
+#[get("/api/v1/links/<discord_id>")]
+// MARK: Get account links
+pub async fn db_get_account_links(
+    _auth: ApiAuth<scopes::VerificationRead>,
+    _signed: SignedRequest,
+    app_data: &State<Arc<Mutex<Application>>>,
+    discord_id: &str,
+) -> Result<Json<Vec<DatabaseAccountLink>>, ApiError> {
+    let discord_id = discord_id_param("discord_id", discord_id)?;
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            let db_handler = db_handler_lock.lock().await;
+            db_handler
+                .player_database
+                .get_account_links(&discord_id)
+                .map_err(ApiError::from)
+                .map(Json)
+        }
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
+
+#[utoipa::path(
+    tag = "Account links",
+    request_body = DatabaseModifyAccountLink,
+    responses(
+        (status = 200, description = "Success"),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:write"]))
+)]
+#[post(
+    "/api/v1/links/primary",
+    format = "application/json",
+    data = "<parsed_data>"
+)]
+// MARK: Set primary account
+pub async fn db_set_primary_account(
+    auth: ApiAuth<scopes::VerificationWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
+    app_data: &State<Arc<Mutex<Application>>>,
+    parsed_data: SignedJson<DatabaseModifyAccountLink>,
+) -> Result<(), ApiError> {
+    let body = validated(parsed_data.0)?;
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            let mut db_handler = db_handler_lock.lock().await;
+            let before = db_handler
+                .player_database
+                .get_account_links(&body.discord_id)
+                .map_err(ApiError::from)?;
+            db_handler
+                .player_database
+                .set_primary_account(&body.discord_id, body.player_id)
+                .map_err(ApiError::from)?;
+            audit::record(
+                &db_handler,
+                &AuditActor::api_key(&auth.key.name),
+                actions::LINK_PRIMARY,
+                audit::target("discord", &body.discord_id),
+                &before,
+                db_handler
+                    .player_database
+                    .get_account_links(&body.discord_id)
+                    .ok(),
+            );
+            on_discord_user_changed(app_data.inner(), body.discord_id);
+            Ok(())
+        }
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
+
+#[utoipa::path(
+    tag = "Account links",
+    request_body = DatabaseModifyAccountLink,
+    responses(
+        (status = 200, description = "Success"),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:write"]))
+)]
+#[post(
+    "/api/v1/links/unlink",
+    format = "application/json",
+    data = "<parsed_data>"
+)]
+// MARK: Unlink account
+pub async fn db_unlink_account(
+    auth: ApiAuth<scopes::VerificationWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
+    app_data: &State<Arc<Mutex<Application>>>,
+    parsed_data: SignedJson<DatabaseModifyAccountLink>,
+) -> Result<(), ApiError> {
+    let body = validated(parsed_data.0)?;
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            let mut db_handler = db_handler_lock.lock().await;
+            let before = db_handler
+                .player_database
+                .get_account_links(&body.discord_id)
+                .map_err(ApiError::from)?;
+            db_handler
+                .player_database
+                .unlink_account(&body.discord_id, body.player_id)
+                .map_err(ApiError::from)?;
+            audit::record(
+                &db_handler,
+                &AuditActor::api_key(&auth.key.name),
+                actions::LINK_UNLINK,
+                audit::target("discord", &body.discord_id),
+                &before,
+                db_handler
+                    .player_database
+                    .get_account_links(&body.discord_id)
+                    .ok(),
+            );
+            on_verification_changed(app_data.inner(), body.player_id);
+            on_discord_user_changed(app_data.inner(), body.discord_id);
+            Ok(())
+        }
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
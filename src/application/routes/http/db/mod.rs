@@ -1,4 +1,5 @@
 pub(crate) mod leaderboard;
+pub(crate) mod links;
 pub(crate) mod players;
 pub(crate) mod punishments;
 pub(crate) mod verification;
@@ -1,53 +1,156 @@
 use std::sync::Arc;
 
 use chrono::Utc;
-use rand::distributions::Alphanumeric;
-use rand::{thread_rng, Rng};
-use rocket::http::Status;
+use rocket::response::stream::TextStream;
 use rocket::serde::json::Json;
 use rocket::State;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
+use utoipa::ToSchema;
 
 use crate::application::application::Application;
+use crate::application::db::audit::structs::AuditActor;
+use crate::application::features::audit::{self, actions};
+use crate::application::features::events::EventKind;
+use crate::application::db::player::identity::PlayerIdentity;
 use crate::application::db::player::structs::{
     DatabasePlayer, DatabasePlayerCount, DatabasePlayerJoin,
 };
 use crate::application::db::punishments::structs::{DatabasePunishment, PunishmentType};
-use crate::application::routes::http::DbAuthHeader;
+use crate::application::db::punishments::PunishmentDatabase;
+use crate::application::db::database::BatchOperations as _;
+use crate::application::routes::batch::{check_batch_size, BatchResponse};
+use crate::application::routes::errors::ApiError;
+use crate::application::routes::export::{export_stream, ExportFormat, ExportResponse};
+use crate::application::routes::messages::ApiErrorBody;
+use crate::application::routes::auth::{scopes, ApiAuth};
+use crate::application::routes::idempotency::Idempotent;
+use crate::application::routes::signing::{SignedJson, SignedRequest};
+use crate::application::routes::validation::{
+    discord_id_param, validated, FieldErrors, Validate, MAX_NAME_LENGTH, MAX_REASON_LENGTH,
+};
+
+/// Publishes the join without the player's IPs and contact details
+fn publish_player_join(app: &Application, server_id: u64, player: &DatabasePlayer) {
+    app.events.publish(
+        EventKind::PlayerJoin,
+        Some(server_id),
+        serde_json::json!({
+            "player_id": player.player_id,
+            "steam_id": player.steam_id,
+            "username": player.usernames.last(),
+            "times_joined": player.times_joined,
+        }),
+    );
+}
+
+impl Validate for DatabasePlayerJoin {
+    fn validate(&mut self, fields: &mut FieldErrors) {
+        fields.text("username", &mut self.username, MAX_NAME_LENGTH);
+        fields.ip("ip_addr", &mut self.ip_addr);
+    }
+}
 
+#[utoipa::path(
+    tag = "Players",
+    request_body = DatabasePlayerJoin,
+    responses(
+        (status = 200, description = "Joined player", body = DatabasePlayer),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:write"]))
+)]
 #[post(
-    "/db/player/join/<_server_id>",
+    "/api/v1/servers/<server_id>/joins",
     format = "application/json",
     data = "<parsed_data>"
 )]
 // MARK: On player join
 pub async fn db_on_player_join(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PlayersWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
     app_data: &State<Arc<Mutex<Application>>>,
-    _server_id: u64, // unused for now
-    parsed_data: Json<DatabasePlayerJoin>,
-) -> Result<Json<DatabasePlayer>, Status> {
+    server_id: u64,
+    parsed_data: SignedJson<DatabasePlayerJoin>,
+) -> Result<Json<DatabasePlayer>, ApiError> {
+    let body = validated(parsed_data.0)?;
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
             let mut db_handler = db_handler_lock.lock().await;
-            db_handler
+            let player = db_handler
                 .player_database
-                .player_joined(parsed_data.0)
-                .map_err(|_| Status::InternalServerError)
-                .map(|val| Json(val))
+                .player_joined(body)
+                .map_err(ApiError::from)?;
+            publish_player_join(&app, server_id, &player);
+            Ok(Json(player))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/player/get/all")]
+#[utoipa::path(
+    tag = "Players",
+    request_body = Vec<DatabasePlayerJoin>,
+    responses(
+        (status = 200, description = "Result of every join, processed in one transaction", body = BatchResponse<DatabasePlayer>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:write"]))
+)]
+#[post(
+    "/api/v1/servers/<server_id>/joins/batch",
+    format = "application/json",
+    data = "<parsed_data>"
+)]
+// MARK: On player join batch
+pub async fn db_on_player_join_batch(
+    _auth: ApiAuth<scopes::PlayersWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
+    app_data: &State<Arc<Mutex<Application>>>,
+    server_id: u64,
+    parsed_data: SignedJson<Vec<DatabasePlayerJoin>>,
+) -> Result<Json<BatchResponse<DatabasePlayer>>, ApiError> {
+    check_batch_size(parsed_data.0.len())?;
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            let mut db_handler = db_handler_lock.lock().await;
+            let results = db_handler
+                .player_database
+                .run_batch(parsed_data.0, |db, join| {
+                    db.player_joined(validated(join)?).map_err(ApiError::from)
+                })
+                .map_err(ApiError::from)?;
+            for player in results.iter().flatten() {
+                publish_player_join(&app, server_id, player);
+            }
+            Ok(Json(BatchResponse::from_results(results)))
+        }
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
+
+#[utoipa::path(
+    tag = "Players",
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabasePlayer>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:read"]))
+)]
+#[get("/api/v1/players")]
 // MARK: Get all players
 pub async fn db_get_all_players(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PlayersRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
-) -> Result<Json<Vec<DatabasePlayer>>, Status> {
+) -> Result<Json<Vec<DatabasePlayer>>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -55,20 +158,68 @@ pub async fn db_get_all_players(
             db_handler
                 .player_database
                 .get_all_players()
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
+
+#[utoipa::path(
+    tag = "Players",
+    params(("format" = Option<String>, Query, description = "`csv` (default) or `ndjson`")),
+    responses(
+        (status = 200, description = "Every player, streamed", content((String = "text/csv"), (String = "application/x-ndjson"))),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:read"]))
+)]
+#[get("/api/v1/players/export?<format>")]
+// MARK: Export players
+pub async fn db_export_players(
+    _auth: ApiAuth<scopes::PlayersRead>,
+    _signed: SignedRequest,
+    app_data: &State<Arc<Mutex<Application>>>,
+    format: Option<&str>,
+) -> Result<ExportResponse<TextStream![String]>, ApiError> {
+    let format = ExportFormat::parse(format)?;
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            // Own connection, so the export doesn't hold the database lock
+            let player_db = db_handler_lock
+                .lock()
+                .await
+                .player_database
+                .open_reader()
+                .map_err(|_| ApiError::DatabaseUnavailable)?;
+            export_stream("players", format, move |on_row| {
+                player_db.export_players(on_row)
+            })
+            .await
+        }
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/player/get/id/<player_id>")]
+#[utoipa::path(
+    tag = "Players",
+    responses(
+        (status = 200, description = "Success", body = DatabasePlayer),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:read"]))
+)]
+#[get("/api/v1/players/<player_id>")]
 // MARK: Get player by player id
 pub async fn db_get_player_by_player_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PlayersRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     player_id: u64,
-) -> Result<Json<DatabasePlayer>, Status> {
+) -> Result<Json<DatabasePlayer>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -76,59 +227,83 @@ pub async fn db_get_player_by_player_id(
             db_handler
                 .player_database
                 .get_player_by_id(player_id)
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/player/get/steam/<steam_id>")]
+#[utoipa::path(
+    tag = "Players",
+    params(("steam_id" = PlayerIdentity, Path, description = "SCP:SL user ID")),
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabasePlayer>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:read"]))
+)]
+#[get("/api/v1/players/by-user/<steam_id>", rank = 2)]
 // MARK: Get player by steam id
 pub async fn db_get_player_by_steam_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PlayersRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
-    steam_id: &str,
-) -> Result<Json<Vec<DatabasePlayer>>, Status> {
+    steam_id: PlayerIdentity,
+) -> Result<Json<Vec<DatabasePlayer>>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
             let db_handler = db_handler_lock.lock().await;
             db_handler
                 .player_database
-                .get_players_by_steam(steam_id)
-                .map_err(|_| Status::InternalServerError)
+                .get_players_by_identity(&steam_id)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/player/get/discord/<discord_id>")]
+#[utoipa::path(
+    tag = "Players",
+    params(("discord_id" = String, Path, description = "Discord user ID of 17 to 20 digits")),
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabasePlayer>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:read"]))
+)]
+#[get("/api/v1/players/by-discord/<discord_id>", rank = 2)]
 // MARK: Get player by discord id
 pub async fn db_get_player_by_discord_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PlayersRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     discord_id: &str,
-) -> Result<Json<Vec<DatabasePlayer>>, Status> {
+) -> Result<Json<Vec<DatabasePlayer>>, ApiError> {
+    // Unlinked players store the "NULL" sentinel, it must not match them
+    let discord_id = discord_id_param("discord_id", discord_id)?;
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
             let db_handler = db_handler_lock.lock().await;
             db_handler
                 .player_database
-                .get_players_by_discord(discord_id)
-                .map_err(|_| Status::InternalServerError)
+                .get_players_by_discord(&discord_id)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct DatabasePlayerPunishment {
     username: String,
-    steam_id: String,
+    steam_id: PlayerIdentity,
     ip: String,
     reason: String,
     punishment_duration: u32,
@@ -138,18 +313,47 @@ pub struct DatabasePlayerPunishment {
     punishment_type: PunishmentType,
 }
 
+impl Validate for DatabasePlayerPunishment {
+    fn validate(&mut self, fields: &mut FieldErrors) {
+        fields.text("username", &mut self.username, MAX_NAME_LENGTH);
+        fields.ip("ip", &mut self.ip);
+        fields.text("reason", &mut self.reason, MAX_REASON_LENGTH);
+        fields.text("issuer_name", &mut self.issuer_name, MAX_NAME_LENGTH);
+        // Punishments issued from the server console have no issuer ID or IP
+        if !self.issuer_steam_id.trim().is_empty() {
+            fields.user_id("issuer_steam_id", &mut self.issuer_steam_id);
+        }
+        if !self.issuer_ip.trim().is_empty() {
+            fields.ip("issuer_ip", &mut self.issuer_ip);
+        }
+    }
+}
+
+#[utoipa::path(
+    tag = "Players",
+    request_body = DatabasePlayerPunishment,
+    responses(
+        (status = 200, description = "Created punishment", body = DatabasePunishment),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["punishments:write"]))
+)]
 #[post(
-    "/db/player/punishment/add/<player_id>",
+    "/api/v1/players/<player_id>/punishments",
     format = "application/json",
     data = "<parsed_data>"
 )]
 // MARK: Add punishment to player
 pub async fn db_add_punishment_to_player(
-    _auth_header: DbAuthHeader,
+    auth: ApiAuth<scopes::PunishmentsWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
     app_data: &State<Arc<Mutex<Application>>>,
     player_id: u64,
-    parsed_data: Json<DatabasePlayerPunishment>,
-) -> Result<Json<DatabasePunishment>, Status> {
+    parsed_data: SignedJson<DatabasePlayerPunishment>,
+) -> Result<Json<DatabasePunishment>, ApiError> {
+    let body = validated(parsed_data.0)?;
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -157,27 +361,23 @@ pub async fn db_add_punishment_to_player(
             let mut player = db_handler
                 .player_database
                 .get_player_by_id(player_id)
-                .map_err(|_| Status::ExpectationFailed)?;
+                .map_err(ApiError::from)?;
 
-            let punishment_id: String = thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(8)
-                .map(char::from)
-                .collect();
+            let punishment_id = PunishmentDatabase::generate_punishment_id();
 
             let data = DatabasePunishment {
                 punishment_id: (&punishment_id).to_string(),
                 player_id,
-                username: parsed_data.0.username,
-                steam_id: parsed_data.0.steam_id,
-                ip: parsed_data.0.ip,
-                reason: parsed_data.0.reason,
-                punishment_duration: parsed_data.0.punishment_duration,
+                username: body.username,
+                steam_id: body.steam_id.user_id(),
+                ip: body.ip,
+                reason: body.reason,
+                punishment_duration: body.punishment_duration,
                 punishment_created_at: Utc::now(),
-                issuer_steam_id: parsed_data.0.issuer_steam_id,
-                issuer_name: parsed_data.0.issuer_name,
-                issuer_ip: parsed_data.0.issuer_ip,
-                punishment_type: parsed_data.0.punishment_type,
+                issuer_steam_id: body.issuer_steam_id,
+                issuer_name: body.issuer_name,
+                issuer_ip: body.issuer_ip,
+                punishment_type: body.punishment_type,
             };
 
             match db_handler.punishment_database.create_new_punishment(&data) {
@@ -188,24 +388,58 @@ pub async fn db_add_punishment_to_player(
                         player.ban_ids = Some(vec![punishment_id]);
                     }
                     match db_handler.player_database.modify_player(player_id, player) {
-                        Ok(_) => Ok(Json(data)),
-                        Err(_) => Err(Status::InternalServerError),
+                        Ok(_) => {
+                            audit::record(
+                                &db_handler,
+                                &AuditActor::api_key(&auth.key.name),
+                                actions::PUNISHMENT_CREATE,
+                                audit::target("player", player_id),
+                                (),
+                                &data,
+                            );
+                            app.events.publish(
+                                EventKind::PunishmentCreated,
+                                None,
+                                serde_json::json!({
+                                    "punishment_id": data.punishment_id,
+                                    "player_id": data.player_id,
+                                    "username": data.username,
+                                    "steam_id": data.steam_id,
+                                    "reason": data.reason,
+                                    "punishment_type": data.punishment_type,
+                                    "punishment_duration": data.punishment_duration,
+                                    "issuer_name": data.issuer_name,
+                                }),
+                            );
+                            Ok(Json(data))
+                        }
+                        Err(e) => Err(ApiError::Storage(e)),
                     }
                 }
-                Err(_) => Err(Status::NotModified),
+                Err(e) => Err(ApiError::from(e)),
             }
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/player/count?<since>")]
+#[utoipa::path(
+    tag = "Players",
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabasePlayerCount>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:read"]))
+)]
+#[get("/api/v1/player-counts?<since>")]
 // MARK: Get player count
 pub async fn db_get_player_count(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PlayersRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     since: Option<u64>,
-) -> Result<Json<Vec<DatabasePlayerCount>>, Status> {
+) -> Result<Json<Vec<DatabasePlayerCount>>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -214,31 +448,84 @@ pub async fn db_get_player_count(
                 db_handler
                     .player_database
                     .get_player_count_from(timestamp)
-                    .map_err(|_| Status::InternalServerError)
+                    .map_err(ApiError::from)
                     .map(|val| Json(val))
             } else {
                 db_handler
                     .player_database
                     .get_player_count()
-                    .map_err(|_| Status::InternalServerError)
+                    .map_err(ApiError::from)
                     .map(|val| Json(val))
             }
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
+
+#[utoipa::path(
+    tag = "Players",
+    params(
+        ("since" = Option<u64>, Query, description = "Unix time of the oldest player count"),
+        ("format" = Option<String>, Query, description = "`csv` (default) or `ndjson`"),
+    ),
+    responses(
+        (status = 200, description = "Recorded player counts, streamed", content((String = "text/csv"), (String = "application/x-ndjson"))),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:read"]))
+)]
+#[get("/api/v1/player-counts/export?<since>&<format>")]
+// MARK: Export player count
+pub async fn db_export_player_count(
+    _auth: ApiAuth<scopes::PlayersRead>,
+    _signed: SignedRequest,
+    app_data: &State<Arc<Mutex<Application>>>,
+    since: Option<u64>,
+    format: Option<&str>,
+) -> Result<ExportResponse<TextStream![String]>, ApiError> {
+    let format = ExportFormat::parse(format)?;
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            let player_db = db_handler_lock
+                .lock()
+                .await
+                .player_database
+                .open_reader()
+                .map_err(|_| ApiError::DatabaseUnavailable)?;
+            export_stream("player-counts", format, move |on_row| {
+                player_db.export_player_count(since, on_row)
+            })
+            .await
+        }
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
+#[utoipa::path(
+    tag = "Players",
+    request_body = DatabasePlayerCount,
+    responses(
+        (status = 200, description = "Success"),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["players:write"]))
+)]
 #[post(
-    "/db/player/count",
+    "/api/v1/player-counts",
     format = "application/json",
     data = "<parsed_data>"
 )]
 // MARK: Set player count
 pub async fn db_set_some_player_count(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PlayersWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
     app_data: &State<Arc<Mutex<Application>>>,
-    parsed_data: Json<DatabasePlayerCount>,
-) -> Result<(), Status> {
+    parsed_data: SignedJson<DatabasePlayerCount>,
+) -> Result<(), ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -246,8 +533,8 @@ pub async fn db_set_some_player_count(
             db_handler
                 .player_database
                 .set_player_count(parsed_data.0)
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
@@ -1,19 +1,38 @@
 use std::sync::Arc;
 
 use crate::application::application::Application;
+use crate::application::db::audit::structs::AuditActor;
+use crate::application::features::audit::{self, actions};
+use crate::application::features::events::EventKind;
+use crate::application::db::player::identity::PlayerIdentity;
 use crate::application::db::punishments::structs::DatabasePunishment;
-use crate::application::routes::http::DbAuthHeader;
-use rocket::http::Status;
+use crate::application::routes::errors::ApiError;
+use crate::application::routes::export::{export_stream, ExportFormat, ExportResponse};
+use crate::application::routes::messages::ApiErrorBody;
+use crate::application::routes::auth::{scopes, ApiAuth};
+use crate::application::routes::signing::SignedRequest;
+use crate::application::routes::validation::ip_param;
+use rocket::response::stream::TextStream;
 use rocket::serde::json::Json;
 use rocket::State;
 use tokio::sync::Mutex;
 
-#[get("/db/punishments")]
+#[utoipa::path(
+    tag = "Punishments",
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabasePunishment>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["punishments:read"]))
+)]
+#[get("/api/v1/punishments")]
 // MARK: Get all punishments
 pub async fn db_get_all_punishments(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PunishmentsRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
-) -> Result<Json<Vec<DatabasePunishment>>, Status> {
+) -> Result<Json<Vec<DatabasePunishment>>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -21,20 +40,67 @@ pub async fn db_get_all_punishments(
             db_handler
                 .punishment_database
                 .get_all_punishments()
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/punishments/get/<punishment_id>")]
+#[utoipa::path(
+    tag = "Punishments",
+    params(("format" = Option<String>, Query, description = "`csv` (default) or `ndjson`")),
+    responses(
+        (status = 200, description = "Every punishment, streamed", content((String = "text/csv"), (String = "application/x-ndjson"))),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["punishments:read"]))
+)]
+#[get("/api/v1/punishments/export?<format>")]
+// MARK: Export punishments
+pub async fn db_export_punishments(
+    _auth: ApiAuth<scopes::PunishmentsRead>,
+    _signed: SignedRequest,
+    app_data: &State<Arc<Mutex<Application>>>,
+    format: Option<&str>,
+) -> Result<ExportResponse<TextStream![String]>, ApiError> {
+    let format = ExportFormat::parse(format)?;
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            let punishment_db = db_handler_lock
+                .lock()
+                .await
+                .punishment_database
+                .open_reader()
+                .map_err(|_| ApiError::DatabaseUnavailable)?;
+            export_stream("punishments", format, move |on_row| {
+                punishment_db.export_punishments(on_row)
+            })
+            .await
+        }
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
+
+#[utoipa::path(
+    tag = "Punishments",
+    responses(
+        (status = 200, description = "Success", body = DatabasePunishment),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["punishments:read"]))
+)]
+#[get("/api/v1/punishments/<punishment_id>")]
 // MARK: Get punishment by it's ID
 pub async fn db_get_punishment_by_punishment_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PunishmentsRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     punishment_id: String,
-) -> Result<Json<DatabasePunishment>, Status> {
+) -> Result<Json<DatabasePunishment>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -42,20 +108,98 @@ pub async fn db_get_punishment_by_punishment_id(
             db_handler
                 .punishment_database
                 .get_punishment_by_punishment_id(punishment_id)
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
+
+#[utoipa::path(
+    tag = "Punishments",
+    responses(
+        (status = 200, description = "Revoked punishment", body = DatabasePunishment),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["punishments:write"]))
+)]
+#[delete("/api/v1/punishments/<punishment_id>")]
+// MARK: Revoke punishment
+/// Deletes the punishment and removes it from the player's `ban_ids`
+pub async fn db_revoke_punishment(
+    auth: ApiAuth<scopes::PunishmentsWrite>,
+    _signed: SignedRequest,
+    app_data: &State<Arc<Mutex<Application>>>,
+    punishment_id: String,
+) -> Result<Json<DatabasePunishment>, ApiError> {
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            let mut db_handler = db_handler_lock.lock().await;
+            let punishment = db_handler
+                .punishment_database
+                .get_punishment_by_punishment_id(punishment_id.clone())
+                .map_err(ApiError::from)?;
+            db_handler
+                .punishment_database
+                .delete_punishment(&punishment_id)
+                .map_err(ApiError::from)?;
+
+            if let Ok(mut player) = db_handler
+                .player_database
+                .get_player_by_id(punishment.player_id)
+            {
+                if let Some(ban_ids) = &mut player.ban_ids {
+                    ban_ids.retain(|ban_id| *ban_id != punishment_id);
+                }
+                db_handler
+                    .player_database
+                    .modify_player(punishment.player_id, player)
+                    .map_err(ApiError::Storage)?;
+            }
+            audit::record(
+                &db_handler,
+                &AuditActor::api_key(&auth.key.name),
+                actions::PUNISHMENT_REVOKE,
+                audit::target("punishment", &punishment.punishment_id),
+                &punishment,
+                (),
+            );
+
+            app.events.publish(
+                EventKind::PunishmentRevoked,
+                None,
+                serde_json::json!({
+                    "punishment_id": punishment.punishment_id,
+                    "player_id": punishment.player_id,
+                    "steam_id": punishment.steam_id,
+                    "punishment_type": punishment.punishment_type,
+                }),
+            );
+            Ok(Json(punishment))
+        }
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/punishments/get/id/<player_id>")]
+#[utoipa::path(
+    tag = "Punishments",
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabasePunishment>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["punishments:read"]))
+)]
+#[get("/api/v1/players/<player_id>/punishments")]
 // MARK: Get punishment by player_id
 pub async fn get_punishments_by_player_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PunishmentsRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     player_id: u64,
-) -> Result<Json<Vec<DatabasePunishment>>, Status> {
+) -> Result<Json<Vec<DatabasePunishment>>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -63,41 +207,66 @@ pub async fn get_punishments_by_player_id(
             db_handler
                 .punishment_database
                 .get_punishments_by_player_id(player_id)
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/punishments/get/steam/<steam_id>")]
+#[utoipa::path(
+    tag = "Punishments",
+    params(("steam_id" = PlayerIdentity, Path, description = "SCP:SL user ID")),
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabasePunishment>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["punishments:read"]))
+)]
+#[get("/api/v1/punishments/by-user/<steam_id>")]
 // MARK: Get punishment by steam_id
 pub async fn get_punishments_by_steam_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PunishmentsRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
-    steam_id: String,
-) -> Result<Json<Vec<DatabasePunishment>>, Status> {
+    steam_id: PlayerIdentity,
+) -> Result<Json<Vec<DatabasePunishment>>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
             let db_handler = db_handler_lock.lock().await;
             db_handler
                 .punishment_database
-                .get_punishments_by_steam_id(steam_id)
-                .map_err(|_| Status::InternalServerError)
+                .get_punishments_by_steam_id(&steam_id)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/punishments/get/ip/<ip>")]
+#[utoipa::path(
+    tag = "Punishments",
+    params(("ip" = String, Path, description = "IPv4 or IPv6 address, IPv4-mapped IPv6 addresses match IPv4")),
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabasePunishment>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["punishments:read"]))
+)]
+#[get("/api/v1/punishments/by-ip/<ip>")]
 // MARK: Get punishment by ip
 pub async fn get_punishments_by_ip(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::PunishmentsRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     ip: String,
-) -> Result<Json<Vec<DatabasePunishment>>, Status> {
+) -> Result<Json<Vec<DatabasePunishment>>, ApiError> {
+    // Punishments store IPs in canonical form, `::ffff:10.0.0.1` has to find `10.0.0.1`
+    let ip = ip_param("ip", ip)?;
+
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -105,9 +274,9 @@ pub async fn get_punishments_by_ip(
             db_handler
                 .punishment_database
                 .get_punishments_by_ip(ip)
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
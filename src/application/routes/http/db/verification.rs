@@ -1,23 +1,46 @@
+use chrono::{Duration, Utc};
 use rocket::{http::Status, serde::json::Json};
 use std::sync::Arc;
 
 use crate::application::{
     application::Application,
+    db::audit::structs::AuditActor,
+    db::player::identity::PlayerIdentity,
     db::player::structs::{
-        DatabaseModifyPlayerVerification, DatabasePlayerVerification, PlayerVerification,
+        DatabaseModifyPlayerVerification, DatabasePlayerVerification,
+        DatabaseVerificationReview, PlayerVerification,
+    },
+    features::audit::{self, actions},
+    features::verification::{on_verification_changed, review},
+    routes::{
+        auth::{scopes, ApiAuth},
+        errors::ApiError,
+        idempotency::Idempotent,
+        messages::ApiErrorBody,
+        signing::{SignedJson, SignedRequest},
+        validation::{discord_id_param, validated, FieldErrors, Validate, MAX_CODE_LENGTH},
     },
-    routes::http::DbAuthHeader,
 };
 use rocket::State;
 use tokio::sync::Mutex;
 
-#[get("/db/player/verification/get/id/<player_id>")]
+#[utoipa::path(
+    tag = "Verification",
+    responses(
+        (status = 200, description = "Success", body = DatabasePlayerVerification),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:read"]))
+)]
+#[get("/api/v1/players/<player_id>/verification")]
 // MARK: Get player verification by player_id
 pub async fn db_get_player_verification_by_player_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::VerificationRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     player_id: u64,
-) -> Result<Json<DatabasePlayerVerification>, Status> {
+) -> Result<Json<DatabasePlayerVerification>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -25,47 +48,70 @@ pub async fn db_get_player_verification_by_player_id(
             db_handler
                 .player_database
                 .get_player_verification(player_id)
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)
                 .map(|val| Json(val))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/player/verification/get/steam/<steam_id>")]
+#[utoipa::path(
+    tag = "Verification",
+    params(("steam_id" = PlayerIdentity, Path, description = "SCP:SL user ID")),
+    responses(
+        (status = 200, description = "Success", body = DatabasePlayerVerification),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:read"]))
+)]
+#[get("/api/v1/verifications/by-user/<steam_id>")]
 // MARK: Get player verification by steam_id
 pub async fn db_get_player_verification_by_steam_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::VerificationRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
-    steam_id: String,
-) -> Result<Json<DatabasePlayerVerification>, Status> {
+    steam_id: PlayerIdentity,
+) -> Result<Json<DatabasePlayerVerification>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
             let db_handler = db_handler_lock.lock().await;
             let players = db_handler
                 .player_database
-                .get_players_by_steam(&steam_id)
-                .map_err(|_| Status::InternalServerError)?;
+                .get_players_by_identity(&steam_id)
+                .map_err(ApiError::from)?;
 
             if players.is_empty() {
-                return Err(Status::NotFound);
+                return Err(ApiError::not_found("Player"));
             }
 
             let verification = DatabasePlayerVerification::from(players[0].clone());
             Ok(Json(verification))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/player/verification/get/discord/<discord_id>")]
+#[utoipa::path(
+    tag = "Verification",
+    params(("discord_id" = String, Path, description = "Discord user ID of 17 to 20 digits")),
+    responses(
+        (status = 200, description = "Success", body = DatabasePlayerVerification),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:read"]))
+)]
+#[get("/api/v1/verifications/by-discord/<discord_id>")]
 // MARK: Get player verification by discord_id
 pub async fn db_get_player_verification_by_discord_id(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::VerificationRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     discord_id: String,
-) -> Result<Json<DatabasePlayerVerification>, Status> {
+) -> Result<Json<DatabasePlayerVerification>, ApiError> {
+    let discord_id = discord_id_param("discord_id", &discord_id)?;
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -73,26 +119,36 @@ pub async fn db_get_player_verification_by_discord_id(
             let players = db_handler
                 .player_database
                 .get_players_by_discord(&discord_id)
-                .map_err(|_| Status::InternalServerError)?;
+                .map_err(ApiError::from)?;
 
             if players.is_empty() {
-                return Err(Status::NotFound);
+                return Err(ApiError::not_found("Player"));
             }
 
             let verification = DatabasePlayerVerification::from(players[0].clone());
             Ok(Json(verification))
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
-#[get("/db/player/verification/get/code/<code>")]
+#[utoipa::path(
+    tag = "Verification",
+    responses(
+        (status = 200, description = "Success", body = DatabasePlayerVerification),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:read"]))
+)]
+#[get("/api/v1/verifications/by-code/<code>")]
 // MARK: Get player verification by code
 pub async fn db_get_player_verification_by_code(
-    _auth_header: DbAuthHeader,
+    _auth: ApiAuth<scopes::VerificationRead>,
+    _signed: SignedRequest,
     app_data: &State<Arc<Mutex<Application>>>,
     code: String,
-) -> Result<Json<DatabasePlayerVerification>, Status> {
+) -> Result<Json<DatabasePlayerVerification>, ApiError> {
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
@@ -100,10 +156,10 @@ pub async fn db_get_player_verification_by_code(
             let players = db_handler
                 .player_database
                 .get_all_players()
-                .map_err(|_| Status::InternalServerError)?;
+                .map_err(ApiError::from)?;
 
             if players.is_empty() {
-                return Err(Status::NotFound);
+                return Err(ApiError::not_found("Player"));
             }
 
             let output = players
@@ -119,127 +175,298 @@ pub async fn db_get_player_verification_by_code(
 
             match output {
                 Some(data) => Ok(Json(data)),
-                None => Err(Status::NotFound),
+                None => Err(ApiError::not_found("Verification")),
             }
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
+impl Validate for DatabaseModifyPlayerVerification {
+    fn validate(&mut self, fields: &mut FieldErrors) {
+        if let Some(discord_id) = &mut self.discord_id {
+            fields.discord_id("discord_id", discord_id);
+        }
+        fields.optional_text("code", &mut self.code, MAX_CODE_LENGTH);
+    }
+}
+
+#[utoipa::path(
+    tag = "Verification",
+    request_body = DatabaseModifyPlayerVerification,
+    responses(
+        (status = 200, description = "Success"),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:write"]))
+)]
 #[post(
-    "/db/player/verification/add",
+    "/api/v1/verifications",
     format = "application/json",
     data = "<parsed_data>"
 )]
 // MARK: Add player verification
 pub async fn db_add_player_verification(
-    _auth_header: DbAuthHeader,
+    auth: ApiAuth<scopes::VerificationWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
     app_data: &State<Arc<Mutex<Application>>>,
-    parsed_data: Json<DatabaseModifyPlayerVerification>,
-) -> Result<(), Status> {
-    let _verification_key = match &parsed_data.0.code {
+    parsed_data: SignedJson<DatabaseModifyPlayerVerification>,
+) -> Result<(), ApiError> {
+    let body = validated(parsed_data.0)?;
+    let _verification_key = match &body.code {
         Some(val) => val,
-        None => return Err(Status::BadRequest),
+        None => return Err(ApiError::missing_field("code")),
     };
 
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
             let mut db_handler = db_handler_lock.lock().await;
-            db_handler
+            let player = db_handler
+                .player_database
+                .get_players_by_identity(&body.steam_id)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| ApiError::not_found("Player"))?;
+            if player.verified_status.unwrap_or(PlayerVerification::None) != PlayerVerification::None {
+                return Err(ApiError::Conflict(
+                    "Player is already verified or has a pending verification".to_string(),
+                ));
+            }
+            let player_id = db_handler
                 .player_database
-                .add_player_verification(parsed_data.0)
-                .map_err(|_| Status::InternalServerError)
+                .add_player_verification(body)
+                .map_err(ApiError::from)?;
+            audit::record(
+                &db_handler,
+                &AuditActor::api_key(&auth.key.name),
+                actions::VERIFICATION_ADD,
+                audit::target("player", player_id),
+                (),
+                db_handler.player_database.get_player_verification(player_id).ok(),
+            );
+            on_verification_changed(app_data.inner(), player_id);
+            Ok(())
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
+#[utoipa::path(
+    tag = "Verification",
+    request_body = DatabaseModifyPlayerVerification,
+    responses(
+        (status = 200, description = "Player verified"),
+        (status = 202, description = "Verification put under staff review"),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:write"]))
+)]
 #[post(
-    "/db/player/verification/update",
+    "/api/v1/verifications/verify",
     format = "application/json",
     data = "<parsed_data>"
 )]
 // MARK: Verify player
+/// Returns [`Status::Accepted`] when the verification was put under staff review
 pub async fn db_update_player_verification(
-    _auth_header: DbAuthHeader,
+    auth: ApiAuth<scopes::VerificationWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
     app_data: &State<Arc<Mutex<Application>>>,
-    parsed_data: Json<DatabaseModifyPlayerVerification>,
-) -> Result<(), Status> {
+    parsed_data: SignedJson<DatabaseModifyPlayerVerification>,
+) -> Result<Status, ApiError> {
+    let body = validated(parsed_data.0)?;
     // Check for discord_id in the request
-    let discord_id = match &parsed_data.0.discord_id {
-        Some(val) => val,
-        None => return Err(Status::BadRequest),
+    let discord_id = match &body.discord_id {
+        Some(val) => val.clone(),
+        None => return Err(ApiError::missing_field("discord_id")),
     };
 
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
             let mut db_handler = db_handler_lock.lock().await;
-            let existing_verified_player = match db_handler
+            let existing_verified_player = db_handler
                 .player_database
-                .get_player_by_discord_or_steam(discord_id, &parsed_data.0.steam_id)
-            {
-                Ok(Some(player)) => player,
-                Ok(None) => return Err(Status::NotFound),
-                Err(_) => return Err(Status::InternalServerError),
-            };
+                .get_player_by_discord_or_steam(&discord_id, &body.steam_id)
+                .map_err(ApiError::from)?
+                .ok_or_else(|| ApiError::not_found("Player"))?;
             if existing_verified_player.is_verified() {
-                return Err(Status::AlreadyReported);
+                return Err(ApiError::Conflict("Player is already verified".to_string()));
             }
             if existing_verified_player.is_verification_banned() {
-                return Err(Status::Forbidden);
+                return Err(ApiError::Forbidden(
+                    "Player verification is banned or under review".to_string(),
+                ));
             }
 
-            // Check if player has already verification, with different account
-            let players = db_handler
+            let player_id = existing_verified_player.player_id;
+            let before = DatabasePlayerVerification::from(existing_verified_player.clone());
+            let actor = AuditActor::api_key(&auth.key.name);
+            let verification_cfg = app
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.verification.as_ref());
+
+            // Check limit of accounts linked to the discord user
+            let links = db_handler
                 .player_database
-                .get_players_by_discord(discord_id)
-                .map_err(|_| Status::NotModified)?;
+                .get_account_links(&discord_id)
+                .map_err(ApiError::from)?;
+            if let Some(max_links) = verification_cfg.and_then(|cfg| cfg.max_linked_accounts) {
+                if links.len() as u32 >= max_links {
+                    return Err(ApiError::Forbidden(
+                        "Discord user reached the limit of linked accounts".to_string(),
+                    ));
+                }
+            }
 
-            if players.len() > 0 {
-                return Err(Status::Forbidden);
+            // Check for recent unlinks, so accounts can't be traded
+            if let Some(cooldown) = verification_cfg.and_then(|cfg| cfg.unlink_cooldown_hours) {
+                let last_unlink = db_handler
+                    .player_database
+                    .get_last_foreign_unlink(player_id, &discord_id)
+                    .map_err(ApiError::from)?;
+                if last_unlink.is_some_and(|date| Utc::now() - date < Duration::hours(cooldown as i64))
+                {
+                    return Err(ApiError::Locked(
+                        "Player was recently unlinked from another Discord user".to_string(),
+                    ));
+                }
             }
 
-            db_handler
+            // Suspicious verifications are put under review instead
+            let review_reasons = match verification_cfg.and_then(|cfg| cfg.review.as_ref()) {
+                Some(review_cfg) => review::find_suspicious_reasons(
+                    &db_handler,
+                    &existing_verified_player,
+                    &discord_id,
+                    review_cfg,
+                ),
+                None => vec![],
+            };
+            if review_reasons.is_empty() {
+                db_handler
+                    .player_database
+                    .link_account(player_id, &discord_id)
+                    .map_err(ApiError::from)?;
+                audit::record(
+                    &db_handler,
+                    &actor,
+                    actions::VERIFICATION_VERIFY,
+                    audit::target("player", player_id),
+                    &before,
+                    db_handler.player_database.get_player_verification(player_id).ok(),
+                );
+                on_verification_changed(app_data.inner(), player_id);
+                return Ok(Status::Ok);
+            }
+
+            // Suspended player always has a review to resolve it
+            let review_id = db_handler
                 .player_database
-                .set_player_verification(
-                    existing_verified_player.player_id,
-                    PlayerVerification::Success,
-                    parsed_data.0.discord_id,
-                    None,
-                )
-                .map_err(|_| Status::BadRequest)
+                .suspend_for_review(player_id, &discord_id, &review_reasons)
+                .map_err(ApiError::from)?;
+            audit::record(
+                &db_handler,
+                &actor,
+                actions::VERIFICATION_VERIFY,
+                audit::target("player", player_id),
+                &before,
+                db_handler.player_database.get_player_verification(player_id).ok(),
+            );
+            on_verification_changed(app_data.inner(), player_id);
+            review::queue_review(app_data.inner(), review_id);
+            Ok(Status::Accepted)
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
 
+#[utoipa::path(
+    tag = "Verification",
+    request_body = DatabaseModifyPlayerVerification,
+    responses(
+        (status = 200, description = "Success"),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:write"]))
+)]
 #[post(
-    "/db/player/verification/set",
+    "/api/v1/verifications/status",
     format = "application/json",
     data = "<parsed_data>"
 )]
 // MARK: Set player verification
 pub async fn dn_modify_player_verification(
-    _auth_header: DbAuthHeader,
+    auth: ApiAuth<scopes::VerificationWrite>,
+    _signed: SignedRequest,
+    _idempotent: Idempotent,
     app_data: &State<Arc<Mutex<Application>>>,
-    parsed_data: Json<DatabaseModifyPlayerVerification>,
-) -> Result<(), Status> {
+    parsed_data: SignedJson<DatabaseModifyPlayerVerification>,
+) -> Result<(), ApiError> {
+    let body = validated(parsed_data.0)?;
     let app = app_data.lock().await;
     match app.databases.as_ref() {
         Some(db_handler_lock) => {
             let mut db_handler = db_handler_lock.lock().await;
+            let player_id = body.player_id;
+            let before = db_handler.player_database.get_player_verification(player_id).ok();
             db_handler
                 .player_database
                 .set_player_verification(
-                    parsed_data.0.player_id,
-                    parsed_data.0.verified_status,
-                    parsed_data.0.discord_id,
-                    parsed_data.0.code,
+                    player_id,
+                    body.verified_status,
+                    body.discord_id,
+                    body.code,
                 )
-                .map_err(|_| Status::InternalServerError)
+                .map_err(ApiError::from)?;
+            audit::record(
+                &db_handler,
+                &AuditActor::api_key(&auth.key.name),
+                actions::VERIFICATION_SET,
+                audit::target("player", player_id),
+                &before,
+                db_handler.player_database.get_player_verification(player_id).ok(),
+            );
+            on_verification_changed(app_data.inner(), player_id);
+            Ok(())
+        }
+        None => Err(ApiError::DatabaseUnavailable),
+    }
+}
+
+#[utoipa::path(
+    tag = "Verification",
+    responses(
+        (status = 200, description = "Success", body = Vec<DatabaseVerificationReview>),
+        (status = "4XX", response = ApiErrorBody),
+        (status = "5XX", response = ApiErrorBody),
+    ),
+    security(("api_key" = ["verification:read"]))
+)]
+#[get("/api/v1/verifications/reviews")]
+// MARK: Get pending verification reviews
+pub async fn db_get_pending_verification_reviews(
+    _auth: ApiAuth<scopes::VerificationRead>,
+    _signed: SignedRequest,
+    app_data: &State<Arc<Mutex<Application>>>,
+) -> Result<Json<Vec<DatabaseVerificationReview>>, ApiError> {
+    let app = app_data.lock().await;
+    match app.databases.as_ref() {
+        Some(db_handler_lock) => {
+            let db_handler = db_handler_lock.lock().await;
+            db_handler
+                .player_database
+                .get_pending_verification_reviews()
+                .map_err(ApiError::from)
+                .map(Json)
         }
-        None => Err(Status::FailedDependency),
+        None => Err(ApiError::DatabaseUnavailable),
     }
 }
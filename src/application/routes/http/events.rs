@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use rocket::{
+  request::{self, FromRequest},
+  response::stream::{Event, EventStream},
+  tokio::{select, sync::broadcast::error::RecvError},
+  Request, Shutdown, State,
+};
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::Application,
+  features::events::{DistrictEvent, EventKind},
+  routes::{
+    auth::{scopes, ApiAuth},
+    errors::ApiError,
+    messages::ApiErrorBody,
+  },
+};
+
+/// `Last-Event-ID` header, sent by `EventSource` when it reconnects
+pub struct LastEventId(Option<u64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+  type Error = std::convert::Infallible;
+
+  async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+    request::Outcome::Success(LastEventId(
+      request
+        .headers()
+        .get_one("Last-Event-ID")
+        .and_then(|val| val.parse().ok()),
+    ))
+  }
+}
+
+/// Events a subscriber wants to receive
+struct EventFilter {
+  server_id: Option<u64>,
+  kinds: Option<Vec<EventKind>>,
+  /// Servers the API key is bound to
+  allowed_servers: Option<Vec<u64>>,
+}
+
+impl EventFilter {
+  fn matches(&self, event: &DistrictEvent) -> bool {
+    if let Some(kinds) = &self.kinds {
+      if !kinds.contains(&event.kind) {
+        return false;
+      }
+    }
+    if let Some(server_id) = self.server_id {
+      if event.server_id != Some(server_id) {
+        return false;
+      }
+    }
+    // Events of no server (verification changes, ...) carry Discord and Steam IDs of any player,
+    // keys bound to servers only get the events of their servers
+    match (&self.allowed_servers, event.server_id) {
+      (Some(allowed), Some(server_id)) => allowed.contains(&server_id),
+      (Some(_), None) => false,
+      (None, _) => true,
+    }
+  }
+}
+
+fn to_sse(event: &DistrictEvent) -> Event {
+  Event::json(event)
+    .id(event.id.to_string())
+    .event(event.kind.as_str())
+}
+
+#[utoipa::path(
+  tag = "Events",
+  params(
+    ("server_id" = Option<u64>, Query, description = "Only events of this server"),
+    ("types" = Option<String>, Query, description = "Comma separated event kinds (server_status, player_join, punishment_created, punishment_revoked, verification_changed, server_up, server_down, log)"),
+    ("Last-Event-ID" = Option<u64>, Header, description = "Resume after this event, if it is still buffered"),
+  ),
+  responses(
+    (status = 200, description = "Stream of events, the SSE `event` field is the event kind. Keys bound to servers only get the events of their servers, not events of no server (`verification_changed`, ...)", content_type = "text/event-stream", body = DistrictEvent),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["events:read"]))
+)]
+#[get("/api/v1/events?<server_id>&<types>")]
+// MARK: Event stream
+pub async fn events_stream(
+  auth: ApiAuth<scopes::EventsRead>,
+  app_data: &State<Arc<Mutex<Application>>>,
+  last_event_id: LastEventId,
+  server_id: Option<u64>,
+  types: Option<&str>,
+  mut shutdown: Shutdown,
+) -> Result<EventStream![], ApiError> {
+  let kinds = match types {
+    Some(types) => Some(
+      types
+        .split(',')
+        .map(|kind| {
+          EventKind::from_name(kind.trim()).ok_or_else(|| {
+            ApiError::Validation(
+              format!("Unknown event type '{}'", kind.trim()),
+              Some(serde_json::json!({ "field": "types" })),
+            )
+          })
+        })
+        .collect::<Result<Vec<_>, _>>()?,
+    ),
+    None => None,
+  };
+  if let Some(server_id) = server_id {
+    if !auth.key.allows_server(server_id) {
+      return Err(ApiError::Forbidden(format!(
+        "API key is not allowed to act for server {}",
+        server_id
+      )));
+    }
+  }
+  let filter = EventFilter {
+    server_id,
+    kinds,
+    allowed_servers: auth.key.servers.clone(),
+  };
+
+  let events = app_data.lock().await.events.clone();
+  let (backlog, mut receiver) = events.subscribe(last_event_id.0);
+
+  Ok(EventStream! {
+    for event in backlog.iter().filter(|event| filter.matches(event)) {
+      yield to_sse(event);
+    }
+    loop {
+      let event = select! {
+        received = receiver.recv() => match received {
+          Ok(event) => event,
+          Err(RecvError::Closed) => break,
+          // Slow subscribers skip the events they missed
+          Err(RecvError::Lagged(_)) => continue,
+        },
+        _ = &mut shutdown => break,
+      };
+      if filter.matches(&event) {
+        yield to_sse(&event);
+      }
+    }
+  })
+}
@@ -0,0 +1,236 @@
+use std::{
+  sync::{atomic::Ordering, Arc},
+  time::Duration,
+};
+
+use chrono::Utc;
+use rocket::{http::Status, serde::json::Json, State};
+use serde::Serialize;
+use tokio::{sync::Mutex, time::timeout};
+use utoipa::ToSchema;
+
+use crate::application::application::Application;
+
+/// The timer loop ticks every second, a longer pause means it's stuck or gone
+const TIMER_MAX_TICK_AGE_SECS: i64 = 30;
+/// How long a check waits for a lock before reporting the subsystem as down
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, ToSchema)]
+pub struct TimerHealth {
+  pub ok: bool,
+  /// Unix time of the last tick
+  pub last_tick: Option<i64>,
+  pub last_tick_age_secs: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DatabaseHealth {
+  pub name: String,
+  pub ok: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BotHealth {
+  /// `main` or the name of the server the bot belongs to
+  pub name: String,
+  pub server_id: Option<u64>,
+  /// Bot received its Discord context and can send messages
+  pub ready: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ServerHealth {
+  pub id: u64,
+  pub name: String,
+  pub open: bool,
+  /// Seconds since the server last sent its status, not set when it's not connected
+  pub last_heard_age_secs: Option<i64>,
+}
+
+/// # Health report
+/// `status` is `ok` when every critical subsystem (timer loop, databases, bots) works,
+/// game servers going quiet is reported, but doesn't make the report fail
+#[derive(Serialize, ToSchema)]
+pub struct HealthReport {
+  /// `ok` or `unavailable`
+  pub status: String,
+  pub timer: TimerHealth,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub databases: Vec<DatabaseHealth>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub bots: Vec<BotHealth>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub servers: Vec<ServerHealth>,
+  /// Why the report failed
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub problems: Vec<String>,
+}
+
+impl HealthReport {
+  fn new(timer: TimerHealth) -> Self {
+    let mut report = HealthReport {
+      status: String::new(),
+      timer,
+      databases: vec![],
+      bots: vec![],
+      servers: vec![],
+      problems: vec![],
+    };
+    if !report.timer.ok {
+      report
+        .problems
+        .push("timer loop is not ticking".to_string());
+    }
+    report
+  }
+
+  fn respond(mut self) -> (Status, Json<HealthReport>) {
+    let status = if self.problems.is_empty() {
+      self.status = "ok".to_string();
+      Status::Ok
+    } else {
+      self.status = "unavailable".to_string();
+      Status::ServiceUnavailable
+    };
+    (status, Json(self))
+  }
+}
+
+fn timer_health(app: &Application) -> TimerHealth {
+  let last_tick = app.timer_last_tick.load(Ordering::Relaxed);
+  if last_tick == 0 {
+    return TimerHealth {
+      ok: false,
+      last_tick: None,
+      last_tick_age_secs: None,
+    };
+  }
+  let age = Utc::now().timestamp() - last_tick;
+  TimerHealth {
+    ok: age <= TIMER_MAX_TICK_AGE_SECS,
+    last_tick: Some(last_tick),
+    last_tick_age_secs: Some(age),
+  }
+}
+
+#[utoipa::path(
+  tag = "Meta",
+  responses(
+    (status = 200, description = "Process is running", body = HealthReport),
+    (status = 503, description = "Application is locked up or the timer loop stopped", body = HealthReport),
+  )
+)]
+#[get("/health/live")]
+// MARK: Liveness
+pub async fn health_live(
+  app_data: &State<Arc<Mutex<Application>>>,
+) -> (Status, Json<HealthReport>) {
+  let app = match timeout(LOCK_TIMEOUT, app_data.lock()).await {
+    Ok(val) => val,
+    Err(_) => {
+      let mut report = HealthReport::new(TimerHealth {
+        ok: false,
+        last_tick: None,
+        last_tick_age_secs: None,
+      });
+      report.problems = vec!["application is locked".to_string()];
+      return report.respond();
+    }
+  };
+  HealthReport::new(timer_health(&app)).respond()
+}
+
+#[utoipa::path(
+  tag = "Meta",
+  responses(
+    (status = 200, description = "Every critical subsystem works", body = HealthReport),
+    (status = 503, description = "A critical subsystem is down, see `problems`", body = HealthReport),
+  )
+)]
+#[get("/health/ready")]
+// MARK: Readiness
+pub async fn health_ready(
+  app_data: &State<Arc<Mutex<Application>>>,
+) -> (Status, Json<HealthReport>) {
+  let (timer, databases, main_bot, servers) = match timeout(LOCK_TIMEOUT, app_data.lock()).await {
+    Ok(app) => (
+      timer_health(&app),
+      app.databases.clone(),
+      app.main_bot.clone(),
+      app.servers.clone(),
+    ),
+    Err(_) => return health_live(app_data).await,
+  };
+  let mut report = HealthReport::new(timer);
+
+  match databases {
+    Some(db_handler_lock) => match timeout(LOCK_TIMEOUT, db_handler_lock.lock()).await {
+      Ok(db_handler) => {
+        for (name, result) in db_handler.ping() {
+          if let Err(e) = &result {
+            report
+              .problems
+              .push(format!("database '{}' doesn't respond: {}", name, e));
+          }
+          report.databases.push(DatabaseHealth {
+            name: name.to_string(),
+            ok: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+          });
+        }
+      }
+      Err(_) => report.problems.push("databases are locked".to_string()),
+    },
+    None => report.problems.push("databases are not set up".to_string()),
+  }
+
+  match main_bot {
+    Some(bot) => {
+      let ready = bot.ctx_manager.get_ctx().await.is_some();
+      if !ready {
+        report.problems.push("main bot is not ready".to_string());
+      }
+      report.bots.push(BotHealth {
+        name: "main".to_string(),
+        server_id: None,
+        ready,
+      });
+    }
+    None => report.problems.push("main bot is not set up".to_string()),
+  }
+
+  let now = Utc::now().timestamp();
+  for server_lock in servers {
+    let server = match timeout(LOCK_TIMEOUT, server_lock.lock()).await {
+      Ok(val) => val,
+      Err(_) => {
+        report.problems.push("a server is locked".to_string());
+        continue;
+      }
+    };
+    if let Some(bot) = server.bot.as_ref() {
+      let ready = bot.ctx_manager.get_ctx().await.is_some();
+      if !ready {
+        report
+          .problems
+          .push(format!("bot of server '{}' is not ready", server.name));
+      }
+      report.bots.push(BotHealth {
+        name: server.name.clone(),
+        server_id: Some(server.id),
+        ready,
+      });
+    }
+    report.servers.push(ServerHealth {
+      id: server.id,
+      name: server.name.clone(),
+      open: server.status.open,
+      last_heard_age_secs: server.status.last_heard.map(|last_heard| now - last_heard),
+    });
+  }
+
+  report.respond()
+}
@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use rocket::{serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::application::{
+  application::Application,
+  config::auth_config::ApiScope,
+  db::{audit::structs::AuditActor, auth::structs::DatabaseApiKeyUsage},
+  features::{
+    audit::{self, actions},
+    rate_limit::RateLimitCounter,
+  },
+  routes::{
+    auth::{scopes, ApiAuth},
+    errors::ApiError,
+    messages::ApiErrorBody,
+  },
+  utils::api_key,
+};
+
+/// How long the previous key of an identity keeps working after a new one is minted
+const DEFAULT_ROTATION_GRACE_HOURS: u32 = 24;
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiKeyInfo {
+  pub name: String,
+  /// Id of a key minted through the API, not set for keys from the config
+  pub minted_id: Option<i64>,
+  pub scopes: Vec<ApiScope>,
+  pub servers: Option<Vec<u64>>,
+  pub expires_at: Option<DateTime<Utc>>,
+  /// Whether this is the identity the request was made with
+  pub current: bool,
+  pub usage: Option<DatabaseApiKeyUsage>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ApiKeyMint {
+  /// Identity of the key, minting a key for an existing identity rotates it
+  pub name: String,
+  /// Required for a new identity, rotated keys keep the scopes of the previous key when not set
+  pub scopes: Option<Vec<ApiScope>>,
+  /// Rotated keys keep the servers of the previous key when not set
+  pub servers: Option<Vec<u64>>,
+  pub expires_at: Option<DateTime<Utc>>,
+  /// Hours the previous key keeps working, 24 by default
+  pub grace_hours: Option<u32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MintedApiKey {
+  pub id: i64,
+  pub name: String,
+  /// The secret, it is not stored and can't be shown again
+  pub key: String,
+  pub scopes: Vec<ApiScope>,
+  pub servers: Option<Vec<u64>>,
+  pub expires_at: Option<DateTime<Utc>>,
+  /// Until when the previous key of the identity keeps working, if there was one
+  pub previous_key_expires_at: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+  tag = "Auth",
+  responses(
+    (status = 200, description = "Configured and minted API keys with their usage", body = Vec<ApiKeyInfo>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["admin"]))
+)]
+#[get("/api/v1/auth/keys")]
+// MARK: Get api keys
+pub async fn auth_get_keys(
+  auth: ApiAuth<scopes::Admin>,
+  app_data: &State<Arc<Mutex<Application>>>,
+) -> Result<Json<Vec<ApiKeyInfo>>, ApiError> {
+  let app = app_data.lock().await;
+  let keys = app
+    .config
+    .as_ref()
+    .map(|cfg| cfg.auth.api_keys())
+    .unwrap_or_default();
+
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => {
+      let db_handler = db_handler_lock.lock().await;
+      let mut infos = keys
+        .into_iter()
+        .filter(|key| !key.is_expired())
+        .map(|key| {
+          Ok(ApiKeyInfo {
+            usage: db_handler.auth_database.get_key_usage(&key.name)?,
+            current: key.name == auth.key.name,
+            name: key.name,
+            minted_id: None,
+            scopes: key.scopes,
+            servers: key.servers,
+            expires_at: key.expires_at,
+          })
+        })
+        .collect::<Result<Vec<_>, rusqlite::Error>>()
+        .map_err(ApiError::from)?;
+
+      for key in db_handler
+        .auth_database
+        .get_active_api_keys()
+        .map_err(ApiError::from)?
+        .into_iter()
+        .filter(|key| !key.is_expired())
+      {
+        infos.push(ApiKeyInfo {
+          usage: db_handler
+            .auth_database
+            .get_key_usage(&key.name)
+            .map_err(ApiError::from)?,
+          current: key.name == auth.key.name,
+          name: key.name,
+          minted_id: Some(key.id),
+          scopes: key.scopes,
+          servers: key.servers,
+          expires_at: key.expires_at,
+        });
+      }
+      Ok(Json(infos))
+    }
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Auth",
+  request_body = ApiKeyMint,
+  responses(
+    (status = 200, description = "New key, the secret is shown only this once", body = MintedApiKey),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["admin"]))
+)]
+#[post(
+  "/api/v1/auth/keys",
+  format = "application/json",
+  data = "<parsed_data>"
+)]
+// MARK: Mint api key
+pub async fn auth_mint_key(
+  auth: ApiAuth<scopes::Admin>,
+  app_data: &State<Arc<Mutex<Application>>>,
+  parsed_data: Json<ApiKeyMint>,
+) -> Result<Json<MintedApiKey>, ApiError> {
+  let app = app_data.lock().await;
+  let request = parsed_data.into_inner();
+
+  if request.name.trim().is_empty() {
+    return Err(ApiError::missing_field("name"));
+  }
+  let in_config = app.config.as_ref().is_some_and(|cfg| {
+    cfg
+      .auth
+      .api_keys()
+      .iter()
+      .any(|key| key.name == request.name)
+  });
+  if in_config {
+    return Err(ApiError::Conflict(format!(
+      "Key '{}' is defined in the config, rotate it there",
+      request.name
+    )));
+  }
+
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => {
+      let mut db_handler = db_handler_lock.lock().await;
+      let previous = db_handler
+        .auth_database
+        .get_active_api_keys()
+        .map_err(ApiError::from)?
+        .into_iter()
+        .find(|key| key.name == request.name && !key.is_expired());
+
+      let scopes = match (request.scopes, &previous) {
+        (Some(scopes), _) => scopes,
+        (None, Some(previous)) => previous.scopes.clone(),
+        (None, None) => return Err(ApiError::missing_field("scopes")),
+      };
+      let servers = match (request.servers, &previous) {
+        (Some(servers), _) => Some(servers),
+        (None, Some(previous)) => previous.servers.clone(),
+        (None, None) => None,
+      };
+      let grace_hours = request.grace_hours.unwrap_or(DEFAULT_ROTATION_GRACE_HOURS);
+      let previous_until = Utc::now() + Duration::hours(grace_hours as i64);
+
+      let secret = api_key::generate_key();
+      let key = db_handler
+        .auth_database
+        .rotate_api_key(
+          &request.name,
+          &api_key::hash_key(&secret),
+          &scopes,
+          &servers,
+          request.expires_at,
+          previous_until,
+        )
+        .map_err(ApiError::from)?;
+      audit::record(
+        &db_handler,
+        &AuditActor::api_key(&auth.key.name),
+        actions::API_KEY_MINT,
+        audit::target("api_key", &key.name),
+        &previous,
+        &key,
+      );
+
+      Ok(Json(MintedApiKey {
+        id: key.id,
+        name: key.name,
+        key: secret,
+        scopes: key.scopes,
+        servers: key.servers,
+        expires_at: key.expires_at,
+        previous_key_expires_at: previous.map(|previous| {
+          previous
+            .expires_at
+            .map_or(previous_until, |val| val.min(previous_until))
+        }),
+      }))
+    }
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Auth",
+  responses(
+    (status = 200, description = "Requests allowed and rejected by every rate limit bucket", body = Vec<RateLimitCounter>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["admin"]))
+)]
+#[get("/api/v1/auth/rate-limits")]
+// MARK: Get rate limits
+pub async fn auth_get_rate_limits(
+  _auth: ApiAuth<scopes::Admin>,
+  app_data: &State<Arc<Mutex<Application>>>,
+) -> Json<Vec<RateLimitCounter>> {
+  let app = app_data.lock().await;
+  Json(app.rate_limiter.counters())
+}
@@ -1,44 +1,64 @@
 use std::{collections::HashMap, sync::Arc};
 
+use chrono::Utc;
 use rocket::{serde::json::Json, State};
+use serde::Deserialize;
 use serde_json::Value;
 use tokio::sync::Mutex;
+use utoipa::ToSchema;
 
 use crate::application::{
   self,
   application::Application,
-  features::logs::JsonValueNotObject,
-  routes::messages::{http_response_message_200, http_response_message_500},
+  features::{events::EventKind, logs::JsonValueNotObject, uptime},
+  routes::{
+    batch::{check_batch_size, BatchResponse},
+    errors::ApiError,
+    messages::{http_response_message_200, ApiErrorBody, ApiMessageBody},
+  },
   server::status::DistrictServerStatus,
 };
 
-use super::LogAuthHeader;
+use crate::application::routes::auth::{scopes, ApiAuth};
+use crate::application::routes::idempotency::Idempotent;
+use crate::application::routes::signing::{SignedJson, SignedRequest};
 
 /// # Handles POST requests to set Server status (players online, tps, etc...)
 ///
 /// ## Arguments
 ///
 /// * `status_data` - JSON data containing [`crate::application::server::status::DistrictServerStatus`] information.
+#[utoipa::path(
+  tag = "Logs",
+  request_body = DistrictServerStatus,
+  responses(
+    (status = 200, response = ApiMessageBody),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["servers:write"]))
+)]
 #[post(
-  "/server/<server_id>/status",
+  "/api/v1/servers/<server_id>/status",
   format = "application/json",
   data = "<status_data>"
 )]
 pub async fn log_server_status(
-  _auth_header: LogAuthHeader,
+  _auth: ApiAuth<scopes::ServersWrite>,
+  _signed: SignedRequest,
+  _idempotent: Idempotent,
   app_data: &State<Arc<Mutex<Application>>>,
   server_id: u64,
-  status_data: Json<DistrictServerStatus>,
-) -> Json<Value> {
+  mut status_data: SignedJson<DistrictServerStatus>,
+) -> Result<Json<Value>, ApiError> {
+  let now = Utc::now().timestamp();
+  status_data.0.last_heard = Some(now);
   let app = app_data.lock().await;
   if let Some(db_lock) = &app.databases {
     let mut db_handler = db_lock.lock().await;
-    if let Err(player_count_error) = db_handler
+    db_handler
       .player_database
-      .set_player_count_auto(status_data.0.player_count.clone().into())
-    {
-      return http_response_message_500(Some(player_count_error.to_string()));
-    }
+      .set_player_count_auto(status_data.0.player_count.clone().into())?;
     if let Some(player_list) = &status_data.0.player_ids {
       for player in player_list.iter() {
         let _ = db_handler.player_database.add_playtime_to_player(
@@ -51,25 +71,54 @@ pub async fn log_server_status(
   match app.try_get_server(server_id).await {
     Ok(server_lock) => {
       let mut server = server_lock.lock().await;
+      let was_open = server.status.open;
+      let changed = server.status.open != status_data.0.open
+        || server.status.player_count != status_data.0.player_count
+        || server.status.max_player_count != status_data.0.max_player_count;
       server.status = status_data.0;
-      http_response_message_200()
+      if let Some(db_lock) = &app.databases {
+        let db_handler = db_lock.lock().await;
+        uptime::record_status(&db_handler.status_database, &mut server, was_open, now);
+      }
+      if changed {
+        app
+          .events
+          .publish(EventKind::ServerStatus, Some(server_id), &server.status);
+      }
+      if server.status.open != was_open {
+        let reason = (!server.status.open).then_some(uptime::INCIDENT_CLOSED);
+        uptime::publish_transition(&app.events, &server, reason);
+      }
+      Ok(http_response_message_200())
     }
-    Err(e) => http_response_message_500(Some(e.to_string())),
+    Err(e) => Err(ApiError::NotFound(e.to_string())),
   }
 }
 
+#[utoipa::path(
+  tag = "Logs",
+  request_body = HashMap<String, JsonValueNotObject>,
+  responses(
+    (status = 200, response = ApiMessageBody),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["logs:write"]))
+)]
 #[post(
-  "/logs/<server_id>/<translation>",
+  "/api/v1/servers/<server_id>/logs/<translation>",
   format = "application/json",
   data = "<parsed_data>"
 )]
 pub async fn log_with_translation(
-  _auth_header: LogAuthHeader,
+  _auth: ApiAuth<scopes::LogsWrite>,
+  _signed: SignedRequest,
+  _idempotent: Idempotent,
   app_data: &State<Arc<Mutex<Application>>>,
   server_id: u64,
   translation: &str,
-  parsed_data: Json<HashMap<String, JsonValueNotObject>>,
-) -> Json<Value> {
+  parsed_data: SignedJson<HashMap<String, JsonValueNotObject>>,
+) -> Result<Json<Value>, ApiError> {
   match application::features::logs::handle_log_with_data(
     app_data.inner(),
     server_id,
@@ -78,7 +127,61 @@ pub async fn log_with_translation(
   )
   .await
   {
-    Ok(_) => http_response_message_200(),
-    Err(e) => http_response_message_500(Some(e)),
+    Ok(_) => Ok(http_response_message_200()),
+    Err(e) => Err(ApiError::Internal(e)),
+  }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TranslatedLog {
+  pub translation: String,
+  pub data: HashMap<String, JsonValueNotObject>,
+}
+
+/// # Handles POST requests with many translated logs at once
+///
+/// Logs are sent to the server channel in the given order, every log has its own result
+#[utoipa::path(
+  tag = "Logs",
+  request_body = Vec<TranslatedLog>,
+  responses(
+    (status = 200, description = "Result of every log", body = BatchResponse<ApiMessageBody>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["logs:write"]))
+)]
+#[post(
+  "/api/v1/servers/<server_id>/logs",
+  format = "application/json",
+  data = "<parsed_data>"
+)]
+pub async fn log_batch_with_translation(
+  _auth: ApiAuth<scopes::LogsWrite>,
+  _signed: SignedRequest,
+  _idempotent: Idempotent,
+  app_data: &State<Arc<Mutex<Application>>>,
+  server_id: u64,
+  parsed_data: SignedJson<Vec<TranslatedLog>>,
+) -> Result<Json<BatchResponse<ApiMessageBody>>, ApiError> {
+  check_batch_size(parsed_data.0.len())?;
+  let mut results = vec![];
+  for log in parsed_data.0 {
+    let result = application::features::logs::handle_log_with_data(
+      app_data.inner(),
+      server_id,
+      &log.translation,
+      log.data,
+    )
+    .await;
+    results.push(
+      result
+        .map(|_| ApiMessageBody {
+          status: 200,
+          message: "OK".to_string(),
+        })
+        .map_err(ApiError::Internal),
+    );
   }
+  Ok(Json(BatchResponse::from_results(results)))
 }
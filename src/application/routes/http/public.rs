@@ -0,0 +1,305 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use rocket::{
+  fairing::{Fairing, Info, Kind},
+  http::{Header, Method, Status},
+  request::{self, FromRequest},
+  serde::json::Json,
+  Request, Response, State,
+};
+use serde::Serialize;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::application::{
+  application::Application,
+  db::leaderboards::structs::LeaderboardRecordType,
+  routes::{
+    auth::{auth_failure, AuthError},
+    errors::ApiError,
+    messages::ApiErrorBody,
+  },
+};
+
+pub const PUBLIC_API_PREFIX: &str = "/api/v1/public/";
+
+const DEFAULT_LEADERBOARD_LIMIT: u32 = 10;
+const MAX_LEADERBOARD_LIMIT: u32 = 100;
+/// Player count history reaches back one day by default and one week at most
+const DEFAULT_HISTORY_SECS: u64 = 24 * 3600;
+const MAX_HISTORY_SECS: u64 = 7 * 24 * 3600;
+
+/// # Public access guard
+/// Lets requests through when the public API is enabled, with the per-IP rate limit applied
+// MARK: Public access guard
+pub struct PublicAccess;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for PublicAccess {
+  type Error = AuthError;
+
+  async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+    let mut app = match request.guard::<&State<Arc<Mutex<Application>>>>().await {
+      request::Outcome::Success(state) => state.lock().await,
+      request::Outcome::Error(_) | request::Outcome::Forward(_) => {
+        return request::Outcome::Error((Status::InternalServerError, AuthError::Missing));
+      }
+    };
+    let app = &mut *app;
+    let Some(cfg) = app.config.as_ref() else {
+      return request::Outcome::Forward(Status::NotFound);
+    };
+    if cfg.public_api.is_none() {
+      return request::Outcome::Forward(Status::NotFound);
+    }
+
+    if let (Some(limits), Some(ip)) = (&cfg.rate_limits, request.client_ip()) {
+      if let Err(e) = app.rate_limiter.check_ip(limits, &ip.to_string()) {
+        return auth_failure(request, AuthError::RateLimited(e.limit, e.retry_after));
+      }
+    }
+    request::Outcome::Success(PublicAccess)
+  }
+}
+
+/// # Public API headers
+/// Adds CORS headers for the configured origins and `Cache-Control` to public routes
+// MARK: Public API headers fairing
+pub(crate) struct PublicApiHeaders;
+
+#[rocket::async_trait]
+impl Fairing for PublicApiHeaders {
+  fn info(&self) -> Info {
+    Info {
+      name: "Public API headers",
+      kind: Kind::Response,
+    }
+  }
+
+  async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+    if !request.uri().path().as_str().starts_with(PUBLIC_API_PREFIX) {
+      return;
+    }
+    let Some(app_data) = request.rocket().state::<Arc<Mutex<Application>>>() else {
+      return;
+    };
+    let Some(public_cfg) = app_data
+      .lock()
+      .await
+      .config
+      .as_ref()
+      .and_then(|cfg| cfg.public_api.clone())
+    else {
+      return;
+    };
+
+    if let Some(origin) = request.headers().get_one("Origin") {
+      if let Some(allowed) = public_cfg.allowed_origin(origin) {
+        response.set_header(Header::new("Access-Control-Allow-Origin", allowed));
+        response.set_header(Header::new("Access-Control-Allow-Methods", "GET, OPTIONS"));
+        response.set_header(Header::new("Access-Control-Max-Age", "86400"));
+      }
+      response.set_header(Header::new("Vary", "Origin"));
+    }
+    if request.method() == Method::Get && response.status() == Status::Ok {
+      response.set_header(Header::new(
+        "Cache-Control",
+        format!("public, max-age={}", public_cfg.cache_max_age),
+      ));
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PublicServer {
+  pub id: u64,
+  pub name: String,
+  pub open: bool,
+  pub player_count: u16,
+  pub max_player_count: u16,
+  pub tps: u8,
+  pub max_tps: u8,
+  pub last_heard: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PublicLeaderboardEntry {
+  pub rank: u32,
+  /// Latest username, hidden for players with `do_not_track`
+  pub username: Option<String>,
+  pub value: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PublicPlayerCount {
+  pub timestamp: u64,
+  pub player_count: u32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PublicPlayerCounts {
+  /// Players on all servers right now
+  pub online: u32,
+  pub servers_online: u32,
+  pub servers_total: u32,
+  /// Recorded counts, oldest first
+  pub history: Vec<PublicPlayerCount>,
+}
+
+/// Preflight for browsers, headers are added by [`PublicApiHeaders`]
+#[options("/api/v1/public/<_..>")]
+pub async fn public_preflight(_public: PublicAccess) -> Status {
+  Status::NoContent
+}
+
+#[utoipa::path(
+  tag = "Public",
+  responses(
+    (status = 200, description = "Configured servers with their status", body = Vec<PublicServer>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  )
+)]
+#[get("/api/v1/public/servers")]
+// MARK: Public servers
+pub async fn public_get_servers(
+  _public: PublicAccess,
+  app_data: &State<Arc<Mutex<Application>>>,
+) -> Json<Vec<PublicServer>> {
+  let servers = app_data.lock().await.servers.clone();
+  let mut public_servers = vec![];
+  for server_lock in servers {
+    let server = server_lock.lock().await;
+    public_servers.push(PublicServer {
+      id: server.id,
+      name: server.name.clone(),
+      open: server.status.open,
+      player_count: server.status.player_count,
+      max_player_count: server.status.max_player_count,
+      tps: server.status.tps,
+      max_tps: server.status.max_tps,
+      last_heard: server
+        .status
+        .last_heard
+        .and_then(|last_heard| DateTime::from_timestamp(last_heard, 0)),
+    });
+  }
+  Json(public_servers)
+}
+
+#[utoipa::path(
+  tag = "Public",
+  params(
+    ("type" = u8, Query, description = "Leaderboard record type"),
+    ("limit" = Option<u32>, Query, description = "Number of entries, 10 by default and 100 at most"),
+  ),
+  responses(
+    (status = 200, description = "Players with the highest total of the record type", body = Vec<PublicLeaderboardEntry>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  )
+)]
+#[get("/api/v1/public/leaderboards?<type>&<limit>")]
+// MARK: Public leaderboard
+pub async fn public_get_leaderboard(
+  _public: PublicAccess,
+  app_data: &State<Arc<Mutex<Application>>>,
+  r#type: u8,
+  limit: Option<u32>,
+) -> Result<Json<Vec<PublicLeaderboardEntry>>, ApiError> {
+  let kind = LeaderboardRecordType::from_u8(r#type).map_err(|_| {
+    ApiError::Validation(
+      format!("Unknown leaderboard type {}", r#type),
+      Some(serde_json::json!({ "field": "type" })),
+    )
+  })?;
+  let limit = limit
+    .unwrap_or(DEFAULT_LEADERBOARD_LIMIT)
+    .clamp(1, MAX_LEADERBOARD_LIMIT);
+
+  let app = app_data.lock().await;
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => {
+      let db_handler = db_handler_lock.lock().await;
+      let top = match db_handler.leaderboard_database.as_ref() {
+        Some(leaderboard_db) => leaderboard_db.get_top_by_type(kind, limit)?,
+        None => return Err(ApiError::leaderboards_disabled()),
+      };
+
+      let entries = top
+        .into_iter()
+        .enumerate()
+        .map(|(index, (player_id, value))| {
+          let username = db_handler
+            .player_database
+            .get_player_by_id(player_id)
+            .ok()
+            .filter(|player| !player.do_not_track)
+            .and_then(|player| player.usernames.last().cloned());
+          PublicLeaderboardEntry {
+            rank: index as u32 + 1,
+            username,
+            value,
+          }
+        })
+        .collect();
+      Ok(Json(entries))
+    }
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Public",
+  params(("since" = Option<u64>, Query, description = "Unix time of the oldest history entry, one day back by default and one week at most")),
+  responses(
+    (status = 200, description = "Current and recorded player counts of all servers", body = PublicPlayerCounts),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  )
+)]
+#[get("/api/v1/public/player-counts?<since>")]
+// MARK: Public player counts
+pub async fn public_get_player_counts(
+  _public: PublicAccess,
+  app_data: &State<Arc<Mutex<Application>>>,
+  since: Option<u64>,
+) -> Result<Json<PublicPlayerCounts>, ApiError> {
+  let now = Utc::now().timestamp() as u64;
+  let since = since
+    .unwrap_or(now.saturating_sub(DEFAULT_HISTORY_SECS))
+    .max(now.saturating_sub(MAX_HISTORY_SECS));
+
+  let app = app_data.lock().await;
+  let mut counts = PublicPlayerCounts {
+    online: 0,
+    servers_online: 0,
+    servers_total: app.servers.len() as u32,
+    history: vec![],
+  };
+  for server_lock in app.servers.iter() {
+    let server = server_lock.lock().await;
+    if server.status.open {
+      counts.servers_online += 1;
+      counts.online += server.status.player_count as u32;
+    }
+  }
+
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => {
+      let db_handler = db_handler_lock.lock().await;
+      let mut history = db_handler.player_database.get_player_count_from(since)?;
+      history.sort_by_key(|count| count.timestamp);
+      counts.history = history
+        .into_iter()
+        .map(|count| PublicPlayerCount {
+          timestamp: count.timestamp,
+          player_count: count.player_count,
+        })
+        .collect();
+      Ok(Json(counts))
+    }
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
@@ -0,0 +1,331 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use rocket::{serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::application::{
+  application::Application,
+  db::{
+    audit::structs::AuditActor,
+    status::structs::{DatabaseServerIncident, DatabaseStatusSample, UptimeReport},
+  },
+  features::audit::{self, actions},
+  routes::{
+    auth::{scopes, ApiAuth},
+    errors::ApiError,
+    idempotency::Idempotent,
+    messages::{http_response_message_200, ApiErrorBody, ApiMessageBody},
+    signing::{SignedJson, SignedRequest},
+    websocket::structs::{WsCommandResponse, WsReponseStatus, WsResponseCreate as _},
+  },
+  server::server::DistrictServer,
+};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_i, log_x};
+
+/// Period of history routes without `from`
+const DEFAULT_PERIOD_SECS: i64 = 24 * 3600;
+
+/// # Server status
+/// Last status the game server sent, with its websocket state
+#[derive(Serialize, ToSchema)]
+pub struct ServerStatusInfo {
+  pub id: u64,
+  pub name: String,
+  pub open: bool,
+  pub tps: u8,
+  pub max_tps: u8,
+  pub player_count: u16,
+  pub max_player_count: u16,
+  pub player_ids: Option<Vec<u64>>,
+  /// When the server last sent its status, not set until it does or after it went quiet
+  pub last_heard: Option<DateTime<Utc>>,
+  pub websocket_connected: bool,
+  pub websocket_connections: usize,
+}
+
+impl From<&DistrictServer> for ServerStatusInfo {
+  fn from(server: &DistrictServer) -> Self {
+    let status = &server.status;
+    ServerStatusInfo {
+      id: server.id,
+      name: server.name.clone(),
+      open: status.open,
+      tps: status.tps,
+      max_tps: status.max_tps,
+      player_count: status.player_count,
+      max_player_count: status.max_player_count,
+      player_ids: status.player_ids.clone(),
+      last_heard: status
+        .last_heard
+        .and_then(|last_heard| DateTime::from_timestamp(last_heard, 0)),
+      websocket_connected: server.ws_connections > 0,
+      websocket_connections: server.ws_connections,
+    }
+  }
+}
+
+#[utoipa::path(
+  tag = "Servers",
+  responses(
+    (status = 200, description = "Status of every configured server the key may act for", body = Vec<ServerStatusInfo>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["servers:read"]))
+)]
+#[get("/api/v1/servers")]
+// MARK: Get servers
+pub async fn get_servers(
+  auth: ApiAuth<scopes::ServersRead>,
+  _signed: SignedRequest,
+  app_data: &State<Arc<Mutex<Application>>>,
+) -> Json<Vec<ServerStatusInfo>> {
+  let servers = app_data.lock().await.servers.clone();
+  let mut statuses = vec![];
+  for server_lock in servers {
+    let server = server_lock.lock().await;
+    if auth.key.allows_server(server.id) {
+      statuses.push(ServerStatusInfo::from(&*server));
+    }
+  }
+  Json(statuses)
+}
+
+#[utoipa::path(
+  tag = "Servers",
+  responses(
+    (status = 200, description = "Status of the server", body = ServerStatusInfo),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["servers:read"]))
+)]
+#[get("/api/v1/servers/<server_id>/status")]
+// MARK: Get server status
+pub async fn get_server_status(
+  _auth: ApiAuth<scopes::ServersRead>,
+  _signed: SignedRequest,
+  app_data: &State<Arc<Mutex<Application>>>,
+  server_id: u64,
+) -> Result<Json<ServerStatusInfo>, ApiError> {
+  let app = app_data.lock().await;
+  match app.try_get_server(server_id).await {
+    Ok(server_lock) => Ok(Json(ServerStatusInfo::from(&*server_lock.lock().await))),
+    Err(e) => Err(ApiError::NotFound(e.to_string())),
+  }
+}
+
+/// Period of `from` and `to` query parameters (unix seconds), the last day by default
+fn resolve_period(from: Option<i64>, to: Option<i64>) -> Result<(i64, i64), ApiError> {
+  let to = to.unwrap_or_else(|| Utc::now().timestamp());
+  let from = from.unwrap_or(to - DEFAULT_PERIOD_SECS);
+  if from >= to {
+    return Err(ApiError::Validation(
+      "'from' has to be before 'to'".to_string(),
+      Some(serde_json::json!({ "field": "from" })),
+    ));
+  }
+  Ok((from, to))
+}
+
+/// Checks the server exists, so unknown servers get 404 instead of an empty history
+async fn check_server(app: &Application, server_id: u64) -> Result<(), ApiError> {
+  app
+    .try_get_server(server_id)
+    .await
+    .map(|_| ())
+    .map_err(|e| ApiError::NotFound(e.to_string()))
+}
+
+#[utoipa::path(
+  tag = "Servers",
+  params(
+    ("from" = Option<i64>, Query, description = "Start of the period in unix seconds, a day before `to` by default"),
+    ("to" = Option<i64>, Query, description = "End of the period in unix seconds, now by default"),
+  ),
+  responses(
+    (status = 200, description = "Status samples of the server (one a minute and every open/closed change), oldest first", body = Vec<DatabaseStatusSample>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["servers:read"]))
+)]
+#[get("/api/v1/servers/<server_id>/history?<from>&<to>")]
+// MARK: Get server status history
+pub async fn get_server_history(
+  _auth: ApiAuth<scopes::ServersRead>,
+  _signed: SignedRequest,
+  app_data: &State<Arc<Mutex<Application>>>,
+  server_id: u64,
+  from: Option<i64>,
+  to: Option<i64>,
+) -> Result<Json<Vec<DatabaseStatusSample>>, ApiError> {
+  let (from, to) = resolve_period(from, to)?;
+  let app = app_data.lock().await;
+  check_server(&app, server_id).await?;
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => db_handler_lock
+      .lock()
+      .await
+      .status_database
+      .get_samples(server_id, from, to)
+      .map_err(ApiError::from)
+      .map(Json),
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Servers",
+  params(
+    ("from" = Option<i64>, Query, description = "Start of the period in unix seconds, a day before `to` by default"),
+    ("to" = Option<i64>, Query, description = "End of the period in unix seconds, now by default"),
+  ),
+  responses(
+    (status = 200, description = "Downtime incidents overlapping the period, oldest first", body = Vec<DatabaseServerIncident>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["servers:read"]))
+)]
+#[get("/api/v1/servers/<server_id>/incidents?<from>&<to>")]
+// MARK: Get server incidents
+pub async fn get_server_incidents(
+  _auth: ApiAuth<scopes::ServersRead>,
+  _signed: SignedRequest,
+  app_data: &State<Arc<Mutex<Application>>>,
+  server_id: u64,
+  from: Option<i64>,
+  to: Option<i64>,
+) -> Result<Json<Vec<DatabaseServerIncident>>, ApiError> {
+  let (from, to) = resolve_period(from, to)?;
+  let app = app_data.lock().await;
+  check_server(&app, server_id).await?;
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => db_handler_lock
+      .lock()
+      .await
+      .status_database
+      .get_incidents(server_id, from, to)
+      .map_err(ApiError::from)
+      .map(Json),
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Servers",
+  params(
+    ("from" = Option<i64>, Query, description = "Start of the period in unix seconds, a day before `to` by default"),
+    ("to" = Option<i64>, Query, description = "End of the period in unix seconds, now by default"),
+  ),
+  responses(
+    (status = 200, description = "Uptime, downtime and average TPS of the server over the period", body = UptimeReport),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["servers:read"]))
+)]
+#[get("/api/v1/servers/<server_id>/uptime?<from>&<to>")]
+// MARK: Get server uptime
+pub async fn get_server_uptime(
+  _auth: ApiAuth<scopes::ServersRead>,
+  _signed: SignedRequest,
+  app_data: &State<Arc<Mutex<Application>>>,
+  server_id: u64,
+  from: Option<i64>,
+  to: Option<i64>,
+) -> Result<Json<UptimeReport>, ApiError> {
+  let (from, to) = resolve_period(from, to)?;
+  let app = app_data.lock().await;
+  check_server(&app, server_id).await?;
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => db_handler_lock
+      .lock()
+      .await
+      .status_database
+      .get_uptime_report(server_id, from, to, Utc::now().timestamp())
+      .map_err(ApiError::from)
+      .map(Json),
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+/// # Server command
+/// Console command for the game server, sent over its websocket
+#[derive(Deserialize, ToSchema)]
+pub struct ServerCommand {
+  pub command: String,
+}
+
+#[utoipa::path(
+  tag = "Servers",
+  request_body = ServerCommand,
+  responses(
+    (status = 200, response = ApiMessageBody),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["servers:command"]))
+)]
+#[post(
+  "/api/v1/servers/<server_id>/commands",
+  format = "application/json",
+  data = "<parsed_data>"
+)]
+// MARK: Send server command
+pub async fn send_server_command(
+  auth: ApiAuth<scopes::ServersCommand>,
+  _signed: SignedRequest,
+  _idempotent: Idempotent,
+  app_data: &State<Arc<Mutex<Application>>>,
+  server_id: u64,
+  parsed_data: SignedJson<ServerCommand>,
+) -> Result<Json<Value>, ApiError> {
+  let command = parsed_data.0.command.trim();
+  if command.is_empty() {
+    return Err(ApiError::Validation(
+      "Command can't be empty".to_string(),
+      Some(serde_json::json!({ "field": "command" })),
+    ));
+  }
+
+  let app = app_data.lock().await;
+  let server_lock = app
+    .try_get_server(server_id)
+    .await
+    .map_err(|e| ApiError::NotFound(e.to_string()))?;
+  let mut server = server_lock.lock().await;
+  // Commands are only delivered over the websocket, they'd pile up while it's away
+  if server.ws_connections == 0 {
+    return Err(ApiError::Conflict(format!(
+      "Server '{}' has no websocket connected",
+      server.name
+    )));
+  }
+  server.ws_msgs.push_back(WsCommandResponse::create(
+    WsReponseStatus::Ok,
+    "Ok",
+    Some(command),
+  ));
+  log_i!(format!(
+    "Key '{}' sent command to server '{}': {}",
+    auth.key.name, server.name, command
+  ));
+  if let Some(db_handler_lock) = app.databases.as_ref() {
+    audit::record(
+      &*db_handler_lock.lock().await,
+      &AuditActor::api_key(&auth.key.name),
+      actions::SERVER_COMMAND,
+      audit::target("server", server_id),
+      (),
+      serde_json::json!({ "command": command }),
+    );
+  }
+  Ok(http_response_message_200())
+}
@@ -1,12 +1,29 @@
-use rocket::response::content::RawHtml;
+use rocket::response::content::{RawCss, RawHtml, RawJavaScript};
 
 #[get("/")]
 pub fn static_index_page() -> RawHtml<String> {
   RawHtml("<title>DISTRICT Server</title><style>html{height:100dvh;display:grid;place-items:center;}body{border:2px solid gray;padding:2rem;text-align:center;}
-    </style><body><h1>DISTRICT Server</h1><p>You have found a DISTRICT server!</p><p>Don't worry, there is nothing to see here!</p></body>".to_string())
+    </style><body><h1>DISTRICT Server</h1><p>You have found a DISTRICT server!</p><p>Staff can log in to the <a href=\"/dashboard\">dashboard</a>.</p></body>".to_string())
 }
 
 #[get("/test")]
 pub(crate) async fn static_test() -> String {
   String::from("YES!")
 }
+
+/// Admin dashboard, a static page that logs in with an API key and uses the HTTP API
+// MARK: Dashboard
+#[get("/dashboard")]
+pub fn static_dashboard() -> RawHtml<&'static str> {
+  RawHtml(include_str!("../../../../assets/dashboard/index.html"))
+}
+
+#[get("/dashboard/dashboard.js")]
+pub fn static_dashboard_js() -> RawJavaScript<&'static str> {
+  RawJavaScript(include_str!("../../../../assets/dashboard/dashboard.js"))
+}
+
+#[get("/dashboard/dashboard.css")]
+pub fn static_dashboard_css() -> RawCss<&'static str> {
+  RawCss(include_str!("../../../../assets/dashboard/dashboard.css"))
+}
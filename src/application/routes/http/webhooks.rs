@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use rocket::{serde::json::Json, State};
+use serde::Serialize;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::application::{
+  application::Application,
+  config::webhook_config::WebhookEvent,
+  db::{
+    audit::structs::AuditActor,
+    webhooks::structs::{DatabaseWebhookDelivery, WebhookDeliveryStatus},
+  },
+  features::{
+    audit::{self, actions},
+    webhooks,
+  },
+  routes::{
+    auth::{scopes, ApiAuth},
+    errors::ApiError,
+    idempotency::Idempotent,
+    messages::ApiErrorBody,
+  },
+};
+
+/// Deliveries returned when the request doesn't set `limit`
+const DEFAULT_DELIVERY_LIMIT: u32 = 100;
+const MAX_DELIVERY_LIMIT: u32 = 1000;
+
+/// # Webhook info
+/// Configured webhook, without its secret
+#[derive(Serialize, ToSchema)]
+pub struct WebhookInfo {
+  pub name: String,
+  pub url: String,
+  pub events: Vec<WebhookEvent>,
+  pub max_attempts: u32,
+}
+
+#[utoipa::path(
+  tag = "Webhooks",
+  responses(
+    (status = 200, description = "Configured webhooks", body = Vec<WebhookInfo>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["admin"]))
+)]
+#[get("/api/v1/webhooks")]
+// MARK: Get webhooks
+pub async fn get_webhooks(
+  _auth: ApiAuth<scopes::Admin>,
+  app_data: &State<Arc<Mutex<Application>>>,
+) -> Json<Vec<WebhookInfo>> {
+  let app = app_data.lock().await;
+  let webhooks = app
+    .config
+    .as_ref()
+    .map(|cfg| {
+      cfg
+        .webhooks
+        .iter()
+        .map(|webhook| WebhookInfo {
+          name: webhook.name.clone(),
+          url: webhook.url.clone(),
+          events: webhook.events.clone(),
+          max_attempts: webhook.max_attempts,
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  Json(webhooks)
+}
+
+#[utoipa::path(
+  tag = "Webhooks",
+  params(
+    ("webhook" = Option<String>, Query, description = "Only deliveries to this webhook"),
+    ("status" = Option<String>, Query, description = "Only deliveries with this status (pending, delivered, failed)"),
+    ("limit" = Option<u32>, Query, description = "Deliveries to return, 100 by default and 1000 at most"),
+  ),
+  responses(
+    (status = 200, description = "Delivery log, latest first", body = Vec<DatabaseWebhookDelivery>),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["admin"]))
+)]
+#[get("/api/v1/webhooks/deliveries?<webhook>&<status>&<limit>")]
+// MARK: Get deliveries
+pub async fn get_webhook_deliveries(
+  _auth: ApiAuth<scopes::Admin>,
+  app_data: &State<Arc<Mutex<Application>>>,
+  webhook: Option<&str>,
+  status: Option<&str>,
+  limit: Option<u32>,
+) -> Result<Json<Vec<DatabaseWebhookDelivery>>, ApiError> {
+  let status = match status {
+    Some(status) => Some(
+      WebhookDeliveryStatus::from_name(status)
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown delivery status '{}'", status)))?,
+    ),
+    None => None,
+  };
+  let limit = limit
+    .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+    .min(MAX_DELIVERY_LIMIT);
+
+  let app = app_data.lock().await;
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => db_handler_lock
+      .lock()
+      .await
+      .webhook_database
+      .get_deliveries(webhook, status, limit)
+      .map_err(ApiError::from)
+      .map(Json),
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Webhooks",
+  responses(
+    (status = 200, description = "Delivery with the outcome of its last attempt", body = DatabaseWebhookDelivery),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["admin"]))
+)]
+#[get("/api/v1/webhooks/deliveries/<delivery_id>")]
+// MARK: Get delivery
+pub async fn get_webhook_delivery(
+  _auth: ApiAuth<scopes::Admin>,
+  app_data: &State<Arc<Mutex<Application>>>,
+  delivery_id: u64,
+) -> Result<Json<DatabaseWebhookDelivery>, ApiError> {
+  let app = app_data.lock().await;
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => db_handler_lock
+      .lock()
+      .await
+      .webhook_database
+      .get_delivery(delivery_id)
+      .map_err(ApiError::from)
+      .map(Json),
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
+
+#[utoipa::path(
+  tag = "Webhooks",
+  responses(
+    (status = 200, description = "Queued `test` delivery, poll it to see the outcome", body = DatabaseWebhookDelivery),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["admin"]))
+)]
+#[post("/api/v1/webhooks/<name>/test")]
+// MARK: Test webhook
+pub async fn test_webhook(
+  auth: ApiAuth<scopes::Admin>,
+  _idempotent: Idempotent,
+  app_data: &State<Arc<Mutex<Application>>>,
+  name: &str,
+) -> Result<Json<DatabaseWebhookDelivery>, ApiError> {
+  let app = app_data.lock().await;
+  let webhook = app
+    .config
+    .as_ref()
+    .and_then(|cfg| cfg.webhooks.iter().find(|webhook| webhook.name == name))
+    .ok_or_else(|| ApiError::NotFound(format!("Webhook '{}' is not configured", name)))?;
+
+  match app.databases.as_ref() {
+    Some(db_handler_lock) => {
+      let id = webhooks::enqueue(
+        db_handler_lock,
+        webhook,
+        WebhookEvent::Test,
+        None,
+        serde_json::json!({ "requested_by": auth.key.name }),
+      )
+      .await
+      .map_err(ApiError::from)?;
+      let db_handler = db_handler_lock.lock().await;
+      let delivery = db_handler
+        .webhook_database
+        .get_delivery(id)
+        .map_err(ApiError::from)?;
+      audit::record(
+        &db_handler,
+        &AuditActor::api_key(&auth.key.name),
+        actions::WEBHOOK_TEST,
+        audit::target("webhook", name),
+        (),
+        &delivery,
+      );
+      Ok(Json(delivery))
+    }
+    None => Err(ApiError::DatabaseUnavailable),
+  }
+}
@@ -0,0 +1,282 @@
+use std::{io::Cursor, sync::Arc};
+
+use chrono::Utc;
+use rocket::{
+  fairing::{Fairing, Info, Kind},
+  http::{ContentType, Header, Status},
+  request::{self, FromRequest},
+  Request, Response, State,
+};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::application::{application::Application, routes::auth::AuthorizedKey};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_w, log_x};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+/// Set on responses replayed from an earlier request with the same key
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+/// POST routes without [`Idempotent`], their responses carry secrets that must not be stored
+pub const UNSTORED_ROUTES: &[&str] = &["/api/v1/auth/keys"];
+
+const MAX_KEY_LENGTH: usize = 255;
+/// Seconds after which an unfinished request (crashed, connection dropped) no longer holds its key
+const ABANDONED_AFTER_SECS: i64 = 60;
+
+#[derive(Debug, Clone)]
+pub enum IdempotencyError {
+  /// Header is empty, too long or not printable ASCII
+  Invalid,
+  /// Request with the same key is still being handled
+  InProgress,
+  /// Key was used for a request to another route or with another body
+  Mismatch,
+}
+
+/// Stored response of an earlier request with the same key
+#[derive(Clone)]
+struct ReplayedResponse {
+  status: u16,
+  content_type: Option<String>,
+  body: Vec<u8>,
+  /// Hex SHA-256 of the body of the original request
+  body_hash: String,
+}
+
+/// Stored response waiting for [`check_idempotent_body`], on routes that read a body
+struct PendingReplay(Option<ReplayedResponse>);
+
+/// Hex SHA-256 of the request body, stored with the response of a claimed key
+struct RequestBodyHash(Option<String>);
+
+/// What [`IdempotencyResponses`] does with the response of the request
+#[derive(Clone)]
+enum IdempotencyState {
+  /// Store the response under `(key_name, idempotency_key)`
+  Claimed(String, String),
+  /// Replace the response with the stored one
+  Replay(ReplayedResponse),
+}
+
+/// # Idempotency guard
+/// Requests with `Idempotency-Key` header run once per API key and key,
+/// repeats within the configured window get the original response.
+/// Must come after [`super::auth::ApiAuth`], keys are scoped to the API key
+// MARK: Idempotency guard
+pub struct Idempotent;
+
+fn idempotency_failure<T>(
+  request: &Request<'_>,
+  error: IdempotencyError,
+) -> request::Outcome<T, IdempotencyError> {
+  let status = match error {
+    IdempotencyError::Invalid => Status::BadRequest,
+    IdempotencyError::InProgress => Status::Conflict,
+    IdempotencyError::Mismatch => Status::UnprocessableEntity,
+  };
+  request.local_cache(|| Some(error.clone()));
+  request::Outcome::Error((status, error))
+}
+
+fn body_hash(body: &[u8]) -> String {
+  hex::encode(Sha256::digest(body))
+}
+
+/// Routes with a `format` read a JSON body, whose hash is only known to the data guard
+fn reads_body(request: &Request<'_>) -> bool {
+  request.route().is_some_and(|route| route.format.is_some())
+}
+
+/// Keeps the body hash of the request and, for a repeated key, compares it with the
+/// original one. Called by [`super::signing::SignedJson`] once the body is read,
+/// `Some` is the status that stops the request before its handler
+pub(crate) fn check_idempotent_body(request: &Request<'_>, body: &[u8]) -> Option<Status> {
+  let hash = body_hash(body);
+  let PendingReplay(Some(replayed)) = request.local_cache(|| PendingReplay(None)) else {
+    request.local_cache(|| RequestBodyHash(Some(hash)));
+    return None;
+  };
+  if replayed.body_hash != hash {
+    request.local_cache(|| Some(IdempotencyError::Mismatch));
+    return Some(Status::UnprocessableEntity);
+  }
+  request.local_cache(|| Some(IdempotencyState::Replay(replayed.clone())));
+  // Handler is skipped, the stored response is put in place by `IdempotencyResponses`
+  Some(Status::Conflict)
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Idempotent {
+  type Error = IdempotencyError;
+
+  async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+    let idempotency_key = match request.headers().get_one(IDEMPOTENCY_KEY_HEADER) {
+      Some(val) => val,
+      None => return request::Outcome::Success(Idempotent),
+    };
+    let is_valid = !idempotency_key.is_empty()
+      && idempotency_key.len() <= MAX_KEY_LENGTH
+      && idempotency_key.bytes().all(|val| val.is_ascii_graphic());
+    if !is_valid {
+      return idempotency_failure(request, IdempotencyError::Invalid);
+    }
+
+    let app = match request.guard::<&State<Arc<Mutex<Application>>>>().await {
+      request::Outcome::Success(state) => state.lock().await,
+      request::Outcome::Error(_) | request::Outcome::Forward(_) => {
+        return request::Outcome::Error((Status::InternalServerError, IdempotencyError::Invalid));
+      }
+    };
+    let Some(db_handler_lock) = app.databases.as_ref() else {
+      return request::Outcome::Success(Idempotent);
+    };
+    let window_hours = app
+      .config
+      .as_ref()
+      .and_then(|cfg| cfg.idempotency.clone())
+      .unwrap_or_default()
+      .window_hours;
+    let key_name = request
+      .local_cache(|| None::<AuthorizedKey>)
+      .as_ref()
+      .map(|key| key.name.clone())
+      .unwrap_or_default();
+    let method = request.method().as_str();
+    let uri = request.uri().to_string();
+    let now = Utc::now().timestamp();
+
+    let existing = db_handler_lock
+      .lock()
+      .await
+      .auth_database
+      .claim_idempotency_key(
+        &key_name,
+        idempotency_key,
+        method,
+        &uri,
+        now - window_hours as i64 * 3600,
+        now - ABANDONED_AFTER_SECS,
+      );
+    match existing {
+      Ok(None) => {
+        request.local_cache(|| {
+          Some(IdempotencyState::Claimed(
+            key_name,
+            idempotency_key.to_string(),
+          ))
+        });
+        request::Outcome::Success(Idempotent)
+      }
+      Ok(Some(record)) if record.method != method || record.uri != uri => {
+        idempotency_failure(request, IdempotencyError::Mismatch)
+      }
+      Ok(Some(record)) => match record.status {
+        Some(status) => {
+          let replayed = ReplayedResponse {
+            status,
+            content_type: record.content_type,
+            body: record.body.unwrap_or_default(),
+            body_hash: record.body_hash.unwrap_or_default(),
+          };
+          if reads_body(request) {
+            request.local_cache(|| PendingReplay(Some(replayed)));
+            return request::Outcome::Success(Idempotent);
+          }
+          if replayed.body_hash != body_hash(&[]) {
+            return idempotency_failure(request, IdempotencyError::Mismatch);
+          }
+          request.local_cache(|| Some(IdempotencyState::Replay(replayed)));
+          // Handler is skipped, the stored response is put in place by `IdempotencyResponses`
+          request::Outcome::Error((Status::Conflict, IdempotencyError::InProgress))
+        }
+        None => idempotency_failure(request, IdempotencyError::InProgress),
+      },
+      Err(e) => {
+        // Without storage the request runs as if it had no key
+        log_w!(format!("Couldn't claim idempotency key: {}", e));
+        request::Outcome::Success(Idempotent)
+      }
+    }
+  }
+}
+
+/// # Idempotent responses
+/// Stores successful responses of claimed keys and replays stored ones,
+/// failed requests release their key so they can be retried
+// MARK: Idempotency fairing
+pub(crate) struct IdempotencyResponses;
+
+#[rocket::async_trait]
+impl Fairing for IdempotencyResponses {
+  fn info(&self) -> Info {
+    Info {
+      name: "Idempotent responses",
+      kind: Kind::Response,
+    }
+  }
+
+  async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+    let state = match request.local_cache(|| None::<IdempotencyState>) {
+      Some(val) => val.clone(),
+      None => return,
+    };
+
+    let (key_name, idempotency_key) = match state {
+      IdempotencyState::Replay(replayed) => {
+        response.set_status(Status::new(replayed.status));
+        match replayed
+          .content_type
+          .and_then(|val| ContentType::parse_flexible(&val))
+        {
+          Some(content_type) => {
+            response.set_header(content_type);
+          }
+          None => response.remove_header("Content-Type"),
+        }
+        response.set_sized_body(replayed.body.len(), Cursor::new(replayed.body));
+        response.set_header(Header::new(IDEMPOTENT_REPLAYED_HEADER, "true"));
+        return;
+      }
+      IdempotencyState::Claimed(key_name, idempotency_key) => (key_name, idempotency_key),
+    };
+
+    let Some(app_state) = request.rocket().state::<Arc<Mutex<Application>>>() else {
+      return;
+    };
+    let Some(db_handler_lock) = app_state.lock().await.databases.clone() else {
+      return;
+    };
+    let db_handler = db_handler_lock.lock().await;
+
+    let result = if response.status().class().is_success() {
+      let body = response.body_mut().to_bytes().await.unwrap_or_default();
+      let content_type = response.content_type().map(|val| val.to_string());
+      let request_body_hash = match request.local_cache(|| RequestBodyHash(None)) {
+        RequestBodyHash(Some(hash)) => hash.clone(),
+        RequestBodyHash(None) => body_hash(&[]),
+      };
+      let result = db_handler.auth_database.store_idempotent_response(
+        &key_name,
+        &idempotency_key,
+        response.status().code,
+        content_type,
+        &body,
+        &request_body_hash,
+      );
+      response.set_sized_body(body.len(), Cursor::new(body));
+      result
+    } else {
+      db_handler
+        .auth_database
+        .release_idempotency_key(&key_name, &idempotency_key)
+    };
+    if let Err(e) = result {
+      log_w!(format!(
+        "Couldn't finish idempotency key of '{}': {}",
+        key_name, e
+      ));
+    }
+  }
+}
@@ -1,31 +1,43 @@
-use rocket::serde::json::Json;
+use rocket::{http::Status, serde::json::Json};
+use serde::Serialize;
 use serde_json::{json, Value};
+use utoipa::{ToResponse, ToSchema};
 
-pub fn http_response_message_200() -> Json<Value> {
-  Json(json!({
-    "status": 200,
-    "message": "OK"
-  }))
+/// Body of successful responses without data
+#[derive(Serialize, ToSchema, ToResponse)]
+#[response(description = "Success")]
+pub struct ApiMessageBody {
+  pub status: u16,
+  pub message: String,
 }
 
-// pub fn http_response_message_400() -> Json<Value> {
-//   Json(json!({
-//     "status": 400,
-//     "message": "Bad Request"
-//   }))
-// }
+/// Body of error responses
+#[derive(Serialize, ToSchema, ToResponse)]
+#[response(description = "Error")]
+pub struct ApiErrorBody {
+  pub status: u16,
+  pub code: String,
+  pub message: String,
+  pub details: Option<Value>,
+}
 
-// pub fn http_response_message_404() -> Json<Value> {
-//     Json(json!({
-//       "status": 404,
-//       "message": "Not Found"
-//     }))
-// }
+pub fn http_response_message_200() -> Json<Value> {
+  Json(json!(ApiMessageBody {
+    status: 200,
+    message: "OK".to_string(),
+  }))
+}
 
-pub fn http_response_message_500(error: Option<String>) -> Json<Value> {
-  Json(json!({
-    "status": 500,
-    "message": "Internal Server Error",
-    "error": error
+pub fn http_response_message_error(
+  status: Status,
+  code: &str,
+  message: &str,
+  details: Option<Value>,
+) -> Json<Value> {
+  Json(json!(ApiErrorBody {
+    status: status.code,
+    code: code.to_string(),
+    message: message.to_string(),
+    details,
   }))
 }
@@ -0,0 +1,81 @@
+use std::{sync::Arc, time::Instant};
+
+use rocket::{
+  fairing::{Fairing, Info, Kind},
+  http::ContentType,
+  Data, Request, Response, State,
+};
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::Application,
+  features::metrics::METRICS,
+  routes::{
+    auth::{scopes, ApiAuth},
+    messages::ApiErrorBody,
+  },
+};
+
+/// When the request arrived
+struct RequestStart(Option<Instant>);
+
+/// # Request metrics
+/// Counts handled requests and their duration per route
+// MARK: Request metrics fairing
+pub(crate) struct RequestMetrics;
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+  fn info(&self) -> Info {
+    Info {
+      name: "Request metrics",
+      kind: Kind::Request | Kind::Response,
+    }
+  }
+
+  async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data<'_>) {
+    request.local_cache(|| RequestStart(Some(Instant::now())));
+  }
+
+  async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+    let RequestStart(Some(start)) = request.local_cache(|| RequestStart(None)) else {
+      return;
+    };
+    // Unmatched requests share one label, so random paths don't create new series
+    let route = request.route().map_or_else(
+      || "unmatched".to_string(),
+      |route| route.uri.path().to_string(),
+    );
+    METRICS.observe_request(
+      request.method().as_str(),
+      &route,
+      response.status().code,
+      start.elapsed(),
+    );
+  }
+}
+
+#[utoipa::path(
+  tag = "Meta",
+  responses(
+    (status = 200, description = "Metrics in the Prometheus text format", content_type = "text/plain", body = String),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["metrics:read"]))
+)]
+#[get("/metrics")]
+// MARK: Metrics
+pub async fn prometheus_metrics(
+  _auth: ApiAuth<scopes::MetricsRead>,
+  app_data: &State<Arc<Mutex<Application>>>,
+) -> (ContentType, String) {
+  let servers = app_data.lock().await.servers.clone();
+  for server_lock in servers {
+    METRICS.observe_server(&*server_lock.lock().await);
+  }
+  (
+    ContentType::new("text", "plain").with_params(("version", "0.0.4")),
+    METRICS.render(),
+  )
+}
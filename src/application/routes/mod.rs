@@ -1,76 +1,151 @@
+pub(crate) mod auth;
+pub(crate) mod batch;
+pub(crate) mod errors;
+pub(crate) mod export;
 pub(super) mod http;
+pub(crate) mod idempotency;
 pub(super) mod messages;
+pub(crate) mod metrics;
+pub(crate) mod openapi;
+pub(crate) mod signing;
+pub(crate) mod validation;
+pub(crate) mod versioning;
 pub mod websocket;
 
-use http::log_routes::{log_server_status, log_with_translation};
-use http::r#static::{static_index_page, static_test};
-use rocket::Route;
+use http::audit::get_audit_log;
+use http::events::events_stream;
+use http::health::{health_live, health_ready};
+use http::keys::{auth_get_keys, auth_get_rate_limits, auth_mint_key};
+use http::log_routes::{log_batch_with_translation, log_server_status, log_with_translation};
+pub(crate) use http::public::PublicApiHeaders;
+use http::public::{
+  public_get_leaderboard, public_get_player_counts, public_get_servers, public_preflight,
+};
+use http::r#static::{
+  static_dashboard, static_dashboard_css, static_dashboard_js, static_index_page, static_test,
+};
+use http::servers::{
+  get_server_history, get_server_incidents, get_server_status, get_server_uptime, get_servers,
+  send_server_command,
+};
+use http::webhooks::{get_webhook_deliveries, get_webhook_delivery, get_webhooks, test_webhook};
+use rocket::{Catcher, Route};
 
 use self::http::db::leaderboard::{
-  db_add_stat_to_players_leaderboards, db_clear_leaderboards, db_get_player_leaderboards,
-  db_leaderboards_remove_by_date,
+  db_add_stat_to_players_leaderboards, db_add_stats_to_players_leaderboards, db_clear_leaderboards,
+  db_export_leaderboards, db_get_player_leaderboards, db_leaderboards_remove_by_date,
 };
 use self::http::db::{
+  links::{db_get_account_links, db_set_primary_account, db_unlink_account},
   players::{
-    db_add_punishment_to_player, db_get_all_players, db_get_player_by_discord_id,
-    db_get_player_by_player_id, db_get_player_by_steam_id, db_get_player_count, db_on_player_join,
-    db_set_some_player_count,
+    db_add_punishment_to_player, db_export_player_count, db_export_players, db_get_all_players,
+    db_get_player_by_discord_id, db_get_player_by_player_id, db_get_player_by_steam_id,
+    db_get_player_count, db_on_player_join, db_on_player_join_batch, db_set_some_player_count,
   },
   punishments::{
-    db_get_all_punishments, db_get_punishment_by_punishment_id, get_punishments_by_ip,
-    get_punishments_by_player_id, get_punishments_by_steam_id,
+    db_export_punishments, db_get_all_punishments, db_get_punishment_by_punishment_id,
+    db_revoke_punishment, get_punishments_by_ip, get_punishments_by_player_id,
+    get_punishments_by_steam_id,
   },
   verification::{
-    db_add_player_verification, db_get_player_verification_by_code,
-    db_get_player_verification_by_discord_id, db_get_player_verification_by_player_id,
-    db_get_player_verification_by_steam_id, db_update_player_verification,
-    dn_modify_player_verification,
+    db_add_player_verification, db_get_pending_verification_reviews,
+    db_get_player_verification_by_code, db_get_player_verification_by_discord_id,
+    db_get_player_verification_by_player_id, db_get_player_verification_by_steam_id,
+    db_update_player_verification, dn_modify_player_verification,
   },
 };
+use self::metrics::prometheus_metrics;
+use self::versioning::api_versions;
 use self::websocket::websocket_connect;
 
 #[derive(Debug, Clone)]
 pub(crate) struct DistrictRouter {
   routes: Vec<Route>,
+  catchers: Vec<Catcher>,
 }
 
 impl DistrictRouter {
   pub fn new() -> Self {
+    let mut routes = routes![
+      static_index_page,
+      static_test,
+      static_dashboard,
+      static_dashboard_js,
+      static_dashboard_css,
+      health_live,
+      health_ready,
+      api_versions,
+      prometheus_metrics,
+      auth_get_keys,
+      auth_mint_key,
+      auth_get_rate_limits,
+      get_audit_log,
+      events_stream,
+      get_webhooks,
+      get_webhook_deliveries,
+      get_webhook_delivery,
+      test_webhook,
+      public_preflight,
+      public_get_servers,
+      public_get_leaderboard,
+      public_get_player_counts,
+      get_servers,
+      get_server_status,
+      get_server_history,
+      get_server_incidents,
+      get_server_uptime,
+      send_server_command,
+      log_server_status,
+      log_with_translation,
+      log_batch_with_translation,
+      websocket_connect,
+      db_on_player_join,
+      db_on_player_join_batch,
+      db_get_all_players,
+      db_export_players,
+      db_get_player_by_player_id,
+      db_get_player_by_steam_id,
+      db_get_player_by_discord_id,
+      db_add_punishment_to_player,
+      db_get_player_count,
+      db_export_player_count,
+      db_set_some_player_count,
+      db_get_all_punishments,
+      db_export_punishments,
+      db_get_punishment_by_punishment_id,
+      db_revoke_punishment,
+      get_punishments_by_player_id,
+      get_punishments_by_steam_id,
+      get_punishments_by_ip,
+      db_get_player_verification_by_player_id,
+      db_get_player_verification_by_steam_id,
+      db_get_player_verification_by_discord_id,
+      db_get_player_verification_by_code,
+      db_add_player_verification,
+      db_update_player_verification,
+      dn_modify_player_verification,
+      db_get_pending_verification_reviews,
+      db_get_account_links,
+      db_set_primary_account,
+      db_unlink_account,
+      db_get_player_leaderboards,
+      db_export_leaderboards,
+      db_add_stat_to_players_leaderboards,
+      db_add_stats_to_players_leaderboards,
+      db_clear_leaderboards,
+      db_leaderboards_remove_by_date,
+    ];
+    routes.extend(openapi::get_openapi_routes());
+
     DistrictRouter {
-      routes: routes![
-        static_index_page,
-        static_test,
-        log_server_status,
-        log_with_translation,
-        websocket_connect,
-        db_on_player_join,
-        db_get_all_players,
-        db_get_player_by_player_id,
-        db_get_player_by_steam_id,
-        db_get_player_by_discord_id,
-        db_add_punishment_to_player,
-        db_get_player_count,
-        db_set_some_player_count,
-        db_get_all_punishments,
-        db_get_punishment_by_punishment_id,
-        get_punishments_by_player_id,
-        get_punishments_by_steam_id,
-        get_punishments_by_ip,
-        db_get_player_verification_by_player_id,
-        db_get_player_verification_by_steam_id,
-        db_get_player_verification_by_discord_id,
-        db_get_player_verification_by_code,
-        db_add_player_verification,
-        db_update_player_verification,
-        dn_modify_player_verification,
-        db_get_player_leaderboards,
-        db_add_stat_to_players_leaderboards,
-        db_clear_leaderboards,
-        db_leaderboards_remove_by_date,
-      ],
+      routes,
+      catchers: http::catchers::get_catchers(),
     }
   }
   pub fn get_routes(&self) -> &Vec<Route> {
     &self.routes
   }
+  pub fn get_catchers(&self) -> &Vec<Catcher> {
+    &self.catchers
+  }
 }
@@ -0,0 +1,165 @@
+use rocket::Route;
+use utoipa::{
+  openapi::{
+    path::{ParameterBuilder, ParameterIn},
+    security::{ApiKey, ApiKeyValue, SecurityScheme},
+    Object, Required, Type,
+  },
+  Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::application::features::audit::UNAUDITED_ROUTES;
+
+use super::http::{audit, db, events, health, keys, log_routes, public, servers, webhooks};
+use super::messages::{ApiErrorBody, ApiMessageBody};
+use super::{idempotency, metrics, versioning, websocket};
+
+/// # OpenAPI document
+/// Generated from the `#[utoipa::path]` annotations of the routes, served at `/openapi.json`
+// MARK: OpenAPI document
+#[derive(OpenApi)]
+#[openapi(
+  info(title = "District Server API"),
+  paths(
+    db::players::db_on_player_join,
+    db::players::db_on_player_join_batch,
+    db::players::db_get_all_players,
+    db::players::db_export_players,
+    db::players::db_get_player_by_player_id,
+    db::players::db_get_player_by_steam_id,
+    db::players::db_get_player_by_discord_id,
+    db::players::db_add_punishment_to_player,
+    db::players::db_get_player_count,
+    db::players::db_export_player_count,
+    db::players::db_set_some_player_count,
+    db::punishments::db_get_all_punishments,
+    db::punishments::db_export_punishments,
+    db::punishments::db_get_punishment_by_punishment_id,
+    db::punishments::db_revoke_punishment,
+    db::punishments::get_punishments_by_player_id,
+    db::punishments::get_punishments_by_steam_id,
+    db::punishments::get_punishments_by_ip,
+    db::verification::db_get_player_verification_by_player_id,
+    db::verification::db_get_player_verification_by_steam_id,
+    db::verification::db_get_player_verification_by_discord_id,
+    db::verification::db_get_player_verification_by_code,
+    db::verification::db_add_player_verification,
+    db::verification::db_update_player_verification,
+    db::verification::dn_modify_player_verification,
+    db::verification::db_get_pending_verification_reviews,
+    db::links::db_get_account_links,
+    db::links::db_set_primary_account,
+    db::links::db_unlink_account,
+    db::leaderboard::db_get_player_leaderboards,
+    db::leaderboard::db_export_leaderboards,
+    db::leaderboard::db_add_stat_to_players_leaderboards,
+    db::leaderboard::db_add_stats_to_players_leaderboards,
+    db::leaderboard::db_clear_leaderboards,
+    db::leaderboard::db_leaderboards_remove_by_date,
+    servers::get_servers,
+    servers::get_server_status,
+    servers::get_server_history,
+    servers::get_server_incidents,
+    servers::get_server_uptime,
+    servers::send_server_command,
+    log_routes::log_server_status,
+    log_routes::log_with_translation,
+    log_routes::log_batch_with_translation,
+    websocket::websocket_connect,
+    versioning::api_versions,
+    metrics::prometheus_metrics,
+    keys::auth_get_keys,
+    keys::auth_mint_key,
+    keys::auth_get_rate_limits,
+    audit::get_audit_log,
+    events::events_stream,
+    webhooks::get_webhooks,
+    webhooks::get_webhook_deliveries,
+    webhooks::get_webhook_delivery,
+    webhooks::test_webhook,
+    health::health_live,
+    health::health_ready,
+    public::public_get_servers,
+    public::public_get_leaderboard,
+    public::public_get_player_counts,
+  ),
+  components(
+    schemas(ApiErrorBody, ApiMessageBody),
+    responses(ApiErrorBody, ApiMessageBody)
+  ),
+  modifiers(&SecurityAddon, &IdempotencyAddon, &AuditAddon)
+)]
+pub(crate) struct ApiDoc;
+
+/// Registers the `Authorization` header API key from `auth.keys` section of the config
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+  fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+    if let Some(components) = openapi.components.as_mut() {
+      components.add_security_scheme(
+        "api_key",
+        SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+          "Authorization",
+          "API key with the listed scope, websocket also accepts it as `authorization` cookie. Keys bound to servers are only checked against the `<server_id>` of the path, scopes of routes without one (players, punishments, verifications, links, leaderboards) apply to every server",
+        ))),
+      );
+    }
+  }
+}
+
+/// Documents the optional `Idempotency-Key` header on POST routes that store their responses
+struct IdempotencyAddon;
+
+impl Modify for IdempotencyAddon {
+  fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+    for operation in openapi
+      .paths
+      .paths
+      .iter_mut()
+      .filter(|(path, _)| !idempotency::UNSTORED_ROUTES.contains(&path.as_str()))
+      .filter_map(|(_, item)| item.post.as_mut())
+    {
+      operation.parameters.get_or_insert_with(Vec::new).push(
+        ParameterBuilder::new()
+          .name(idempotency::IDEMPOTENCY_KEY_HEADER)
+          .parameter_in(ParameterIn::Header)
+          .required(Required::False)
+          .description(Some(
+            "Repeats with the same key, API key and body within the configured window get the first response (`Idempotent-Replayed: true`) instead of running again",
+          ))
+          .schema(Some(Object::with_type(Type::String)))
+          .build(),
+      );
+    }
+  }
+}
+
+/// Notes the POST routes that write no audit log entries
+struct AuditAddon;
+
+impl Modify for AuditAddon {
+  fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+    for operation in openapi
+      .paths
+      .paths
+      .iter_mut()
+      .filter(|(path, _)| UNAUDITED_ROUTES.contains(&path.as_str()))
+      .filter_map(|(_, item)| item.post.as_mut())
+    {
+      let description = operation.description.get_or_insert_with(String::new);
+      if !description.is_empty() {
+        description.push_str("\n\n");
+      }
+      description.push_str("Game server telemetry, not written to the audit log");
+    }
+  }
+}
+
+/// `/openapi.json` and the interactive viewer at `/docs`
+pub(crate) fn get_openapi_routes() -> Vec<Route> {
+  SwaggerUi::new("/docs/<_..>")
+    .url("/openapi.json", ApiDoc::openapi())
+    .into()
+}
@@ -0,0 +1,260 @@
+use std::{ops::Deref, sync::Arc};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rocket::{
+  data::{self, Data, FromData, ToByteUnit},
+  http::Status,
+  request::{self, FromRequest},
+  Request, State,
+};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq as _;
+use tokio::sync::Mutex;
+
+use crate::application::{
+  application::Application,
+  routes::{
+    auth::{auth_failure, routed_server_id, AuthError, AuthorizedKey},
+    idempotency::check_idempotent_body,
+    versioning::requested_uri,
+  },
+};
+use crate::logger::{LogLevel, Logger};
+use crate::{log_w, log_x};
+
+pub const TIMESTAMP_HEADER: &str = "X-District-Timestamp";
+pub const NONCE_HEADER: &str = "X-District-Nonce";
+pub const CONTENT_HASH_HEADER: &str = "X-District-Content-SHA256";
+pub const SIGNATURE_HEADER: &str = "X-District-Signature";
+/// Server the request acts for, on routes without `<server_id>` segment
+pub const SERVER_HEADER: &str = "X-District-Server";
+
+/// How far (in seconds) the request timestamp may be from the server time
+const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Body digest the signature was made over, checked by [`SignedJson`]
+struct SignedBody(Option<Vec<u8>>);
+
+/// Text the HMAC is calculated over
+///
+/// `METHOD\nURI\nTIMESTAMP\nNONCE\nCONTENT_SHA256`, with the uri as requested (path and query)
+/// and the lowercase hex SHA-256 of the body (of an empty body for requests without one)
+pub fn signing_string(
+  method: &str,
+  uri: &str,
+  timestamp: &str,
+  nonce: &str,
+  content_hash: &str,
+) -> String {
+  format!(
+    "{}\n{}\n{}\n{}\n{}",
+    method.to_uppercase(),
+    uri,
+    timestamp,
+    nonce,
+    content_hash.to_lowercase()
+  )
+}
+
+/// # Signed request guard
+/// Checks the HMAC signature of requests acting for a server with `signing_secret`,
+/// requests for servers without one pass unsigned.
+/// Must come after [`super::auth::ApiAuth`], which tells what servers the key is bound to
+// MARK: Signed request guard
+pub struct SignedRequest;
+
+fn bad_signature<T>(request: &Request<'_>, reason: &str) -> request::Outcome<T, AuthError> {
+  auth_failure(request, AuthError::BadSignature(reason.to_string()))
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SignedRequest {
+  type Error = AuthError;
+
+  async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+    let app = match request.guard::<&State<Arc<Mutex<Application>>>>().await {
+      request::Outcome::Success(state) => state.lock().await,
+      request::Outcome::Error(_) | request::Outcome::Forward(_) => {
+        return request::Outcome::Error((Status::InternalServerError, AuthError::Missing));
+      }
+    };
+    let servers = match app.config.as_ref() {
+      Some(cfg) => &cfg.servers,
+      None => return request::Outcome::Success(SignedRequest),
+    };
+    let secret_of = |server_id: u64| {
+      servers
+        .iter()
+        .find(|server| server.id == server_id)
+        .and_then(|server| server.signing_secret.clone())
+    };
+    let headers = request.headers();
+    let bound_servers = request
+      .local_cache(|| None::<AuthorizedKey>)
+      .as_ref()
+      .and_then(|key| key.servers.clone())
+      .unwrap_or_default();
+
+    let routed_server = routed_server_id(request);
+    let server_id = match routed_server {
+      Some(val) => Some(val),
+      None => match headers.get_one(SERVER_HEADER) {
+        Some(val) => match val.parse::<u64>() {
+          Ok(val) => Some(val),
+          Err(_) => return bad_signature(request, "server header is invalid"),
+        },
+        None if bound_servers.len() == 1 => Some(bound_servers[0]),
+        None => None,
+      },
+    };
+    let secret = match server_id {
+      Some(server_id) => secret_of(server_id),
+      None if bound_servers.iter().any(|id| secret_of(*id).is_some()) => {
+        return bad_signature(request, "server header is required for this key");
+      }
+      None => None,
+    };
+    // A key bound to no server could otherwise skip signing by leaving out the server header
+    let any_server_signs = servers.iter().any(|server| server.signing_secret.is_some());
+    if routed_server.is_none() && bound_servers.is_empty() && any_server_signs && secret.is_none() {
+      return bad_signature(
+        request,
+        "server header must name a server with signing secret",
+      );
+    }
+
+    let (server_id, secret, signature) =
+      match (server_id, secret, headers.get_one(SIGNATURE_HEADER)) {
+        (_, None, None) => return request::Outcome::Success(SignedRequest),
+        (_, None, Some(_)) => return bad_signature(request, "server has no signing secret"),
+        (_, Some(_), None) => return bad_signature(request, "signature is missing"),
+        (Some(server_id), Some(secret), Some(signature)) => (server_id, secret, signature),
+        (None, Some(_), Some(_)) => return bad_signature(request, "server is unknown"),
+      };
+
+    let now = Utc::now().timestamp();
+    let timestamp = match headers.get_one(TIMESTAMP_HEADER) {
+      Some(val) => val,
+      None => return bad_signature(request, "timestamp is missing"),
+    };
+    match timestamp.parse::<i64>() {
+      Ok(val) if (now - val).abs() <= MAX_CLOCK_SKEW_SECS => {}
+      Ok(_) => return bad_signature(request, "timestamp is too old or in the future"),
+      Err(_) => return bad_signature(request, "timestamp is invalid"),
+    }
+    let nonce = match headers.get_one(NONCE_HEADER) {
+      Some(val) if (8..=128).contains(&val.len()) => val,
+      _ => return bad_signature(request, "nonce must be 8 to 128 characters long"),
+    };
+    let empty_body_hash = hex::encode(Sha256::digest([]));
+    let content_hash = headers
+      .get_one(CONTENT_HASH_HEADER)
+      .unwrap_or(&empty_body_hash);
+    let content_digest = match hex::decode(content_hash) {
+      Ok(val) if val.len() == 32 => val,
+      _ => return bad_signature(request, "content hash is invalid"),
+    };
+    let signature = match hex::decode(signature) {
+      Ok(val) => val,
+      Err(_) => return bad_signature(request, "signature is not hex"),
+    };
+
+    let mut mac = match HmacSha256::new_from_slice(secret.as_bytes()) {
+      Ok(val) => val,
+      Err(_) => return bad_signature(request, "server signing secret is invalid"),
+    };
+    mac.update(
+      signing_string(
+        request.method().as_str(),
+        &requested_uri(request),
+        timestamp,
+        nonce,
+        content_hash,
+      )
+      .as_bytes(),
+    );
+    if mac.verify_slice(&signature).is_err() {
+      return bad_signature(request, "signature doesn't match");
+    }
+
+    // Nonces are kept for as long as a request with them could still pass the timestamp check
+    let nonce_used = match app.databases.as_ref() {
+      Some(db_handler_lock) => db_handler_lock
+        .lock()
+        .await
+        .auth_database
+        .use_request_nonce(server_id, nonce, now - 2 * MAX_CLOCK_SKEW_SECS),
+      None => return bad_signature(request, "nonce can't be checked without database"),
+    };
+    match nonce_used {
+      Ok(true) => {}
+      Ok(false) => return bad_signature(request, "nonce was already used"),
+      Err(e) => {
+        log_w!(format!("Couldn't check request nonce: {}", e));
+        return bad_signature(request, "nonce can't be checked");
+      }
+    }
+
+    request.local_cache(|| SignedBody(Some(content_digest)));
+    request::Outcome::Success(SignedRequest)
+  }
+}
+
+/// Why [`SignedJson`] couldn't deserialize the body, reported by the `422` catcher
+#[derive(Debug, Clone)]
+pub struct BodyParseError(pub String);
+
+/// # Signed JSON body
+/// JSON data guard, that checks the body against the digest of a signed request.
+/// Works as [`rocket::serde::json::Json`] for unsigned requests
+// MARK: Signed json
+#[derive(Debug)]
+pub struct SignedJson<T>(pub T);
+
+impl<T> Deref for SignedJson<T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.0
+  }
+}
+
+#[rocket::async_trait]
+impl<'r, T: DeserializeOwned> FromData<'r> for SignedJson<T> {
+  type Error = String;
+
+  async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+    let limit = request.limits().get("json").unwrap_or(1.mebibytes());
+    let body = match data.open(limit).into_bytes().await {
+      Ok(val) if val.is_complete() => val.into_inner(),
+      Ok(_) => {
+        return data::Outcome::Error((Status::PayloadTooLarge, "Body is too large".to_string()))
+      }
+      Err(e) => return data::Outcome::Error((Status::BadRequest, e.to_string())),
+    };
+
+    if let SignedBody(Some(expected)) = request.local_cache(|| SignedBody(None)) {
+      if !bool::from(Sha256::digest(&body).as_slice().ct_eq(expected)) {
+        let reason = "body doesn't match content hash".to_string();
+        request.local_cache(|| Some(AuthError::BadSignature(reason.clone())));
+        return data::Outcome::Error((Status::Unauthorized, reason));
+      }
+    }
+
+    if let Some(status) = check_idempotent_body(request, &body) {
+      return data::Outcome::Error((status, "Idempotency key was already used".to_string()));
+    }
+
+    match serde_json::from_slice(&body) {
+      Ok(val) => data::Outcome::Success(SignedJson(val)),
+      Err(e) => {
+        request.local_cache(|| Some(BodyParseError(e.to_string())));
+        data::Outcome::Error((Status::UnprocessableEntity, e.to_string()))
+      }
+    }
+  }
+}
@@ -0,0 +1,125 @@
+use std::{net::IpAddr, str::FromStr};
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::application::{db::player::identity::PlayerIdentity, routes::errors::ApiError};
+
+/// Longest username or issuer name accepted
+pub const MAX_NAME_LENGTH: usize = 64;
+/// Longest punishment reason accepted
+pub const MAX_REASON_LENGTH: usize = 1024;
+/// Longest verification code accepted
+pub const MAX_CODE_LENGTH: usize = 64;
+
+/// # Field error
+/// Invalid field of a request body, listed in `details.fields` of `validation_failed` errors
+#[derive(Serialize, ToSchema)]
+pub struct FieldError {
+  pub field: String,
+  pub message: String,
+}
+
+/// # Validate
+/// Request bodies that are checked before they reach the database
+pub trait Validate {
+  /// Cleans up fields in place (control characters, surrounding whitespace, canonical IPs)
+  /// and reports the ones that are still invalid
+  fn validate(&mut self, fields: &mut FieldErrors);
+}
+
+/// Invalid fields found while validating a request body
+#[derive(Default)]
+pub struct FieldErrors {
+  errors: Vec<FieldError>,
+}
+
+impl FieldErrors {
+  pub fn invalid(&mut self, field: &str, message: impl Into<String>) {
+    self.errors.push(FieldError {
+      field: field.to_string(),
+      message: message.into(),
+    });
+  }
+
+  /// Strips control characters and surrounding whitespace, then checks the text isn't empty or too long
+  pub fn text(&mut self, field: &str, value: &mut String, max_length: usize) {
+    *value = sanitize(value);
+    if value.is_empty() {
+      self.invalid(field, "Must not be empty");
+    } else if value.chars().count() > max_length {
+      self.invalid(field, format!("Must be at most {} characters", max_length));
+    }
+  }
+
+  /// Like [`FieldErrors::text`], a missing value is fine
+  pub fn optional_text(&mut self, field: &str, value: &mut Option<String>, max_length: usize) {
+    if let Some(val) = value {
+      self.text(field, val, max_length);
+    }
+  }
+
+  /// Checks the value is an IPv4 or IPv6 address and stores it in canonical form,
+  /// IPv4-mapped IPv6 addresses (`::ffff:10.0.0.1`) as IPv4
+  pub fn ip(&mut self, field: &str, value: &mut String) {
+    match IpAddr::from_str(value.trim()) {
+      Ok(ip) => *value = ip.to_canonical().to_string(),
+      Err(_) => self.invalid(field, "Must be an IPv4 or IPv6 address"),
+    }
+  }
+
+  /// Checks the value is an SCP:SL user ID and stores it as `id@platform`
+  pub fn user_id(&mut self, field: &str, value: &mut String) {
+    match PlayerIdentity::from_str(value) {
+      Ok(identity) => *value = identity.user_id(),
+      Err(e) => self.invalid(field, e.to_string()),
+    }
+  }
+
+  /// Checks the value is a Discord snowflake
+  pub fn discord_id(&mut self, field: &str, value: &mut String) {
+    *value = value.trim().to_string();
+    if !(17..=20).contains(&value.len()) || !value.chars().all(|c| c.is_ascii_digit()) {
+      self.invalid(field, "Must be a Discord user ID of 17 to 20 digits");
+    }
+  }
+
+  pub fn into_result(self) -> Result<(), ApiError> {
+    if self.errors.is_empty() {
+      return Ok(());
+    }
+    Err(ApiError::invalid_fields(self.errors))
+  }
+}
+
+/// Validates an IP path parameter, `422` names the parameter
+pub fn ip_param(field: &str, mut value: String) -> Result<String, ApiError> {
+  let mut fields = FieldErrors::default();
+  fields.ip(field, &mut value);
+  fields.into_result().map(|_| value)
+}
+
+/// Validates a Discord ID path parameter, `422` names the parameter
+pub fn discord_id_param(field: &str, value: &str) -> Result<String, ApiError> {
+  let mut value = value.to_string();
+  let mut fields = FieldErrors::default();
+  fields.discord_id(field, &mut value);
+  fields.into_result().map(|_| value)
+}
+
+/// Validates the request body, `422` lists every invalid field
+pub fn validated<T: Validate>(mut value: T) -> Result<T, ApiError> {
+  let mut fields = FieldErrors::default();
+  value.validate(&mut fields);
+  fields.into_result().map(|_| value)
+}
+
+/// Text without control characters (newlines, escapes, NUL, ...) and surrounding whitespace
+fn sanitize(value: &str) -> String {
+  value
+    .chars()
+    .filter(|c| !c.is_control())
+    .collect::<String>()
+    .trim()
+    .to_string()
+}
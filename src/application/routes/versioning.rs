@@ -0,0 +1,319 @@
+use std::io::Cursor;
+
+use rocket::{
+  fairing::{Fairing, Info, Kind},
+  http::{uri::Origin, Header, Method, Status},
+  serde::json::Json,
+  Data, Request, Response,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::application::application::Application;
+
+pub const CURRENT_API_VERSION: &str = "v1";
+
+/// Unversioned routes from before `/api/v1`, `<name>` segments are carried over to the new path
+/// (method, legacy path, current path)
+const LEGACY_ROUTES: &[(Method, &str, &str)] = &[
+  (
+    Method::Post,
+    "/db/player/join/<server_id>",
+    "/api/v1/servers/<server_id>/joins",
+  ),
+  (Method::Get, "/db/player/get/all", "/api/v1/players"),
+  (
+    Method::Get,
+    "/db/player/get/id/<player_id>",
+    "/api/v1/players/<player_id>",
+  ),
+  (
+    Method::Get,
+    "/db/player/get/steam/<user_id>",
+    "/api/v1/players/by-user/<user_id>",
+  ),
+  (
+    Method::Get,
+    "/db/player/get/discord/<discord_id>",
+    "/api/v1/players/by-discord/<discord_id>",
+  ),
+  (
+    Method::Post,
+    "/db/player/punishment/add/<player_id>",
+    "/api/v1/players/<player_id>/punishments",
+  ),
+  (Method::Get, "/db/player/count", "/api/v1/player-counts"),
+  (Method::Post, "/db/player/count", "/api/v1/player-counts"),
+  (
+    Method::Get,
+    "/db/player/verification/get/id/<player_id>",
+    "/api/v1/players/<player_id>/verification",
+  ),
+  (
+    Method::Get,
+    "/db/player/verification/get/steam/<user_id>",
+    "/api/v1/verifications/by-user/<user_id>",
+  ),
+  (
+    Method::Get,
+    "/db/player/verification/get/discord/<discord_id>",
+    "/api/v1/verifications/by-discord/<discord_id>",
+  ),
+  (
+    Method::Get,
+    "/db/player/verification/get/code/<code>",
+    "/api/v1/verifications/by-code/<code>",
+  ),
+  (
+    Method::Post,
+    "/db/player/verification/add",
+    "/api/v1/verifications",
+  ),
+  (
+    Method::Post,
+    "/db/player/verification/update",
+    "/api/v1/verifications/verify",
+  ),
+  (
+    Method::Post,
+    "/db/player/verification/set",
+    "/api/v1/verifications/status",
+  ),
+  (
+    Method::Get,
+    "/db/player/verification/reviews",
+    "/api/v1/verifications/reviews",
+  ),
+  (
+    Method::Get,
+    "/db/player/links/<discord_id>",
+    "/api/v1/links/<discord_id>",
+  ),
+  (
+    Method::Post,
+    "/db/player/links/primary",
+    "/api/v1/links/primary",
+  ),
+  (
+    Method::Post,
+    "/db/player/links/unlink",
+    "/api/v1/links/unlink",
+  ),
+  (Method::Get, "/db/punishments", "/api/v1/punishments"),
+  (
+    Method::Get,
+    "/db/punishments/get/<punishment_id>",
+    "/api/v1/punishments/<punishment_id>",
+  ),
+  (
+    Method::Get,
+    "/db/punishments/get/id/<player_id>",
+    "/api/v1/players/<player_id>/punishments",
+  ),
+  (
+    Method::Get,
+    "/db/punishments/get/steam/<user_id>",
+    "/api/v1/punishments/by-user/<user_id>",
+  ),
+  (
+    Method::Get,
+    "/db/punishments/get/ip/<ip>",
+    "/api/v1/punishments/by-ip/<ip>",
+  ),
+  (Method::Get, "/db/leaderboards/get", "/api/v1/leaderboards"),
+  (Method::Post, "/db/leaderboards/add", "/api/v1/leaderboards"),
+  (
+    Method::Patch,
+    "/db/leaderboards/clear",
+    "/api/v1/leaderboards/clear",
+  ),
+  (
+    Method::Patch,
+    "/db/leaderboards/remove",
+    "/api/v1/leaderboards/remove",
+  ),
+  (
+    Method::Post,
+    "/server/<server_id>/status",
+    "/api/v1/servers/<server_id>/status",
+  ),
+  (
+    Method::Post,
+    "/logs/<server_id>/<translation>",
+    "/api/v1/servers/<server_id>/logs/<translation>",
+  ),
+  (
+    Method::Get,
+    "/ws/<server_id>/connect",
+    "/api/v1/servers/<server_id>/ws",
+  ),
+];
+
+/// Statuses legacy routes answered with where their current route answers differently
+/// (method, legacy path, current status, legacy status)
+const LEGACY_STATUSES: &[(Method, &str, u16, u16)] = &[
+  (Method::Get, "/db/player/get/id/<player_id>", 404, 500),
+  (
+    Method::Post,
+    "/db/player/punishment/add/<player_id>",
+    404,
+    417,
+  ),
+  (
+    Method::Post,
+    "/db/player/punishment/add/<player_id>",
+    409,
+    304,
+  ),
+  (Method::Post, "/db/player/verification/add", 404, 500),
+  (Method::Post, "/db/player/verification/add", 409, 500),
+  (Method::Post, "/db/player/verification/add", 422, 400),
+  (Method::Post, "/db/player/verification/update", 409, 208),
+  (Method::Post, "/db/player/verification/update", 423, 403),
+  (Method::Post, "/db/player/verification/update", 422, 400),
+  (Method::Post, "/db/player/verification/set", 404, 500),
+  (Method::Get, "/db/punishments/get/<punishment_id>", 404, 500),
+  (Method::Get, "/db/leaderboards/get", 503, 204),
+  (Method::Post, "/db/leaderboards/add", 503, 204),
+  (Method::Patch, "/db/leaderboards/clear", 503, 204),
+  (Method::Patch, "/db/leaderboards/remove", 503, 204),
+];
+
+/// Requested uri, current path and legacy path of the legacy route the request was made to
+struct LegacyAlias(Option<(String, String, &'static str)>);
+
+/// Uri (path and query) as sent by the client, before legacy routes were rewritten
+pub(crate) fn requested_uri(request: &Request<'_>) -> String {
+  match request.local_cache(|| LegacyAlias(None)) {
+    LegacyAlias(Some((requested, _, _))) => requested.clone(),
+    LegacyAlias(None) => request.uri().to_string(),
+  }
+}
+
+/// Returns the legacy path matching the request path and the current path, with captured segments filled in
+fn resolve_legacy_path(method: Method, segments: &[&str]) -> Option<(&'static str, String)> {
+  'routes: for (route_method, legacy, current) in LEGACY_ROUTES {
+    if *route_method != method {
+      continue;
+    }
+    let pattern: Vec<&str> = legacy.trim_start_matches('/').split('/').collect();
+    if pattern.len() != segments.len() {
+      continue;
+    }
+
+    let mut path = current.to_string();
+    for (expected, segment) in pattern.iter().zip(segments) {
+      if expected.starts_with('<') {
+        path = path.replace(expected, segment);
+      } else if expected != segment {
+        continue 'routes;
+      }
+    }
+    return Some((legacy, path));
+  }
+  None
+}
+
+/// # Legacy routes
+/// Rewrites requests to unversioned routes onto `/api/v1` and marks their responses with `Deprecation` header
+// MARK: Legacy routes fairing
+pub(crate) struct LegacyRoutes;
+
+#[rocket::async_trait]
+impl Fairing for LegacyRoutes {
+  fn info(&self) -> Info {
+    Info {
+      name: "Legacy route aliases",
+      kind: Kind::Request | Kind::Response,
+    }
+  }
+
+  async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data<'_>) {
+    let segments: Vec<&str> = request
+      .uri()
+      .path()
+      .raw_segments()
+      .map(|segment| segment.as_str())
+      .collect();
+    let Some((legacy_path, current_path)) = resolve_legacy_path(request.method(), &segments) else {
+      return;
+    };
+
+    let uri = match request.uri().query() {
+      Some(query) => format!("{}?{}", current_path, query),
+      None => current_path.clone(),
+    };
+    let requested = request.uri().to_string();
+    if let Ok(origin) = Origin::parse_owned(uri) {
+      request.set_uri(origin);
+      request.local_cache(|| LegacyAlias(Some((requested, current_path, legacy_path))));
+    }
+  }
+
+  async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+    let LegacyAlias(Some((_, current_path, legacy_path))) =
+      request.local_cache(|| LegacyAlias(None))
+    else {
+      return;
+    };
+    response.set_header(Header::new("Deprecation", "true"));
+    response.set_header(Header::new(
+      "Link",
+      format!("<{}>; rel=\"successor-version\"", current_path),
+    ));
+
+    // Plugins written for legacy routes branch on their statuses, errors had no body
+    let legacy_status = LEGACY_STATUSES
+      .iter()
+      .find(|(method, path, status, _)| {
+        *method == request.method() && path == legacy_path && *status == response.status().code
+      })
+      .map(|(_, _, _, legacy_status)| Status::new(*legacy_status));
+    if let Some(status) = legacy_status {
+      response.set_status(status);
+    }
+    if legacy_status.is_some() || response.status().code >= 400 {
+      response.remove_header("Content-Type");
+      response.set_sized_body(0, Cursor::new(Vec::new()));
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiVersion {
+  pub version: String,
+  pub path: String,
+  pub deprecated: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiVersions {
+  pub current: String,
+  pub server_version: String,
+  pub versions: Vec<ApiVersion>,
+}
+
+#[utoipa::path(
+  tag = "Meta",
+  responses((status = 200, description = "Available API versions", body = ApiVersions))
+)]
+#[get("/api")]
+// MARK: Api versions
+pub async fn api_versions() -> Json<ApiVersions> {
+  Json(ApiVersions {
+    current: CURRENT_API_VERSION.to_string(),
+    server_version: Application::get_version(),
+    versions: vec![
+      ApiVersion {
+        version: CURRENT_API_VERSION.to_string(),
+        path: format!("/api/{}", CURRENT_API_VERSION),
+        deprecated: false,
+      },
+      ApiVersion {
+        version: "legacy".to_string(),
+        path: "/".to_string(),
+        deprecated: true,
+      },
+    ],
+  })
+}
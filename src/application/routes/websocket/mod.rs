@@ -14,16 +14,16 @@ use std::{
 
 use rocket::{
   futures::{SinkExt as _, StreamExt as _},
-  http::Status,
-  request::{self, FromRequest},
-  Request, State,
+  State,
 };
 use tokio::{sync::Mutex, time::interval};
 
 use crate::{
   application::{
     application::Application,
-    config::config::ConfigApp,
+    features::metrics::METRICS,
+    routes::auth::{scopes, ApiAuth},
+    routes::messages::ApiErrorBody,
     routes::websocket::structs::{
       WebsocketIncomingMessage, WebsocketRoute, WsMessageResponse, WsReponseStatus,
       WsResponseCreate as _, WsResponses,
@@ -33,73 +33,34 @@ use crate::{
   logger::{LogLevel, Logger},
 };
 
-pub struct WebsocketAuthHeader(String);
-
-#[derive(Debug)]
-pub enum WebsocketAuthError {
-  Missing,
-  Invalid,
-}
-
-// MARK: Authorization
-#[rocket::async_trait]
-impl<'r> FromRequest<'r> for WebsocketAuthHeader {
-  type Error = WebsocketAuthError;
-
-  async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
-    // Access the managed state
-    let managed_state = request.guard::<&State<Arc<Mutex<Application>>>>().await;
-
-    // Check if the managed state is available
-    match managed_state {
-      request::Outcome::Success(state) => {
-        let app = state.lock().await;
-
-        match request.cookies().get("authorization") {
-          Some(auth_cookie) => {
-            let auth_token = auth_cookie.value().to_string();
-            if check_ws_auth(auth_token.clone(), app.config.as_ref().unwrap()) {
-              return request::Outcome::Success(WebsocketAuthHeader(auth_token));
-            } else {
-              return request::Outcome::Error((Status::Unauthorized, WebsocketAuthError::Invalid));
-            }
-          }
-          None => {
-            if let Some(auth_header) = request.headers().get_one("Authorization") {
-              if check_ws_auth(auth_header.to_string(), app.config.as_ref().unwrap()) {
-                return request::Outcome::Success(WebsocketAuthHeader(auth_header.to_string()));
-              } else {
-                return request::Outcome::Error((
-                  Status::Unauthorized,
-                  WebsocketAuthError::Invalid,
-                ));
-              }
-            } else {
-              return request::Outcome::Error((Status::Unauthorized, WebsocketAuthError::Missing));
-            }
-          }
-        }
-      }
-      request::Outcome::Error(_) | request::Outcome::Forward(_) => {
-        // Handle the case where the managed state is not available
-        request::Outcome::Error((Status::InternalServerError, WebsocketAuthError::Missing))
-      }
+/// Counts the connection on the server, so its status shows whether the websocket is connected
+async fn track_connection(app: &Arc<Mutex<Application>>, server_id: u64, connected: bool) {
+  let app = app.lock().await;
+  if let Ok(server_lock) = app.try_get_server(server_id).await {
+    let mut server = server_lock.lock().await;
+    if connected {
+      server.ws_connections += 1;
+      METRICS.websocket_opened(server_id);
+    } else {
+      server.ws_connections = server.ws_connections.saturating_sub(1);
+      METRICS.websocket_closed(server_id);
     }
   }
 }
 
-pub fn check_ws_auth(token: String, config: &ConfigApp) -> bool {
-  let log_token = &config.auth.ws;
-  if token == *log_token {
-    return true;
-  }
-  return false;
-}
-
 // MARK: Websocket connection
-#[get("/ws/<server_id>/connect")]
+#[utoipa::path(
+  tag = "Websocket",
+  responses(
+    (status = 101, description = "Switched to the websocket protocol"),
+    (status = "4XX", response = ApiErrorBody),
+    (status = "5XX", response = ApiErrorBody),
+  ),
+  security(("api_key" = ["ws:connect"]))
+)]
+#[get("/api/v1/servers/<server_id>/ws")]
 pub fn websocket_connect(
-  _auth_header: WebsocketAuthHeader,
+  _auth: ApiAuth<scopes::WsConnect>,
   ws: WebSocket,
   server_id: u64,
   app_data: &State<Arc<Mutex<Application>>>,
@@ -113,6 +74,7 @@ pub fn websocket_connect(
 
   ws.channel(move |stream| {
     Box::pin(async move {
+      track_connection(&app_arc, server_id, true).await;
       let (mut sink, mut stream) = stream.split();
       let mut interval = interval(Duration::from_secs(1));
       let messages_to_send: Arc<Mutex<VecDeque<WsResponses>>> =
@@ -259,6 +221,7 @@ pub fn websocket_connect(
           }
         }
       }
+      track_connection(&app_arc, server_id, false).await;
       Ok(())
     })
   })
@@ -12,7 +12,7 @@ use super::status::DistrictServerStatus;
 use crate::{
   application::{
     application::Application, bot::bot::DistrictBot, config::server::server_config::ConfigServer,
-    routes::websocket::structs::WsResponses,
+    features::metrics::METRICS, routes::websocket::structs::WsResponses,
   },
   log_d, log_x,
   logger::{LogLevel, Logger},
@@ -27,6 +27,10 @@ pub struct DistrictServer {
   pub(crate) channel_id: String,
   pub status: DistrictServerStatus,
   pub(crate) ws_msgs: VecDeque<WsResponses>,
+  /// Open websocket connections of the game server
+  pub(crate) ws_connections: usize,
+  /// When the status was last persisted, see [`crate::application::features::uptime`]
+  pub(crate) last_status_sample: Option<i64>,
   buffer: VecDeque<String>,
   last_sent: Instant,
   pub(crate) srv_cfg: ConfigServer,
@@ -43,6 +47,8 @@ impl DistrictServer {
       channel_id: srv_cfg.channel_id,
       status: DistrictServerStatus::new(),
       ws_msgs: VecDeque::new(),
+      ws_connections: 0,
+      last_status_sample: None,
       buffer: VecDeque::new(),
       last_sent: Instant::now(),
       srv_cfg: cfg_clone,
@@ -55,7 +61,12 @@ impl DistrictServer {
     let server_arc: Arc<Mutex<Self>> = Arc::new(Mutex::new(server));
     {
       let mut server_locked = server_arc.lock().await;
-      let mut bot = DistrictBot::new(srv_cfg.bot, Some(Arc::clone(&server_arc)), None);
+      let mut bot = DistrictBot::new(
+        srv_cfg.bot,
+        Arc::clone(&server_locked.app),
+        Some(Arc::clone(&server_arc)),
+        None,
+      );
       bot.spawn(server_locked.name.clone()).await;
       server_locked.bot = Some(bot);
     }
@@ -63,6 +74,11 @@ impl DistrictServer {
     server_arc
   }
 
+  /// Messages waiting to be sent to the Discord channel
+  pub fn buffered_messages(&self) -> usize {
+    self.buffer.len()
+  }
+
   pub async fn try_clear_buffer(&mut self) {
     if self.buffer.is_empty() {
       return;
@@ -148,7 +164,10 @@ impl DistrictServer {
           .http
           .send_message(channel_id, vec![], &message_content)
           .await
-          .map_err(|e| format!("Discord Error {}", e))?;
+          .map_err(|e| {
+            METRICS.discord_send_failed(self.id);
+            format!("Discord Error {}", e)
+          })?;
       }
     }
 
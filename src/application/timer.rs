@@ -1,3 +1,4 @@
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -10,13 +11,20 @@ use crate::logger::{LogLevel, Logger};
 use crate::{log_d, log_x};
 
 use super::application::Application;
+use super::features::uptime::{publish_transition, record_timeout, INCIDENT_TIMED_OUT};
+use super::features::verification::roles::reconcile_roles;
+use super::features::webhooks::DELIVERY_RETENTION_DAYS;
 
 pub async fn timer_loop(app: Arc<Mutex<Application>>) -> Result<(), String> {
   log_d!("Starting timer loop!");
+  let last_tick = Arc::clone(&app.lock().await.timer_last_tick);
   let mut timer_span: u64 = 0;
   loop {
     timer_span += 1;
+    last_tick.store(Utc::now().timestamp(), Ordering::Relaxed);
     if timer_span % 20 == 0 {
+      let databases = app.lock().await.databases.clone();
+      let events = Arc::clone(&app.lock().await.events);
       for server_lock in app.lock().await.servers.iter_mut() {
         let mut server = server_lock.lock().await;
         let mut presence_data: Option<PresenceData> = None;
@@ -37,8 +45,13 @@ pub async fn timer_loop(app: Arc<Mutex<Application>>) -> Result<(), String> {
                 "Server {} did not reply in the last 1000 seconds, disconnecting server",
                 server.id
               ));
+              if let Some(db_lock) = databases.as_ref() {
+                let db_handler = db_lock.lock().await;
+                record_timeout(&db_handler.status_database, &server, last_heard);
+              }
               server.status.open = false;
               server.status.last_heard = None;
+              publish_transition(&events, &server, Some(INCIDENT_TIMED_OUT));
             }
           }
 
@@ -93,10 +106,41 @@ pub async fn timer_loop(app: Arc<Mutex<Application>>) -> Result<(), String> {
               .player_database
               .remove_inactive_players(normal_clear, false);
           }
+          let _ = db_handler.webhook_database.remove_deliveries_before(
+            Utc::now().timestamp() - DELIVERY_RETENTION_DAYS * 24 * 3600,
+          );
+          let audit_retention_days = config.audit.as_ref().map_or(0, |cfg| cfg.retention_days);
+          if audit_retention_days != 0 {
+            let _ = db_handler.audit_database.remove_entries_before(
+              Utc::now().timestamp() - audit_retention_days as i64 * 24 * 3600,
+            );
+          }
+          let idempotency_window_hours = config.idempotency.clone().unwrap_or_default().window_hours;
+          let _ = db_handler.auth_database.remove_idempotency_keys_before(
+            Utc::now().timestamp() - idempotency_window_hours as i64 * 3600,
+          );
+          let status_retention_days = config
+            .status_history
+            .clone()
+            .unwrap_or_default()
+            .retention_days;
+          if status_retention_days != 0 {
+            let _ = db_handler.status_database.remove_history_before(
+              Utc::now().timestamp() - status_retention_days as i64 * 24 * 3600,
+            );
+          }
         }
       }
     }
 
+    if timer_span % 3600 == 1800 {
+      // Fix drift of verification roles (manual changes, missed events, ...)
+      let cloned_arc = Arc::clone(&app);
+      tokio::spawn(async move {
+        reconcile_roles(&cloned_arc).await;
+      });
+    }
+
     sleep(Duration::from_secs(1)).await;
   }
 }
@@ -0,0 +1,55 @@
+use rand::RngCore as _;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq as _;
+
+const HASH_SCHEME: &str = "sha256";
+const KEY_PREFIX: &str = "dk_";
+
+fn digest(salt: &[u8], key: &str) -> Vec<u8> {
+  let mut hasher = Sha256::new();
+  hasher.update(salt);
+  hasher.update(key.as_bytes());
+  hasher.finalize().to_vec()
+}
+
+/// Generates a new random API key secret
+pub fn generate_key() -> String {
+  let mut secret = [0u8; 32];
+  rand::thread_rng().fill_bytes(&mut secret);
+  format!("{}{}", KEY_PREFIX, hex::encode(secret))
+}
+
+/// Hashes the key with a random salt.
+///
+/// # Returns
+///
+/// * `String` - The salted hash in `sha256$<salt>$<hash>` format, as stored in the config or database.
+pub fn hash_key(key: &str) -> String {
+  let mut salt = [0u8; 16];
+  rand::thread_rng().fill_bytes(&mut salt);
+  format!(
+    "{}${}${}",
+    HASH_SCHEME,
+    hex::encode(salt),
+    hex::encode(digest(&salt, key))
+  )
+}
+
+/// Checks the key against a hash made by `hash_key`, the hashes are compared in constant time.
+/// Returns false if the hash is malformed.
+pub fn verify_key_hash(key: &str, key_hash: &str) -> bool {
+  let mut parts = key_hash.splitn(3, '$');
+  let (Some(HASH_SCHEME), Some(salt), Some(hash)) = (parts.next(), parts.next(), parts.next())
+  else {
+    return false;
+  };
+  let (Ok(salt), Ok(hash)) = (hex::decode(salt), hex::decode(hash)) else {
+    return false;
+  };
+  digest(&salt, key).ct_eq(&hash).into()
+}
+
+/// Compares two plaintext keys in constant time, regardless of their length
+pub fn keys_equal(a: &str, b: &str) -> bool {
+  digest(&[], a).ct_eq(&digest(&[], b)).into()
+}
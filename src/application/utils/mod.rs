@@ -1,2 +1,3 @@
+pub(crate) mod api_key;
 pub(crate) mod json;
 pub(crate) mod time;
@@ -4,7 +4,7 @@ pub mod logger;
 #[macro_use]
 extern crate rocket;
 
-use rocket::{figment::Figment, Route};
+use rocket::{figment::Figment, Catcher, Route};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -21,10 +21,23 @@ async fn setup_app() -> Arc<Mutex<Application>> {
   out
 }
 
+/// `district_server mint-key` prints a new API key with the hash to put into `auth.keys[].key_hash`
+fn run_cli() {
+  if std::env::args().nth(1).as_deref() == Some("mint-key") {
+    let key = application::utils::api_key::generate_key();
+    println!("key:      {}", key);
+    println!("key_hash: {}", application::utils::api_key::hash_key(&key));
+    println!("The key is not stored anywhere, keep it now.");
+    std::process::exit(0);
+  }
+}
+
 #[launch]
 async fn rocket() -> _ {
+  run_cli();
   let app_lock = setup_app().await;
   let routes: Vec<Route>;
+  let catchers: Vec<Catcher>;
   let figment: Figment;
 
   {
@@ -50,8 +63,11 @@ async fn rocket() -> _ {
       .init();
 
     log_i!("Starting rocket server");
-    routes = match app.router.as_ref() {
-      Some(router) => router.get_routes().to_vec(),
+    (routes, catchers) = match app.router.as_ref() {
+      Some(router) => (
+        router.get_routes().to_vec(),
+        router.get_catchers().to_vec(),
+      ),
       None => {
         log_e!("Router not found");
         panic!("Router not found");
@@ -67,5 +83,9 @@ async fn rocket() -> _ {
   rocket::custom(figment)
     .mount("/", routes)
     .manage(app_lock)
-    .register("/", catchers![/* To be added (TODO: HTTP catchers)*/])
+    .register("/", catchers)
+    .attach(application::routes::versioning::LegacyRoutes)
+    .attach(application::routes::idempotency::IdempotencyResponses)
+    .attach(application::routes::metrics::RequestMetrics)
+    .attach(application::routes::PublicApiHeaders)
 }